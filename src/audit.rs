@@ -0,0 +1,319 @@
+use chrono::{DateTime, NaiveDate};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::future::Future;
+
+use crate::cache::{AuditEntry, AuditOutcome, AuditRecord};
+use crate::error::{McpError, McpResult};
+
+/// Payload fields that carry message or profile content and are never stored
+const REDACTED_FIELDS: &[&str] = &["text", "blocks", "attachments", "profile", "value"];
+
+tokio::task_local! {
+    static CONTEXT: AuditContext;
+}
+
+/// Who triggered the Slack calls made while a tool runs
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+    pub client: Option<String>,
+    pub tool: Option<String>,
+}
+
+impl AuditContext {
+    /// Run `fut` with this context attached to every audited call it makes
+    pub async fn scope<F: Future>(self, fut: F) -> F::Output {
+        CONTEXT.scope(self, fut).await
+    }
+
+    /// Context of the running tool call, or an empty one outside of a tool call
+    pub fn current() -> Self {
+        CONTEXT.try_with(Clone::clone).unwrap_or_default()
+    }
+}
+
+/// Build the audit entry for a finished write call
+pub fn entry(method: &str, payload: &Value, result: &anyhow::Result<Value>) -> AuditEntry {
+    let context = AuditContext::current();
+    let (response_ts, outcome, error) = match result {
+        Ok(response) => (
+            response
+                .get("ts")
+                .or_else(|| response.get("scheduled_message_id"))
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            AuditOutcome::Ok,
+            None,
+        ),
+        Err(e) => (None, AuditOutcome::Error, Some(e.to_string())),
+    };
+
+    AuditEntry {
+        client: context.client,
+        tool: context.tool,
+        method: method.to_string(),
+        channel: payload
+            .get("channel")
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+        payload_hash: payload_hash(payload),
+        payload: redact_payload(payload),
+        response_ts,
+        outcome,
+        error,
+    }
+}
+
+/// Hex SHA-256 of the payload's JSON (object keys are sorted, so equal payloads hash equally)
+pub fn payload_hash(payload: &Value) -> String {
+    let digest = Sha256::digest(payload.to_string().as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Replace message and profile content with a size summary, keeping routing fields
+pub fn redact_payload(payload: &Value) -> Value {
+    let Some(object) = payload.as_object() else {
+        return payload.clone();
+    };
+
+    let redacted: Map<String, Value> = object
+        .iter()
+        .map(|(key, value)| {
+            let value = if REDACTED_FIELDS.contains(&key.as_str()) {
+                Value::String(redaction_summary(value))
+            } else {
+                value.clone()
+            };
+            (key.clone(), value)
+        })
+        .collect();
+    Value::Object(redacted)
+}
+
+fn redaction_summary(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("[redacted: {} chars]", s.chars().count()),
+        Value::Array(items) => format!("[redacted: {} items]", items.len()),
+        Value::Object(fields) => format!("[redacted: {} fields]", fields.len()),
+        _ => "[redacted]".to_string(),
+    }
+}
+
+/// Parse a `since` filter: RFC 3339, a `YYYY-MM-DD` date (UTC) or Unix seconds
+pub fn parse_since(value: &str) -> McpResult<i64> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<i64>() {
+        return Ok(seconds);
+    }
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime.timestamp());
+    }
+    if let Some(datetime) = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+    {
+        return Ok(datetime.and_utc().timestamp());
+    }
+    Err(McpError::InvalidParameter(format!(
+        "Invalid since '{}': expected RFC 3339, YYYY-MM-DD or Unix seconds",
+        value
+    )))
+}
+
+/// Export formats for the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Jsonl,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> McpResult<Self> {
+        match value {
+            "jsonl" => Ok(ExportFormat::Jsonl),
+            "csv" => Ok(ExportFormat::Csv),
+            other => Err(McpError::InvalidParameter(format!(
+                "Unknown export format '{}': expected jsonl or csv",
+                other
+            ))),
+        }
+    }
+}
+
+const CSV_COLUMNS: &[&str] = &[
+    "id",
+    "created_at",
+    "client",
+    "tool",
+    "method",
+    "channel",
+    "outcome",
+    "response_ts",
+    "error",
+    "payload_hash",
+    "payload",
+];
+
+/// Render audit records as JSON Lines or CSV
+pub fn export(records: &[AuditRecord], format: ExportFormat) -> McpResult<String> {
+    let mut out = String::new();
+    match format {
+        ExportFormat::Jsonl => {
+            for record in records {
+                out.push_str(&serde_json::to_string(record)?);
+                out.push('\n');
+            }
+        }
+        ExportFormat::Csv => {
+            out.push_str(&CSV_COLUMNS.join(","));
+            out.push('\n');
+            for record in records {
+                let entry = &record.entry;
+                let fields = [
+                    record.id.to_string(),
+                    record.created_at.clone(),
+                    entry.client.clone().unwrap_or_default(),
+                    entry.tool.clone().unwrap_or_default(),
+                    entry.method.clone(),
+                    entry.channel.clone().unwrap_or_default(),
+                    entry.outcome.as_str().to_string(),
+                    entry.response_ts.clone().unwrap_or_default(),
+                    entry.error.clone().unwrap_or_default(),
+                    entry.payload_hash.clone(),
+                    entry.payload.to_string(),
+                ];
+                let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+                out.push_str(&row.join(","));
+                out.push('\n');
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    fn record() -> AuditRecord {
+        let payload = json!({"channel": "C123", "text": "hello, \"world\""});
+        let mut entry = entry(
+            "chat.postMessage",
+            &payload,
+            &Ok(json!({"ok": true, "ts": "1700000000.000100"})),
+        );
+        entry.tool = Some("send_message".to_string());
+        AuditRecord {
+            id: 7,
+            created_at: "2023-11-14T22:13:20+00:00".to_string(),
+            entry,
+        }
+    }
+
+    #[test]
+    fn test_redact_payload_keeps_routing_fields() {
+        let payload = json!({
+            "channel": "C123",
+            "thread_ts": "1700000000.000100",
+            "text": "secret plan",
+            "blocks": [{"type": "section"}, {"type": "divider"}],
+            "profile": {"status_text": "away", "status_emoji": ":palm_tree:"},
+        });
+
+        let redacted = redact_payload(&payload);
+
+        assert_eq!(redacted["channel"], "C123");
+        assert_eq!(redacted["thread_ts"], "1700000000.000100");
+        assert_eq!(redacted["text"], "[redacted: 11 chars]");
+        assert_eq!(redacted["blocks"], "[redacted: 2 items]");
+        assert_eq!(redacted["profile"], "[redacted: 2 fields]");
+    }
+
+    #[test]
+    fn test_payload_hash_is_stable_and_content_sensitive() {
+        let a = json!({"channel": "C1", "text": "hi"});
+        let b = json!({"text": "hi", "channel": "C1"});
+        let c = json!({"channel": "C1", "text": "hello"});
+
+        assert_eq!(payload_hash(&a), payload_hash(&b));
+        assert_ne!(payload_hash(&a), payload_hash(&c));
+        assert_eq!(payload_hash(&a).len(), 64);
+    }
+
+    #[tokio::test]
+    async fn test_entry_uses_scoped_context() {
+        let context = AuditContext {
+            client: Some("claude-desktop".to_string()),
+            tool: Some("add_reaction".to_string()),
+        };
+        let payload = json!({"channel": "C123", "name": "eyes", "timestamp": "1.2"});
+
+        let ok = context
+            .scope(async { entry("reactions.add", &payload, &Ok(json!({"ok": true}))) })
+            .await;
+        assert_eq!(ok.client.as_deref(), Some("claude-desktop"));
+        assert_eq!(ok.tool.as_deref(), Some("add_reaction"));
+        assert_eq!(ok.channel.as_deref(), Some("C123"));
+        assert_eq!(ok.outcome, AuditOutcome::Ok);
+        assert!(ok.response_ts.is_none());
+
+        let failed = entry(
+            "reactions.add",
+            &payload,
+            &Err(anyhow::anyhow!("Slack API error: already_reacted")),
+        );
+        assert!(failed.client.is_none());
+        assert_eq!(failed.outcome, AuditOutcome::Error);
+        assert_eq!(
+            failed.error.as_deref(),
+            Some("Slack API error: already_reacted")
+        );
+    }
+
+    #[rstest]
+    #[case("1700000000", 1_700_000_000)]
+    #[case("2023-11-14T22:13:20Z", 1_700_000_000)]
+    #[case("2023-11-15T07:13:20+09:00", 1_700_000_000)]
+    #[case("2023-11-14", 1_699_920_000)]
+    fn test_parse_since(#[case] input: &str, #[case] expected: i64) {
+        assert_eq!(parse_since(input).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_since_rejects_garbage() {
+        assert!(parse_since("last tuesday").is_err());
+    }
+
+    #[test]
+    fn test_export_jsonl() {
+        let out = export(&[record()], ExportFormat::Jsonl).unwrap();
+        let line: Value = serde_json::from_str(out.trim_end()).unwrap();
+
+        assert_eq!(line["id"], 7);
+        assert_eq!(line["method"], "chat.postMessage");
+        assert_eq!(line["response_ts"], "1700000000.000100");
+        assert_eq!(line["payload"]["text"], "[redacted: 14 chars]");
+    }
+
+    #[test]
+    fn test_export_csv_quotes_fields() {
+        let out = export(&[record()], ExportFormat::Csv).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+
+        assert_eq!(lines[0], CSV_COLUMNS.join(","));
+        assert!(lines[1].starts_with("7,2023-11-14T22:13:20+00:00,,send_message,"));
+        assert!(
+            lines[1].ends_with(r#","{""channel"":""C123"",""text"":""[redacted: 14 chars]""}""#)
+        );
+    }
+}
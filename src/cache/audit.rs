@@ -0,0 +1,263 @@
+use chrono::DateTime;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{Row, params, params_from_iter};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::error::CacheResult;
+use super::sqlite_cache::SqliteCache;
+
+/// Whether an audited Slack call succeeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Ok,
+    Error,
+}
+
+impl AuditOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditOutcome::Ok => "ok",
+            AuditOutcome::Error => "error",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "ok" => AuditOutcome::Ok,
+            _ => AuditOutcome::Error,
+        }
+    }
+}
+
+/// A write call made to Slack, as stored in the audit log
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    /// MCP client name from the initialize handshake
+    pub client: Option<String>,
+    pub tool: Option<String>,
+    /// Slack API method, e.g. "chat.postMessage"
+    pub method: String,
+    pub channel: Option<String>,
+    /// SHA-256 of the full request payload
+    pub payload_hash: String,
+    /// Request payload with message content redacted
+    pub payload: Value,
+    pub response_ts: Option<String>,
+    pub outcome: AuditOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRecord {
+    pub id: i64,
+    /// RFC 3339 time the call finished
+    pub created_at: String,
+    #[serde(flatten)]
+    pub entry: AuditEntry,
+}
+
+/// Filters for reading the audit log
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    /// Maximum number of records; `None` returns all
+    pub limit: Option<usize>,
+    /// Only records at or after this Unix time
+    pub since: Option<i64>,
+    pub tool: Option<String>,
+    pub channel: Option<String>,
+    pub outcome: Option<AuditOutcome>,
+    /// Oldest first instead of newest first
+    pub oldest_first: bool,
+}
+
+impl SqliteCache {
+    /// Append an entry to the audit log
+    pub fn record_audit(&self, entry: &AuditEntry) -> CacheResult<i64> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "INSERT INTO audit_log
+                (client, tool, method, channel, payload_hash, payload, response_ts, outcome, error)
+             VALUES (?, ?, ?, ?, ?, json(?), ?, ?, ?)",
+            params![
+                entry.client,
+                entry.tool,
+                entry.method,
+                entry.channel,
+                entry.payload_hash,
+                serde_json::to_string(&entry.payload)?,
+                entry.response_ts,
+                entry.outcome.as_str(),
+                entry.error,
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Read audit log records matching `query`
+    pub fn list_audit_log(&self, query: &AuditQuery) -> CacheResult<Vec<AuditRecord>> {
+        let conn = self.pool.get()?;
+
+        let mut conditions = Vec::new();
+        let mut values: Vec<SqlValue> = Vec::new();
+        if let Some(since) = query.since {
+            conditions.push("created_at >= ?");
+            values.push(SqlValue::Integer(since));
+        }
+        if let Some(tool) = &query.tool {
+            conditions.push("tool = ?");
+            values.push(SqlValue::Text(tool.clone()));
+        }
+        if let Some(channel) = &query.channel {
+            conditions.push("channel = ?");
+            values.push(SqlValue::Text(channel.clone()));
+        }
+        if let Some(outcome) = query.outcome {
+            conditions.push("outcome = ?");
+            values.push(SqlValue::Text(outcome.as_str().to_string()));
+        }
+
+        let mut sql = "SELECT id, created_at, client, tool, method, channel, payload_hash, payload,
+                    response_ts, outcome, error
+             FROM audit_log"
+            .to_string();
+        if !conditions.is_empty() {
+            sql.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+        }
+        let order = if query.oldest_first { "ASC" } else { "DESC" };
+        sql.push_str(&format!(" ORDER BY id {}", order));
+        if let Some(limit) = query.limit {
+            sql.push_str(" LIMIT ?");
+            values.push(SqlValue::Integer(limit as i64));
+        }
+
+        let mut stmt = conn.prepare(&sql)?;
+        let records = stmt
+            .query_map(params_from_iter(values), audit_record_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(records)
+    }
+}
+
+fn audit_record_from_row(row: &Row) -> rusqlite::Result<AuditRecord> {
+    let created_at: i64 = row.get(1)?;
+    let payload: String = row.get(7)?;
+    let outcome: String = row.get(9)?;
+
+    Ok(AuditRecord {
+        id: row.get(0)?,
+        created_at: DateTime::from_timestamp(created_at, 0)
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default(),
+        entry: AuditEntry {
+            client: row.get(2)?,
+            tool: row.get(3)?,
+            method: row.get(4)?,
+            channel: row.get(5)?,
+            payload_hash: row.get(6)?,
+            payload: serde_json::from_str(&payload).unwrap_or(Value::Null),
+            response_ts: row.get(8)?,
+            outcome: AuditOutcome::parse(&outcome),
+            error: row.get(10)?,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(tool: &str, channel: &str, outcome: AuditOutcome) -> AuditEntry {
+        AuditEntry {
+            client: Some("claude-desktop".to_string()),
+            tool: Some(tool.to_string()),
+            method: "chat.postMessage".to_string(),
+            channel: Some(channel.to_string()),
+            payload_hash: "abc123".to_string(),
+            payload: json!({"channel": channel, "text": "[redacted: 5 chars]"}),
+            response_ts: (outcome == AuditOutcome::Ok).then(|| "1700000000.000100".to_string()),
+            outcome,
+            error: (outcome == AuditOutcome::Error).then(|| "channel_not_found".to_string()),
+        }
+    }
+
+    async fn cache_with_entries() -> SqliteCache {
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+        cache
+            .record_audit(&entry("send_message", "C1", AuditOutcome::Ok))
+            .unwrap();
+        cache
+            .record_audit(&entry("send_message", "C2", AuditOutcome::Error))
+            .unwrap();
+        cache
+            .record_audit(&entry("add_reaction", "C1", AuditOutcome::Ok))
+            .unwrap();
+        cache
+    }
+
+    #[tokio::test]
+    async fn test_record_and_list_audit_log() {
+        let cache = cache_with_entries().await;
+
+        let records = cache.list_audit_log(&AuditQuery::default()).unwrap();
+
+        assert_eq!(records.len(), 3);
+        // Newest first by default
+        assert_eq!(records[0].entry.tool.as_deref(), Some("add_reaction"));
+        assert_eq!(records[2].entry.client.as_deref(), Some("claude-desktop"));
+        assert_eq!(records[2].entry.payload["text"], "[redacted: 5 chars]");
+        assert_eq!(
+            records[2].entry.response_ts.as_deref(),
+            Some("1700000000.000100")
+        );
+        assert!(DateTime::parse_from_rfc3339(&records[0].created_at).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_list_audit_log_filters() {
+        let cache = cache_with_entries().await;
+
+        let by_channel = cache
+            .list_audit_log(&AuditQuery {
+                channel: Some("C1".to_string()),
+                oldest_first: true,
+                ..Default::default()
+            })
+            .unwrap();
+        let tools: Vec<_> = by_channel
+            .iter()
+            .map(|r| r.entry.tool.as_deref().unwrap())
+            .collect();
+        assert_eq!(tools, vec!["send_message", "add_reaction"]);
+
+        let errors = cache
+            .list_audit_log(&AuditQuery {
+                outcome: Some(AuditOutcome::Error),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].entry.error.as_deref(), Some("channel_not_found"));
+
+        let limited = cache
+            .list_audit_log(&AuditQuery {
+                tool: Some("send_message".to_string()),
+                limit: Some(1),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(limited.len(), 1);
+        assert_eq!(limited[0].entry.channel.as_deref(), Some("C2"));
+
+        let future = cache
+            .list_audit_log(&AuditQuery {
+                since: Some(i64::MAX),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(future.is_empty());
+    }
+}
@@ -4,6 +4,10 @@ use rusqlite::params;
 
 use crate::slack::types::SlackChannel;
 
+use super::hangul::HangulQuery;
+use super::ranking::{
+    Candidate, MAX_SCAN_CANDIDATES, Ranking, Scorer, SearchMatch, subsequence_pattern,
+};
 use super::sqlite_cache::SqliteCache;
 
 impl SqliteCache {
@@ -86,18 +90,79 @@ impl SqliteCache {
         Ok(channels)
     }
 
-    pub fn search_channels(&self, query: &str, limit: usize) -> CacheResult<Vec<SlackChannel>> {
+    pub fn get_channel_by_id(&self, channel_id: &str) -> CacheResult<Option<SlackChannel>> {
         let conn = self.pool.get()?;
+        let mut stmt = conn.prepare_cached("SELECT data FROM channels WHERE id = ?1")?;
+
+        let result = stmt.query_row(params![channel_id], |row| {
+            let json: String = row.get(0)?;
+            serde_json::from_str(&json).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    0,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })
+        });
+
+        match result {
+            Ok(channel) => Ok(Some(channel)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Find channels, including archived ones, whose current or previous name is `name`
+    pub fn find_channels_by_name(&self, name: &str) -> CacheResult<Vec<SlackChannel>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare_cached(
+            "SELECT data FROM channels
+             WHERE lower(name) = lower(?1)
+             OR EXISTS (
+                 SELECT 1 FROM json_each(channels.data, '$.previous_names')
+                 WHERE lower(json_each.value) = lower(?1)
+             )
+             ORDER BY is_archived, name",
+        )?;
+
+        let channels = stmt
+            .query_map(params![name], |row| {
+                let json: String = row.get(0)?;
+                serde_json::from_str(&json).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        0,
+                        rusqlite::types::Type::Text,
+                        Box::new(e),
+                    )
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(channels)
+    }
+
+    pub fn search_channels(
+        &self,
+        query: &str,
+        limit: usize,
+        include_archived: bool,
+    ) -> CacheResult<Vec<SearchMatch<SlackChannel>>> {
+        let conn = self.pool.get()?;
+
+        let archived_filter = if include_archived {
+            ""
+        } else {
+            "AND (is_archived = 0 OR is_archived IS NULL)"
+        };
 
         // Handle empty or special queries
         if query.trim().is_empty() {
-            // Return all non-archived channels for empty query
-            let mut stmt = conn.prepare_cached(
-                "SELECT data FROM channels
-                 WHERE (is_archived = 0 OR is_archived IS NULL)
-                 ORDER BY name
-                 LIMIT ?1",
-            )?;
+            // Return all channels for empty query
+            let sql = format!(
+                "SELECT data FROM channels WHERE 1=1 {} ORDER BY name LIMIT ?1",
+                archived_filter
+            );
+            let mut stmt = conn.prepare_cached(&sql)?;
 
             let channels = stmt
                 .query_map(params![limit], |row| {
@@ -112,76 +177,111 @@ impl SqliteCache {
                 })?
                 .collect::<Result<Vec<_>, _>>()?;
 
-            return Ok(channels);
+            return Ok(channels
+                .into_iter()
+                .map(|item| SearchMatch {
+                    item,
+                    score: 1.0,
+                    matched_field: None,
+                })
+                .collect());
         }
 
+        let scorer = Scorer::new(query);
+        let mut ranking = Ranking::new(CHANNEL_FIELDS);
+
         // Phase 1: LIKE substring match on channel name with exact match priority
         let like_pattern = format!("%{query}%");
-        let like_result = conn
-            .prepare_cached(
-                "SELECT data,
+        let like_sql = format!(
+            "SELECT id, name, topic, purpose,
                 CASE
                     WHEN lower(name) = lower(?1) THEN 0
                     ELSE 1
                 END as priority
              FROM channels
-             WHERE (is_archived = 0 OR is_archived IS NULL)
-             AND name LIKE ?2
+             WHERE name LIKE ?2 {}
              ORDER BY priority, name
              LIMIT ?3",
-            )
-            .and_then(|mut stmt| {
-                stmt.query_map(params![query, like_pattern, limit], |row| {
-                    let json: String = row.get(0)?;
-                    serde_json::from_str(&json).map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(
-                            0,
-                            rusqlite::types::Type::Text,
-                            Box::new(e),
-                        )
-                    })
-                })?
-                .collect::<Result<Vec<_>, _>>()
-            })?;
-
-        if !like_result.is_empty() {
-            return Ok(like_result);
-        }
+            archived_filter
+        );
+        let like_result = conn.prepare_cached(&like_sql).and_then(|mut stmt| {
+            stmt.query_map(
+                params![query, like_pattern, MAX_SCAN_CANDIDATES],
+                Candidate::from_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()
+        })?;
+        ranking.add_matches(&scorer, like_result);
 
         // Phase 2: FTS5 fuzzy match (name, topic, purpose) - only if no LIKE results
         let processed_query = self.process_fts_query(query);
-        if processed_query.is_empty() {
-            return Ok(vec![]);
+        if ranking.is_empty() && !processed_query.is_empty() {
+            let fts_sql = format!(
+                "SELECT c.id, c.name, c.topic, c.purpose
+                 FROM channels c
+                 JOIN channels_fts f ON c.rowid = f.rowid
+                 WHERE channels_fts MATCH ?1
+                 {}
+                 ORDER BY rank
+                 LIMIT ?2",
+                archived_filter
+            );
+            let fts_result = conn.prepare_cached(&fts_sql).and_then(|mut stmt| {
+                stmt.query_map(
+                    params![processed_query, MAX_SCAN_CANDIDATES],
+                    Candidate::from_row,
+                )?
+                .collect::<Result<Vec<_>, _>>()
+            })?;
+            ranking.add_matches(&scorer, fts_result);
         }
 
-        let fts_result = conn
-            .prepare_cached(
-                "SELECT c.data
-             FROM channels c
-             JOIN channels_fts f ON c.rowid = f.rowid
-             WHERE channels_fts MATCH ?1
-             AND (c.is_archived = 0 OR c.is_archived IS NULL)
-             ORDER BY rank
-             LIMIT ?2",
-            )
-            .and_then(|mut stmt| {
-                stmt.query_map(params![processed_query, limit], |row| {
-                    let json: String = row.get(0)?;
-                    serde_json::from_str(&json).map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(
-                            0,
-                            rusqlite::types::Type::Text,
-                            Box::new(e),
-                        )
-                    })
-                })?
-                .collect::<Result<Vec<_>, _>>()
+        // Phases 3 and 4 score capped scans in Rust, so only run them when slots remain
+        if ranking.len() < limit {
+            let hangul_query = HangulQuery::new(query);
+
+            // Fuzzy matches hold the query's characters in order, so SQL narrows
+            // them down however many channels the workspace has
+            if let Some(pattern) = subsequence_pattern(query) {
+                let sql = format!(
+                    "SELECT id, name, topic, purpose FROM channels
+                     WHERE 1=1 {}
+                     AND (name LIKE ?1 ESCAPE '\\' OR topic LIKE ?1 ESCAPE '\\'
+                          OR purpose LIKE ?1 ESCAPE '\\')
+                     ORDER BY name
+                     LIMIT ?2",
+                    archived_filter
+                );
+                let candidates = conn.prepare_cached(&sql).and_then(|mut stmt| {
+                    stmt.query_map(params![pattern, MAX_SCAN_CANDIDATES], Candidate::from_row)?
+                        .collect::<Result<Vec<_>, _>>()
+                })?;
+                ranking.add_scanned(&scorer, hangul_query.as_ref(), candidates);
+            }
+
+            // Hangul (초성, romanization) and typo matches can't be narrowed that
+            // way, so they come from the first channels by name
+            let sql = format!(
+                "SELECT id, name, topic, purpose FROM channels
+                 WHERE 1=1 {}
+                 ORDER BY name
+                 LIMIT ?1",
+                archived_filter
+            );
+            let candidates = conn.prepare_cached(&sql).and_then(|mut stmt| {
+                stmt.query_map(params![MAX_SCAN_CANDIDATES], Candidate::from_row)?
+                    .collect::<Result<Vec<_>, _>>()
             })?;
+            ranking.add_scanned(&scorer, hangul_query.as_ref(), candidates);
+        }
 
-        Ok(fts_result)
+        ranking.load(&conn, "channels", limit)
     }
 }
 
+/// Searchable channel columns in priority order
+const CHANNEL_FIELDS: [&str; 3] = ["name", "topic", "purpose"];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,6 +311,7 @@ mod tests {
             topic: None,
             purpose: None,
             num_members: Some(10),
+            previous_names: vec![],
         }
     }
 
@@ -364,7 +465,7 @@ mod tests {
         ];
         cache.save_channels(channels).await.unwrap();
 
-        let results = cache.search_channels(query, 10).unwrap();
+        let results = cache.search_channels(query, 10, false).unwrap();
         assert_eq!(results.len(), expected_count);
     }
 
@@ -378,7 +479,7 @@ mod tests {
         cache.save_channels(channels).await.unwrap();
 
         // Empty query should return all non-archived channels
-        let results = cache.search_channels("", 10).unwrap();
+        let results = cache.search_channels("", 10, false).unwrap();
         assert_eq!(results.len(), 2);
     }
 
@@ -392,7 +493,7 @@ mod tests {
         ];
         cache.save_channels(channels).await.unwrap();
 
-        let results = cache.search_channels("", 2).unwrap();
+        let results = cache.search_channels("", 2, false).unwrap();
         assert_eq!(results.len(), 2);
     }
 
@@ -406,10 +507,27 @@ mod tests {
         cache.save_channels(channels).await.unwrap();
 
         // Search should not return archived channels
-        let results = cache.search_channels("test", 10).unwrap();
+        let results = cache.search_channels("test", 10, false).unwrap();
         assert_eq!(results.len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_search_channels_include_archived() {
+        let cache = setup_cache().await;
+        let channels = vec![
+            create_test_channel("C123", "active-test", false, false, false, false),
+            create_test_channel("C456", "archived-test", false, true, false, false),
+        ];
+        cache.save_channels(channels).await.unwrap();
+
+        let results = cache.search_channels("test", 10, true).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().any(|c| c.id == "C456" && c.is_archived));
+
+        let results = cache.search_channels("", 10, true).unwrap();
+        assert_eq!(results.len(), 2);
+    }
+
     #[tokio::test]
     async fn test_search_channels_includes_private() {
         let cache = setup_cache().await;
@@ -419,7 +537,7 @@ mod tests {
         ];
         cache.save_channels(channels).await.unwrap();
 
-        let results = cache.search_channels("channel", 10).unwrap();
+        let results = cache.search_channels("channel", 10, false).unwrap();
         assert_eq!(results.len(), 2);
     }
 
@@ -432,7 +550,7 @@ mod tests {
         cache.save_channels(channels).await.unwrap();
 
         // Special characters are stripped by process_fts_query
-        let results = cache.search_channels("general*@#$", 10).unwrap();
+        let results = cache.search_channels("general*@#$", 10, false).unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "general");
     }
@@ -447,10 +565,10 @@ mod tests {
         cache.save_channels(channels).await.unwrap();
 
         // FTS5 search should be case-insensitive
-        let results = cache.search_channels("general", 10).unwrap();
+        let results = cache.search_channels("general", 10, false).unwrap();
         assert_eq!(results.len(), 1);
 
-        let results = cache.search_channels("random", 10).unwrap();
+        let results = cache.search_channels("random", 10, false).unwrap();
         assert_eq!(results.len(), 1);
     }
 
@@ -546,7 +664,7 @@ mod tests {
         ];
         cache.save_channels(channels).await.unwrap();
 
-        let results = cache.search_channels("general", 10).unwrap();
+        let results = cache.search_channels("general", 10, false).unwrap();
         // Exact match "general" should be first
         assert_eq!(results.len(), 3);
         assert_eq!(results[0].name, "general");
@@ -563,13 +681,123 @@ mod tests {
         cache.save_channels(channels).await.unwrap();
 
         // "dev" should match channel names via LIKE, not fall through to FTS5
-        let results = cache.search_channels("dev", 10).unwrap();
+        let results = cache.search_channels("dev", 10, false).unwrap();
         assert_eq!(results.len(), 2);
         // Results should be sorted by name after priority
         assert!(results.iter().any(|c| c.name == "dev-team"));
         assert!(results.iter().any(|c| c.name == "dev-backend"));
     }
 
+    #[tokio::test]
+    async fn test_search_channels_ranks_substring_matches_by_score() {
+        let cache = setup_cache().await;
+        let channels = vec![
+            create_test_channel("C1", "a-dev", false, false, false, false),
+            create_test_channel("C2", "dev-ops", false, false, false, false),
+            create_test_channel("C3", "dev", false, false, false, false),
+        ];
+        cache.save_channels(channels).await.unwrap();
+
+        let results = cache.search_channels("dev", 2, false).unwrap();
+        let ids: Vec<&str> = results.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["C3", "C2"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_channels_fuzzy_finds_matches_past_scan_cap() {
+        let cache = setup_cache().await;
+        let mut channels: Vec<SlackChannel> = (0..=MAX_SCAN_CANDIDATES)
+            .map(|i| {
+                create_test_channel(
+                    &format!("C{i:06}"),
+                    &format!("filler-{i:06}"),
+                    false,
+                    false,
+                    false,
+                    false,
+                )
+            })
+            .collect();
+        // Stored last and sorted last, past the first MAX_SCAN_CANDIDATES rows
+        channels.push(create_test_channel(
+            "C999999",
+            "zz-engineering-platform",
+            false,
+            false,
+            false,
+            false,
+        ));
+        cache.save_channels(channels).await.unwrap();
+
+        let results = cache.search_channels("eng-plat", 10, false).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "C999999");
+    }
+
+    fn with_topic(mut channel: SlackChannel, topic: &str) -> SlackChannel {
+        channel.topic = Some(crate::slack::types::ChannelTopic {
+            value: topic.to_string(),
+            creator: "U1".to_string(),
+            last_set: 0,
+        });
+        channel
+    }
+
+    #[tokio::test]
+    async fn test_search_channels_hangul_topic() {
+        let cache = setup_cache().await;
+        let channels = vec![
+            with_topic(
+                create_test_channel("C123", "eng-platform", false, false, false, false),
+                "플랫폼개발팀 공지",
+            ),
+            create_test_channel("C456", "random", false, false, false, false),
+        ];
+        cache.save_channels(channels).await.unwrap();
+
+        // unicode61 keeps "플랫폼개발팀" as one token, so FTS alone cannot find "개발"
+        let results = cache.search_channels("개발", 10, false).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "C123");
+
+        let results = cache.search_channels("ㄱㅂㅌ", 10, false).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "C123");
+    }
+
+    #[tokio::test]
+    async fn test_search_channels_hangul_name_ranks_before_topic() {
+        let cache = setup_cache().await;
+        let channels = vec![
+            with_topic(
+                create_test_channel("C1", "announcements", false, false, false, false),
+                "디자인 공지",
+            ),
+            create_test_channel("C2", "디자인-리뷰", false, false, false, false),
+            create_test_channel("C3", "디자인", false, false, false, false),
+            create_test_channel("C4", "old-디자인", false, true, false, false),
+        ];
+        cache.save_channels(channels).await.unwrap();
+
+        let results = cache.search_channels("ㄷㅈㅇ", 10, false).unwrap();
+        let ids: Vec<&str> = results.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["C3", "C2", "C1"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_channels_romanized_query() {
+        let cache = setup_cache().await;
+        let channels = vec![
+            create_test_channel("C1", "디자인", false, false, false, false),
+            create_test_channel("C2", "general", false, false, false, false),
+        ];
+        cache.save_channels(channels).await.unwrap();
+
+        let results = cache.search_channels("dijain", 10, false).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "C1");
+    }
+
     // New test: FTS5 fallback for channels
     #[tokio::test]
     async fn test_search_channels_fallback_to_fts5() {
@@ -581,7 +809,56 @@ mod tests {
         cache.save_channels(channels).await.unwrap();
 
         // "xyz" has no LIKE match in channel names, should fall back to FTS5 and return empty
-        let results = cache.search_channels("xyz", 10).unwrap();
+        let results = cache.search_channels("xyz", 10, false).unwrap();
         assert_eq!(results.len(), 0);
     }
+
+    #[tokio::test]
+    async fn test_search_channels_fuzzy_abbreviation() {
+        let cache = setup_cache().await;
+        let channels = vec![
+            create_test_channel("C1", "engineering-platform", false, false, false, false),
+            create_test_channel("C2", "marketing", false, false, false, false),
+        ];
+        cache.save_channels(channels).await.unwrap();
+
+        let results = cache.search_channels("eng-plat", 10, false).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "C1");
+        assert_eq!(results[0].matched_field, Some("name"));
+    }
+
+    #[tokio::test]
+    async fn test_search_channels_scores_name_above_topic() {
+        let cache = setup_cache().await;
+        let channels = vec![
+            with_topic(
+                create_test_channel("C1", "general", false, false, false, false),
+                "dev announcements",
+            ),
+            create_test_channel("C2", "dev", false, false, false, false),
+        ];
+        cache.save_channels(channels).await.unwrap();
+
+        let results = cache.search_channels("dev", 10, false).unwrap();
+        assert_eq!(results[0].id, "C2");
+        assert_eq!(results[0].score, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_find_channels_by_name_includes_archived_and_renamed() {
+        let cache = setup_cache().await;
+        let mut renamed = create_test_channel("C2", "product-design", false, false, false, false);
+        renamed.previous_names = vec!["design".to_string()];
+        let channels = vec![
+            create_test_channel("C1", "design", false, true, false, false),
+            renamed,
+            create_test_channel("C3", "design-review", false, false, false, false),
+        ];
+        cache.save_channels(channels).await.unwrap();
+
+        let results = cache.find_channels_by_name("Design").unwrap();
+        let ids: Vec<&str> = results.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["C2", "C1"]);
+    }
 }
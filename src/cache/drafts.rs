@@ -0,0 +1,89 @@
+use rusqlite::{OptionalExtension, params};
+use serde_json::Value;
+
+use super::error::CacheResult;
+use super::sqlite_cache::SqliteCache;
+
+impl SqliteCache {
+    /// Store a message waiting for confirmation; it expires after `ttl_seconds`
+    pub fn save_draft(
+        &self,
+        id: &str,
+        channel: &str,
+        draft: &Value,
+        ttl_seconds: i64,
+    ) -> CacheResult<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "DELETE FROM pending_drafts WHERE expires_at <= unixepoch()",
+            [],
+        )?;
+        conn.execute(
+            "INSERT INTO pending_drafts (id, channel, draft, expires_at)
+             VALUES (?, ?, ?, unixepoch() + ?)",
+            params![id, channel, serde_json::to_string(draft)?, ttl_seconds],
+        )?;
+        Ok(())
+    }
+
+    /// A draft, unless it doesn't exist or has expired, leaving it in place
+    pub fn get_draft(&self, id: &str) -> CacheResult<Option<Value>> {
+        let conn = self.pool.get()?;
+        let draft: Option<String> = conn
+            .query_row(
+                "SELECT draft FROM pending_drafts WHERE id = ? AND expires_at > unixepoch()",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(draft.map(|d| serde_json::from_str(&d)).transpose()?)
+    }
+
+    /// Remove a draft and return it, unless it doesn't exist or has expired.
+    /// Only one caller can take a given draft.
+    pub fn take_draft(&self, id: &str) -> CacheResult<Option<Value>> {
+        let conn = self.pool.get()?;
+        let draft: Option<String> = conn
+            .query_row(
+                "DELETE FROM pending_drafts WHERE id = ? RETURNING draft, expires_at > unixepoch()",
+                params![id],
+                |row| {
+                    let draft: String = row.get(0)?;
+                    let live: bool = row.get(1)?;
+                    Ok(live.then_some(draft))
+                },
+            )
+            .optional()?
+            .flatten();
+        Ok(draft.map(|d| serde_json::from_str(&d)).transpose()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_draft_can_be_taken_once() {
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+        let draft = json!({"channel": "C123", "parts": [{"text": "hello"}]});
+        cache.save_draft("d1", "C123", &draft, 3600).unwrap();
+
+        assert_eq!(cache.get_draft("d1").unwrap(), Some(draft.clone()));
+        assert_eq!(cache.take_draft("d1").unwrap(), Some(draft));
+        assert_eq!(cache.take_draft("d1").unwrap(), None);
+        assert_eq!(cache.take_draft("unknown").unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_expired_draft_is_not_returned() {
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+        cache
+            .save_draft("d1", "C123", &json!({"parts": []}), 0)
+            .unwrap();
+
+        assert_eq!(cache.get_draft("d1").unwrap(), None);
+        assert_eq!(cache.take_draft("d1").unwrap(), None);
+    }
+}
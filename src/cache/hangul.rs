@@ -0,0 +1,374 @@
+//! Hangul-aware matching for user and channel search.
+//!
+//! SQLite's `unicode61` tokenizer treats a run of Hangul as a single token and
+//! porter stemming only understands English, so FTS cannot find "준영" inside
+//! "김준영", initial-consonant (초성) queries like "ㄱㅈㅇ", or romanized
+//! spellings like "junyeong". This module provides those matches in Rust.
+
+const SYLLABLE_BASE: u32 = 0xAC00;
+const SYLLABLE_LAST: u32 = 0xD7A3;
+const JUNGSEONG_COUNT: u32 = 21;
+const JONGSEONG_COUNT: u32 = 28;
+
+const CHOSEONG: [char; 19] = [
+    'ㄱ', 'ㄲ', 'ㄴ', 'ㄷ', 'ㄸ', 'ㄹ', 'ㅁ', 'ㅂ', 'ㅃ', 'ㅅ', 'ㅆ', 'ㅇ', 'ㅈ', 'ㅉ', 'ㅊ', 'ㅋ',
+    'ㅌ', 'ㅍ', 'ㅎ',
+];
+
+// Compound vowels and finals are split into their parts so that a query typed
+// mid-syllable (e.g. "과" vs "관") still matches as a jamo prefix.
+const JUNGSEONG: [&str; 21] = [
+    "ㅏ", "ㅐ", "ㅑ", "ㅒ", "ㅓ", "ㅔ", "ㅕ", "ㅖ", "ㅗ", "ㅗㅏ", "ㅗㅐ", "ㅗㅣ", "ㅛ", "ㅜ",
+    "ㅜㅓ", "ㅜㅔ", "ㅜㅣ", "ㅠ", "ㅡ", "ㅡㅣ", "ㅣ",
+];
+
+const JONGSEONG: [&str; 28] = [
+    "", "ㄱ", "ㄲ", "ㄱㅅ", "ㄴ", "ㄴㅈ", "ㄴㅎ", "ㄷ", "ㄹ", "ㄹㄱ", "ㄹㅁ", "ㄹㅂ", "ㄹㅅ",
+    "ㄹㅌ", "ㄹㅍ", "ㄹㅎ", "ㅁ", "ㅂ", "ㅂㅅ", "ㅅ", "ㅆ", "ㅇ", "ㅈ", "ㅊ", "ㅋ", "ㅌ", "ㅍ",
+    "ㅎ",
+];
+
+// Revised Romanization of Korean, without sound-change rules between syllables
+const ROMAN_INITIAL: [&str; 19] = [
+    "g", "kk", "n", "d", "tt", "r", "m", "b", "pp", "s", "ss", "", "j", "jj", "ch", "k", "t", "p",
+    "h",
+];
+
+const ROMAN_MEDIAL: [&str; 21] = [
+    "a", "ae", "ya", "yae", "eo", "e", "yeo", "ye", "o", "wa", "wae", "oe", "yo", "u", "wo", "we",
+    "wi", "yu", "eu", "ui", "i",
+];
+
+const ROMAN_FINAL: [&str; 28] = [
+    "", "k", "k", "k", "n", "n", "n", "t", "l", "k", "m", "l", "l", "l", "p", "l", "m", "p", "p",
+    "t", "t", "ng", "t", "t", "k", "t", "p", "t",
+];
+
+// Common surname spellings that Revised Romanization does not produce
+const SURNAME_ALIASES: &[(char, &[&str])] = &[
+    ('이', &["lee", "yi", "rhee"]),
+    ('박', &["park"]),
+    ('최', &["choi"]),
+    ('정', &["jung", "chung"]),
+    ('조', &["cho"]),
+    ('오', &["oh"]),
+    ('임', &["lim"]),
+    ('노', &["noh"]),
+    ('서', &["suh"]),
+    ('엄', &["um"]),
+    ('우', &["woo"]),
+    ('전', &["jun", "chun"]),
+    ('성', &["sung"]),
+    ('신', &["shin"]),
+];
+
+/// How closely a query matched a field, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum MatchKind {
+    Exact,
+    Prefix,
+    Substring,
+}
+
+fn syllable_parts(c: char) -> Option<(usize, usize, usize)> {
+    let code = c as u32;
+    if !(SYLLABLE_BASE..=SYLLABLE_LAST).contains(&code) {
+        return None;
+    }
+    let offset = code - SYLLABLE_BASE;
+    let initial = offset / (JUNGSEONG_COUNT * JONGSEONG_COUNT);
+    let medial = (offset % (JUNGSEONG_COUNT * JONGSEONG_COUNT)) / JONGSEONG_COUNT;
+    let final_ = offset % JONGSEONG_COUNT;
+    Some((initial as usize, medial as usize, final_ as usize))
+}
+
+fn is_compat_jamo(c: char) -> bool {
+    ('\u{3131}'..='\u{3163}').contains(&c)
+}
+
+fn is_compat_consonant(c: char) -> bool {
+    ('\u{3131}'..='\u{314E}').contains(&c)
+}
+
+pub(super) fn contains_hangul(s: &str) -> bool {
+    s.chars()
+        .any(|c| syllable_parts(c).is_some() || is_compat_jamo(c))
+}
+
+/// Decompose Hangul syllables into compatibility jamo, dropping whitespace
+pub(super) fn decompose(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() * 3);
+    for c in s.chars().filter(|c| !c.is_whitespace()) {
+        match syllable_parts(c) {
+            Some((initial, medial, final_)) => {
+                out.push(CHOSEONG[initial]);
+                out.push_str(JUNGSEONG[medial]);
+                out.push_str(JONGSEONG[final_]);
+            }
+            None => out.extend(c.to_lowercase()),
+        }
+    }
+    out
+}
+
+/// Initial consonants (초성) of each syllable, e.g. "김준영" -> "ㄱㅈㅇ"
+pub(super) fn choseong(s: &str) -> String {
+    s.chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(|c| match syllable_parts(c) {
+            Some((initial, _, _)) => vec![CHOSEONG[initial]],
+            None => c.to_lowercase().collect(),
+        })
+        .collect()
+}
+
+/// Romanize Hangul syllables, lowercasing and keeping any other characters
+pub(super) fn romanize(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() * 2);
+    for c in s.chars() {
+        match syllable_parts(c) {
+            Some((initial, medial, final_)) => {
+                out.push_str(ROMAN_INITIAL[initial]);
+                out.push_str(ROMAN_MEDIAL[medial]);
+                out.push_str(ROMAN_FINAL[final_]);
+            }
+            None => out.extend(c.to_lowercase()),
+        }
+    }
+    out
+}
+
+/// Normalize Latin text so that common alternative spellings compare equal
+/// (e.g. "kim"/"gim", "yoon"/"yun", "shin"/"sin")
+pub(super) fn loose_latin(s: &str) -> String {
+    let compact: String = s
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    compact
+        .replace("ee", "i")
+        .replace("oo", "u")
+        .replace("sh", "s")
+        .chars()
+        .map(|c| match c {
+            'k' => 'g',
+            't' => 'd',
+            'p' => 'b',
+            'r' => 'l',
+            other => other,
+        })
+        .collect()
+}
+
+/// Loose romanizations of a Hangul field, including common surname spellings
+fn romanized_variants(field: &str) -> Vec<String> {
+    let mut variants = vec![loose_latin(&romanize(field))];
+
+    let trimmed = field.trim_start();
+    if let Some(first) = trimmed.chars().next()
+        && let Some((_, aliases)) = SURNAME_ALIASES.iter().find(|(c, _)| *c == first)
+    {
+        let rest = romanize(&trimmed[first.len_utf8()..]);
+        for alias in *aliases {
+            variants.push(loose_latin(&format!("{}{}", alias, rest)));
+        }
+    }
+
+    variants
+}
+
+fn compare(needle: &str, haystack: &str) -> Option<MatchKind> {
+    if needle.is_empty() || haystack.is_empty() {
+        None
+    } else if haystack == needle {
+        Some(MatchKind::Exact)
+    } else if haystack.starts_with(needle) {
+        Some(MatchKind::Prefix)
+    } else if haystack.contains(needle) {
+        Some(MatchKind::Substring)
+    } else {
+        None
+    }
+}
+
+fn better(current: Option<MatchKind>, candidate: Option<MatchKind>) -> Option<MatchKind> {
+    match (current, candidate) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Pre-processed search query for Hangul-aware matching
+#[derive(Debug, Clone)]
+pub(super) struct HangulQuery {
+    /// Query consists only of initial consonants, e.g. "ㄱㅈㅇ"
+    choseong_only: bool,
+    has_hangul: bool,
+    jamo: String,
+    compact: String,
+    loose: String,
+}
+
+impl HangulQuery {
+    /// Returns `None` when the query cannot benefit from Hangul matching
+    /// (no Hangul and not a plain Latin word that could be a romanization)
+    pub(super) fn new(query: &str) -> Option<Self> {
+        let compact: String = query.chars().filter(|c| !c.is_whitespace()).collect();
+        if compact.is_empty() {
+            return None;
+        }
+
+        let has_hangul = contains_hangul(&compact);
+        let is_latin_word = compact
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+            && compact.chars().filter(|c| c.is_ascii_alphabetic()).count() >= 2;
+
+        if !has_hangul && !is_latin_word {
+            return None;
+        }
+
+        let loose = if has_hangul {
+            loose_latin(&romanize(&compact))
+        } else {
+            loose_latin(&compact)
+        };
+
+        Some(Self {
+            choseong_only: compact.chars().all(is_compat_consonant),
+            has_hangul,
+            jamo: decompose(&compact),
+            compact,
+            loose,
+        })
+    }
+
+    /// Match a single field, returning how well it matched
+    pub(super) fn match_field(&self, field: &str) -> Option<MatchKind> {
+        if field.trim().is_empty() {
+            return None;
+        }
+
+        let field_has_hangul = contains_hangul(field);
+        let mut best = None;
+
+        if self.choseong_only && field_has_hangul {
+            best = better(best, compare(&self.compact, &choseong(field)));
+        }
+
+        if self.has_hangul {
+            if field_has_hangul {
+                best = better(best, compare(&self.jamo, &decompose(field)));
+            } else if self.loose.len() >= 3 {
+                // Korean query against a romanized handle, e.g. "준영" vs "junyeong.eom"
+                best = better(best, compare(&self.loose, &loose_latin(field)));
+            }
+        } else if field_has_hangul {
+            // Romanized query against a Korean name, e.g. "junyeong" vs "김준영"
+            for variant in romanized_variants(field) {
+                best = better(best, compare(&self.loose, &variant));
+            }
+        }
+
+        best
+    }
+
+    /// Best match across several fields; returns the match and the field index
+    pub(super) fn match_fields(&self, fields: &[Option<&str>]) -> Option<(MatchKind, usize)> {
+        fields
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, field)| field.and_then(|f| self.match_field(f)).map(|m| (m, idx)))
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("김준영", "ㄱㅣㅁㅈㅜㄴㅇㅕㅇ")]
+    #[case("관리", "ㄱㅗㅏㄴㄹㅣ")]
+    #[case("닭", "ㄷㅏㄹㄱ")]
+    #[case("Dev 팀", "devㅌㅣㅁ")]
+    fn test_decompose(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(decompose(input), expected);
+    }
+
+    #[rstest]
+    #[case("김준영", "ㄱㅈㅇ")]
+    #[case("개발 팀", "ㄱㅂㅌ")]
+    #[case("Eom 준영", "eomㅈㅇ")]
+    fn test_choseong(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(choseong(input), expected);
+    }
+
+    #[rstest]
+    #[case("김준영", "gimjunyeong")]
+    #[case("박서연", "bakseoyeon")]
+    #[case("최민호", "choeminho")]
+    #[case("한국-dev", "hanguk-dev")]
+    fn test_romanize(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(romanize(input), expected);
+    }
+
+    #[rstest]
+    #[case("kim", "gim")]
+    #[case("Yoon", "yun")]
+    #[case("shin", "sin")]
+    #[case("junyeong.eom", "junyeongeom")]
+    fn test_loose_latin(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(loose_latin(input), expected);
+    }
+
+    #[test]
+    fn test_query_not_applicable() {
+        assert!(HangulQuery::new("").is_none());
+        assert!(HangulQuery::new("   ").is_none());
+        assert!(HangulQuery::new("a").is_none());
+        assert!(HangulQuery::new("@#$").is_none());
+        assert!(HangulQuery::new("user@example.com").is_none());
+    }
+
+    #[rstest]
+    #[case("준영", "김준영", Some(MatchKind::Substring))]
+    #[case("김준", "김준영", Some(MatchKind::Prefix))]
+    #[case("김준영", "김 준영", Some(MatchKind::Exact))]
+    #[case("김주", "김준영", Some(MatchKind::Prefix))]
+    #[case("ㄱㅈㅇ", "김준영", Some(MatchKind::Exact))]
+    #[case("ㅈㅇ", "김준영", Some(MatchKind::Substring))]
+    #[case("ㄱㅈㅇ", "gjy", None)]
+    #[case("junyeong", "김준영", Some(MatchKind::Substring))]
+    #[case("kimjunyeong", "김준영", Some(MatchKind::Exact))]
+    #[case("park", "박서연", Some(MatchKind::Prefix))]
+    #[case("lee", "이민수", Some(MatchKind::Prefix))]
+    #[case("yoon", "윤지호", Some(MatchKind::Prefix))]
+    #[case("준영", "junyeong.eom", Some(MatchKind::Prefix))]
+    #[case("alice", "alice", None)]
+    #[case("박", "김준영", None)]
+    fn test_match_field(
+        #[case] query: &str,
+        #[case] field: &str,
+        #[case] expected: Option<MatchKind>,
+    ) {
+        let query = HangulQuery::new(query).unwrap();
+        assert_eq!(query.match_field(field), expected);
+    }
+
+    #[test]
+    fn test_match_fields_prefers_best_kind_then_field_order() {
+        let query = HangulQuery::new("준영").unwrap();
+
+        let result = query.match_fields(&[Some("junyeong.eom"), Some("준영"), None]);
+        assert_eq!(result, Some((MatchKind::Exact, 1)));
+
+        let result = query.match_fields(&[Some("김준영"), Some("김준영")]);
+        assert_eq!(result, Some((MatchKind::Substring, 0)));
+
+        assert_eq!(query.match_fields(&[None, Some("alice")]), None);
+    }
+}
@@ -113,6 +113,8 @@ mod tests {
             is_bot: false,
             is_admin: false,
             deleted: false,
+            tz: None,
+            tz_offset: None,
             profile: Some(SlackUserProfile {
                 real_name: Some(name.to_string()),
                 display_name: Some(name.to_string()),
@@ -139,6 +141,7 @@ mod tests {
             topic: None,
             purpose: None,
             num_members: Some(10),
+            previous_names: vec![],
         }
     }
 
@@ -1,11 +1,21 @@
+mod audit;
 mod channels;
+mod drafts;
 mod error;
+mod hangul;
 mod helpers;
 mod locks;
+mod quotas;
+mod ranking;
 mod schema;
+mod sent;
 pub mod sqlite_cache;
+mod status;
 mod users;
 
+pub use audit::{AuditEntry, AuditOutcome, AuditQuery, AuditRecord};
+pub use quotas::QuotaCheck;
+pub use sent::{IdempotencyClaim, SentMessageKey};
 pub use sqlite_cache::SqliteCache;
 
 // Cache refresh types
@@ -0,0 +1,256 @@
+use chrono::Utc;
+use rusqlite::{OptionalExtension, TransactionBehavior, params};
+use serde::{Deserialize, Serialize};
+
+use super::error::CacheResult;
+use super::sqlite_cache::SqliteCache;
+
+/// A quota counter's state in its current window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaUsage {
+    pub used: u64,
+    /// Unix time when the window ends and the counter starts over
+    pub resets_at: i64,
+}
+
+/// Result of counting against a quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaCheck {
+    /// Counted; usage includes this call
+    Allowed(QuotaUsage),
+    /// Not counted: it would go over the limit
+    Exceeded(QuotaUsage),
+}
+
+/// Stored in `metadata` under `quota:<key>`
+#[derive(Debug, Serialize, Deserialize)]
+struct QuotaCounter {
+    window_start: i64,
+    resets_at: i64,
+    count: u64,
+}
+
+impl SqliteCache {
+    /// Current usage of the quota `key`
+    #[cfg(test)]
+    pub fn quota_usage(&self, key: &str, window_seconds: u64) -> CacheResult<QuotaUsage> {
+        let conn = self.pool.get()?;
+        let (window_start, resets_at) = window(Utc::now().timestamp(), window_seconds);
+        let counter = read_counter(&conn, key)?;
+        Ok(QuotaUsage {
+            used: counter
+                .filter(|c| c.window_start == window_start)
+                .map_or(0, |c| c.count),
+            resets_at,
+        })
+    }
+
+    /// Count `amount` against the quota `key` unless that would take it over `limit`
+    /// (no limit: always count)
+    pub fn consume_quota(
+        &self,
+        key: &str,
+        limit: Option<u64>,
+        window_seconds: u64,
+        amount: u64,
+    ) -> CacheResult<QuotaCheck> {
+        self.consume_quota_at(key, limit, window_seconds, amount, Utc::now().timestamp())
+    }
+
+    // Windows are aligned to multiples of their length, so every instance agrees on
+    // when they reset. The immediate transaction takes SQLite's write lock before
+    // reading, so concurrent instances can't both spend the last call.
+    fn consume_quota_at(
+        &self,
+        key: &str,
+        limit: Option<u64>,
+        window_seconds: u64,
+        amount: u64,
+        now: i64,
+    ) -> CacheResult<QuotaCheck> {
+        let (window_start, resets_at) = window(now, window_seconds);
+
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        let used = read_counter(&tx, key)?
+            .filter(|c| c.window_start == window_start)
+            .map_or(0, |c| c.count);
+        if limit.is_some_and(|limit| used + amount > limit) {
+            return Ok(QuotaCheck::Exceeded(QuotaUsage { used, resets_at }));
+        }
+
+        let counter = QuotaCounter {
+            window_start,
+            resets_at,
+            count: used + amount,
+        };
+        // Counters of finished windows are never read again
+        tx.execute(
+            "DELETE FROM metadata WHERE key LIKE 'quota:%' AND json_extract(value, '$.resets_at') <= ?",
+            params![now],
+        )?;
+        tx.execute(
+            "INSERT OR REPLACE INTO metadata (key, value, updated_at) VALUES (?, ?, ?)",
+            params![
+                format!("quota:{}", key),
+                serde_json::to_string(&counter)?,
+                now
+            ],
+        )?;
+        tx.commit()?;
+
+        Ok(QuotaCheck::Allowed(QuotaUsage {
+            used: counter.count,
+            resets_at,
+        }))
+    }
+
+    /// Give back `amount` counted against the quota `key` in the current window,
+    /// for calls that were counted but didn't go through
+    pub fn release_quota(&self, key: &str, window_seconds: u64, amount: u64) -> CacheResult<()> {
+        self.release_quota_at(key, window_seconds, amount, Utc::now().timestamp())
+    }
+
+    fn release_quota_at(
+        &self,
+        key: &str,
+        window_seconds: u64,
+        amount: u64,
+        now: i64,
+    ) -> CacheResult<()> {
+        let (window_start, _) = window(now, window_seconds);
+
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        // A counter from an earlier window has already started over
+        let Some(mut counter) = read_counter(&tx, key)?.filter(|c| c.window_start == window_start)
+        else {
+            return Ok(());
+        };
+        counter.count = counter.count.saturating_sub(amount);
+        tx.execute(
+            "UPDATE metadata SET value = ?, updated_at = ? WHERE key = ?",
+            params![
+                serde_json::to_string(&counter)?,
+                now,
+                format!("quota:{}", key)
+            ],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// Start and end of the window containing `now`
+fn window(now: i64, window_seconds: u64) -> (i64, i64) {
+    let length = window_seconds.max(1) as i64;
+    let start = now - now.rem_euclid(length);
+    (start, start + length)
+}
+
+fn read_counter(conn: &rusqlite::Connection, key: &str) -> CacheResult<Option<QuotaCounter>> {
+    let value: Option<String> = conn
+        .query_row(
+            "SELECT value FROM metadata WHERE key = ?",
+            params![format!("quota:{}", key)],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(value.and_then(|v| serde_json::from_str(&v).ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_010;
+
+    #[tokio::test]
+    async fn test_consume_quota_until_limit() {
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+
+        for used in 1..=2 {
+            assert_eq!(
+                cache.consume_quota_at("send", Some(2), 60, 1, NOW).unwrap(),
+                QuotaCheck::Allowed(QuotaUsage {
+                    used,
+                    resets_at: 1_700_000_040,
+                })
+            );
+        }
+        assert_eq!(
+            cache
+                .consume_quota_at("send", Some(2), 60, 1, NOW + 5)
+                .unwrap(),
+            QuotaCheck::Exceeded(QuotaUsage {
+                used: 2,
+                resets_at: 1_700_000_040,
+            })
+        );
+
+        // Other keys have their own counters
+        assert!(matches!(
+            cache
+                .consume_quota_at("other", Some(2), 60, 1, NOW)
+                .unwrap(),
+            QuotaCheck::Allowed(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_quota_resets_with_next_window() {
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+        cache.consume_quota_at("send", Some(1), 60, 1, NOW).unwrap();
+
+        assert_eq!(
+            cache
+                .consume_quota_at("send", Some(1), 60, 1, NOW + 30)
+                .unwrap(),
+            QuotaCheck::Allowed(QuotaUsage {
+                used: 1,
+                resets_at: 1_700_000_100,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_release_quota() {
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+        cache.consume_quota_at("send", Some(1), 60, 1, NOW).unwrap();
+        cache.release_quota_at("send", 60, 1, NOW + 1).unwrap();
+
+        assert!(matches!(
+            cache
+                .consume_quota_at("send", Some(1), 60, 1, NOW + 2)
+                .unwrap(),
+            QuotaCheck::Allowed(QuotaUsage { used: 1, .. })
+        ));
+
+        // Releasing in a later window leaves the new window's counter alone
+        cache.release_quota_at("send", 60, 1, NOW + 60).unwrap();
+        cache
+            .consume_quota_at("send", Some(1), 60, 1, NOW + 60)
+            .unwrap();
+        cache.release_quota_at("send", 60, 5, NOW + 120).unwrap();
+        assert!(matches!(
+            cache
+                .consume_quota_at("send", Some(1), 60, 1, NOW + 61)
+                .unwrap(),
+            QuotaCheck::Exceeded(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unlimited_consume_and_usage() {
+        // A window that can't roll over while the test runs
+        const WINDOW: u64 = 1 << 40;
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+        cache.consume_quota("api", None, WINDOW, 5).unwrap();
+        cache.consume_quota("api", None, WINDOW, 7).unwrap();
+
+        assert_eq!(cache.quota_usage("api", WINDOW).unwrap().used, 12);
+        assert_eq!(cache.quota_usage("unused", WINDOW).unwrap().used, 0);
+    }
+}
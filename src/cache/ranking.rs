@@ -0,0 +1,442 @@
+use std::collections::HashSet;
+use std::ops::Deref;
+
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use super::error::CacheResult;
+use super::hangul::{HangulQuery, MatchKind};
+
+/// Most rows a search phase reads before scoring them in Rust, so a search that
+/// falls through to Hangul and fuzzy matching stays bounded in large workspaces
+pub(super) const MAX_SCAN_CANDIDATES: usize = 10_000;
+
+/// LIKE pattern (with `ESCAPE '\'`) for fields holding every character of `query`
+/// in order, as any skim/fzf-style match does; `None` for queries too short to be
+/// fuzzy-matched
+pub(super) fn subsequence_pattern(query: &str) -> Option<String> {
+    let query = query.trim().to_lowercase();
+    if query.chars().count() < 2 {
+        return None;
+    }
+    let mut pattern = String::from("%");
+    for c in query.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+        pattern.push('%');
+    }
+    Some(pattern)
+}
+
+/// Minimum normalized score for a fuzzy-only candidate to be returned
+const MIN_FUZZY_SCORE: f64 = 0.25;
+
+/// Upper bound for fuzzy and typo scores, so they never outrank substring matches
+const MAX_FUZZY_SCORE: f64 = 0.6;
+
+/// Shortest query that is checked for typos (transpositions, missing letters)
+const MIN_TYPO_QUERY_LEN: usize = 4;
+
+/// A search result with its relevance score (0.0-1.0) and the field that matched
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchMatch<T> {
+    pub item: T,
+    pub score: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched_field: Option<&'static str>,
+}
+
+impl<T> Deref for SearchMatch<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.item
+    }
+}
+
+/// Score and field of the best-matching field for a candidate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct FieldMatch {
+    pub field: &'static str,
+    pub score: f64,
+}
+
+/// Scores Hangul-aware matches on the same scale as `Scorer`
+pub(super) fn hangul_score(kind: MatchKind) -> f64 {
+    match kind {
+        MatchKind::Exact => 0.9,
+        MatchKind::Prefix => 0.75,
+        MatchKind::Substring => 0.6,
+    }
+}
+
+/// Ranks candidate fields against a query.
+///
+/// Exact, prefix and substring matches score 0.6-1.0 depending on how much of the
+/// field the query covers. Skim/fzf-style subsequence matches (e.g. "eng-plat" for
+/// "engineering-platform") and near-miss typos (e.g. "jonh" for "john") score at
+/// most `MAX_FUZZY_SCORE`.
+pub(super) struct Scorer {
+    query: String,
+    matcher: SkimMatcherV2,
+    perfect_score: Option<i64>,
+}
+
+impl Scorer {
+    pub(super) fn new(query: &str) -> Self {
+        let query = query.trim().to_lowercase();
+        let matcher = SkimMatcherV2::default().ignore_case();
+        let perfect_score = matcher.fuzzy_match(&query, &query).filter(|s| *s > 0);
+
+        Self {
+            query,
+            matcher,
+            perfect_score,
+        }
+    }
+
+    fn coverage(&self, field: &str) -> f64 {
+        self.query.chars().count() as f64 / field.chars().count().max(1) as f64
+    }
+
+    fn exact_score(&self, field: &str) -> Option<f64> {
+        if field == self.query {
+            Some(1.0)
+        } else if field.starts_with(&self.query) {
+            Some(0.8 + 0.15 * self.coverage(field))
+        } else if field.contains(&self.query) {
+            Some(0.6 + 0.2 * self.coverage(field))
+        } else {
+            None
+        }
+    }
+
+    fn fuzzy_score(&self, field: &str) -> Option<f64> {
+        if self.query.chars().count() < 2 {
+            return None;
+        }
+
+        let skim = self.perfect_score.and_then(|perfect| {
+            self.matcher
+                .fuzzy_match(field, &self.query)
+                .map(|score| (score as f64 / perfect as f64).clamp(0.0, 1.0))
+        });
+
+        let query_len = self.query.chars().count();
+        let typo = if query_len >= MIN_TYPO_QUERY_LEN {
+            let max_distance = if query_len <= 5 { 1 } else { 2 };
+            std::iter::once(field)
+                .chain(field.split(|c: char| !c.is_alphanumeric()))
+                .filter(|token| !token.is_empty())
+                .map(|token| osa_distance(&self.query, token))
+                .filter(|distance| *distance <= max_distance)
+                .min()
+                .map(|distance| 1.0 - distance as f64 / query_len as f64)
+        } else {
+            None
+        };
+
+        skim.into_iter()
+            .chain(typo)
+            .map(|score| score * MAX_FUZZY_SCORE)
+            .reduce(f64::max)
+    }
+
+    /// Best score across fields from any matching strategy
+    pub(super) fn best(&self, fields: &[(&'static str, Option<&str>)]) -> Option<FieldMatch> {
+        self.best_by(fields, |field| {
+            self.exact_score(field).or_else(|| self.fuzzy_score(field))
+        })
+    }
+
+    /// Best fuzzy-only score across fields, if it clears `MIN_FUZZY_SCORE`
+    pub(super) fn best_fuzzy(&self, fields: &[(&'static str, Option<&str>)]) -> Option<FieldMatch> {
+        self.best_by(fields, |field| self.fuzzy_score(field))
+            .filter(|m| m.score >= MIN_FUZZY_SCORE)
+    }
+
+    fn best_by(
+        &self,
+        fields: &[(&'static str, Option<&str>)],
+        score: impl Fn(&str) -> Option<f64>,
+    ) -> Option<FieldMatch> {
+        if self.query.is_empty() {
+            return None;
+        }
+
+        let mut best: Option<FieldMatch> = None;
+        for (name, value) in fields {
+            let Some(value) = value.map(str::to_lowercase).filter(|v| !v.is_empty()) else {
+                continue;
+            };
+            if let Some(score) = score(&value)
+                && best.is_none_or(|b| score > b.score)
+            {
+                best = Some(FieldMatch { field: name, score });
+            }
+        }
+        best
+    }
+}
+
+/// A cached row's id and searchable columns, read without its JSON data
+pub(super) struct Candidate<const N: usize> {
+    id: String,
+    fields: [Option<String>; N],
+}
+
+impl<const N: usize> Candidate<N> {
+    /// Maps a row selecting `id` followed by the `N` searchable columns
+    pub(super) fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        let mut fields: [Option<String>; N] = std::array::from_fn(|_| None);
+        for (idx, field) in fields.iter_mut().enumerate() {
+            *field = row.get(idx + 1)?;
+        }
+        Ok(Self {
+            id: row.get(0)?,
+            fields,
+        })
+    }
+}
+
+struct Hit {
+    id: String,
+    name: String,
+    score: f64,
+    field: Option<usize>,
+}
+
+/// Collects scored candidates from every search phase so they are ranked together
+pub(super) struct Ranking<const N: usize> {
+    field_names: [&'static str; N],
+    seen: HashSet<String>,
+    hits: Vec<Hit>,
+}
+
+impl<const N: usize> Ranking<N> {
+    /// `field_names` labels the searchable columns, in priority order
+    pub(super) fn new(field_names: [&'static str; N]) -> Self {
+        Self {
+            field_names,
+            seen: HashSet::new(),
+            hits: Vec::new(),
+        }
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.hits.len()
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.hits.is_empty()
+    }
+
+    fn labeled<'a>(&self, candidate: &'a Candidate<N>) -> [(&'static str, Option<&'a str>); N] {
+        std::array::from_fn(|idx| (self.field_names[idx], candidate.fields[idx].as_deref()))
+    }
+
+    fn push(&mut self, candidate: Candidate<N>, score: f64, field: Option<usize>) {
+        let Candidate { id, fields } = candidate;
+        let name = fields.into_iter().next().flatten().unwrap_or_default();
+        self.seen.insert(id.clone());
+        self.hits.push(Hit {
+            id,
+            name,
+            score,
+            field,
+        });
+    }
+
+    /// Adds substring and full-text matches, scored on their best field
+    pub(super) fn add_matches(&mut self, scorer: &Scorer, candidates: Vec<Candidate<N>>) {
+        for candidate in candidates {
+            if self.seen.contains(&candidate.id) {
+                continue;
+            }
+            let best = scorer.best(&self.labeled(&candidate));
+            let field = best.and_then(|m| self.field_names.iter().position(|f| *f == m.field));
+            self.push(candidate, best.map_or(0.5, |m| m.score), field);
+        }
+    }
+
+    /// Adds Hangul-aware (초성, jamo, romanization) matches, then skim/fzf-style fuzzy
+    /// matches for typos and abbreviations, from candidates no earlier phase found
+    pub(super) fn add_scanned(
+        &mut self,
+        scorer: &Scorer,
+        hangul_query: Option<&HangulQuery>,
+        candidates: Vec<Candidate<N>>,
+    ) {
+        for candidate in candidates {
+            if self.seen.contains(&candidate.id) {
+                continue;
+            }
+            let values: [Option<&str>; N] =
+                std::array::from_fn(|idx| candidate.fields[idx].as_deref());
+            if let Some((kind, idx)) = hangul_query.and_then(|q| q.match_fields(&values)) {
+                self.push(candidate, hangul_score(kind), Some(idx));
+            } else if let Some(m) = scorer.best_fuzzy(&self.labeled(&candidate)) {
+                let field = self.field_names.iter().position(|f| *f == m.field);
+                self.push(candidate, m.score, field);
+            }
+        }
+    }
+
+    /// Loads the best `limit` hits from `table`, ordered by score, then field
+    /// priority, then name
+    pub(super) fn load<T: DeserializeOwned>(
+        mut self,
+        conn: &Connection,
+        table: &str,
+        limit: usize,
+    ) -> CacheResult<Vec<SearchMatch<T>>> {
+        self.hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.field.unwrap_or(N).cmp(&b.field.unwrap_or(N)))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        self.hits.truncate(limit);
+
+        let mut stmt = conn.prepare_cached(&format!("SELECT data FROM {table} WHERE id = ?1"))?;
+        let mut results = Vec::with_capacity(self.hits.len());
+        for hit in self.hits {
+            // A sync may have replaced the row since it was scored
+            let Some(json) = stmt
+                .query_row(params![hit.id], |row| row.get::<_, String>(0))
+                .optional()?
+            else {
+                continue;
+            };
+            results.push(SearchMatch {
+                item: serde_json::from_str(&json)?,
+                score: hit.score,
+                matched_field: hit.field.map(|idx| self.field_names[idx]),
+            });
+        }
+        Ok(results)
+    }
+}
+
+/// Optimal string alignment distance (Levenshtein plus adjacent transpositions)
+fn osa_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("john", "john", 0)]
+    #[case("jonh", "john", 1)]
+    #[case("jhon", "john", 1)]
+    #[case("jon", "john", 1)]
+    #[case("kitten", "sitting", 3)]
+    #[case("", "abc", 3)]
+    fn test_osa_distance(#[case] a: &str, #[case] b: &str, #[case] expected: usize) {
+        assert_eq!(osa_distance(a, b), expected);
+    }
+
+    #[rstest]
+    #[case("Eng-Plat", Some("%e%n%g%-%p%l%a%t%"))]
+    #[case("50%_off", Some("%5%0%\\%%\\_%o%f%f%"))]
+    #[case("a", None)]
+    fn test_subsequence_pattern(#[case] query: &str, #[case] expected: Option<&str>) {
+        assert_eq!(subsequence_pattern(query).as_deref(), expected);
+    }
+
+    #[test]
+    fn test_best_exact_prefix_substring_ordering() {
+        let scorer = Scorer::new("dev");
+
+        let exact = scorer.best(&[("name", Some("dev"))]).unwrap();
+        let prefix = scorer.best(&[("name", Some("dev-team"))]).unwrap();
+        let substring = scorer.best(&[("name", Some("eng-dev"))]).unwrap();
+
+        assert_eq!(exact.score, 1.0);
+        assert!(prefix.score < exact.score);
+        assert!(substring.score < prefix.score);
+        assert!(substring.score >= 0.6);
+    }
+
+    #[test]
+    fn test_best_is_case_insensitive() {
+        let scorer = Scorer::new("Alice");
+        let m = scorer.best(&[("name", Some("ALICE"))]).unwrap();
+        assert_eq!(m.score, 1.0);
+    }
+
+    #[test]
+    fn test_best_reports_matched_field() {
+        let scorer = Scorer::new("platform");
+        let m = scorer
+            .best(&[
+                ("name", Some("eng")),
+                ("topic", None),
+                ("purpose", Some("Platform team")),
+            ])
+            .unwrap();
+        assert_eq!(m.field, "purpose");
+    }
+
+    #[rstest]
+    #[case("eng-plat", "engineering-platform")]
+    #[case("jonh", "john")]
+    #[case("jonh", "john.smith")]
+    #[case("alcie", "alice")]
+    fn test_best_fuzzy_matches(#[case] query: &str, #[case] field: &str) {
+        let scorer = Scorer::new(query);
+        let m = scorer.best_fuzzy(&[("name", Some(field))]).unwrap();
+        assert!(m.score >= MIN_FUZZY_SCORE, "{} -> {}", query, m.score);
+        assert!(m.score <= MAX_FUZZY_SCORE);
+    }
+
+    #[rstest]
+    #[case("xyz", "alice")]
+    #[case("test", "alice@example.com")]
+    #[case("a", "alice")]
+    fn test_best_fuzzy_rejects(#[case] query: &str, #[case] field: &str) {
+        let scorer = Scorer::new(query);
+        assert!(scorer.best_fuzzy(&[("name", Some(field))]).is_none());
+    }
+
+    #[test]
+    fn test_search_match_derefs_to_item() {
+        let m = SearchMatch {
+            item: "value".to_string(),
+            score: 1.0,
+            matched_field: Some("name"),
+        };
+        assert_eq!(m.len(), 5);
+    }
+}
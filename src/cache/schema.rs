@@ -1,10 +1,10 @@
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::params;
+use rusqlite::{Connection, OptionalExtension, params};
 
 use super::error::CacheResult;
 
-pub const SCHEMA_VERSION: i32 = 2;
+pub const SCHEMA_VERSION: i32 = 6;
 
 pub async fn initialize_schema(pool: &Pool<SqliteConnectionManager>) -> CacheResult<()> {
     let conn = pool.get()?;
@@ -117,11 +117,67 @@ pub async fn initialize_schema(pool: &Pool<SqliteConnectionManager>) -> CacheRes
 
         CREATE INDEX IF NOT EXISTS idx_locks_expires ON locks(expires_at);
 
+        -- Audit log of write calls made to Slack
+        CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+            client TEXT,
+            tool TEXT,
+            method TEXT NOT NULL,
+            channel TEXT,
+            payload_hash TEXT NOT NULL,
+            payload JSON NOT NULL,
+            response_ts TEXT,
+            outcome TEXT NOT NULL,
+            error TEXT
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_audit_log_created ON audit_log(created_at);
+
+        -- Messages posted by send_message, for idempotency keys and duplicate detection.
+        -- ts stays NULL until every part of a keyed send is posted; parts lists those
+        -- posted so far, and claimed is cleared when a send fails part-way.
+        CREATE TABLE IF NOT EXISTS sent_messages (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            idempotency_key TEXT UNIQUE,
+            channel TEXT NOT NULL,
+            thread_ts TEXT NOT NULL DEFAULT '',
+            content_hash TEXT NOT NULL,
+            ts TEXT,
+            parts JSON NOT NULL DEFAULT '[]',
+            claimed INTEGER NOT NULL DEFAULT 1,
+            created_at INTEGER NOT NULL DEFAULT (unixepoch())
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_sent_messages_content
+            ON sent_messages(channel, thread_ts, content_hash);
+
+        -- Messages to confirmation-required channels, waiting for confirm_draft
+        CREATE TABLE IF NOT EXISTS pending_drafts (
+            id TEXT PRIMARY KEY,
+            channel TEXT NOT NULL,
+            draft JSON NOT NULL,
+            created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+            expires_at INTEGER NOT NULL
+        );
+
         -- Initialize schema version
         INSERT OR IGNORE INTO metadata (key, value) VALUES ('schema_version', json(?));
         "
     )?;
 
+    // Tables that already existed kept their old shape
+    let stored_version: Option<i64> = conn
+        .query_row(
+            "SELECT value FROM metadata WHERE key = 'schema_version'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(version) = stored_version {
+        migrate(&conn, version)?;
+    }
+
     // Set schema version
     conn.execute(
         "INSERT OR REPLACE INTO metadata (key, value) VALUES ('schema_version', json(?))",
@@ -130,3 +186,87 @@ pub async fn initialize_schema(pool: &Pool<SqliteConnectionManager>) -> CacheRes
 
     Ok(())
 }
+
+/// Bring tables created by schema `from_version` up to date
+fn migrate(conn: &Connection, from_version: i64) -> CacheResult<()> {
+    // Version 6: sent_messages records the parts of split messages posted so far
+    if from_version < 6 {
+        let columns: Vec<String> = conn
+            .prepare("SELECT name FROM pragma_table_info('sent_messages')")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        if !columns.iter().any(|c| c == "parts") {
+            conn.execute_batch(
+                "ALTER TABLE sent_messages ADD COLUMN parts JSON NOT NULL DEFAULT '[]'",
+            )?;
+        }
+        if !columns.iter().any(|c| c == "claimed") {
+            conn.execute_batch(
+                "ALTER TABLE sent_messages ADD COLUMN claimed INTEGER NOT NULL DEFAULT 1",
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_migrates_sent_messages_from_version_5() {
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(SqliteConnectionManager::memory())
+            .unwrap();
+        pool.get()
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE metadata (
+                    key TEXT PRIMARY KEY,
+                    value JSON NOT NULL,
+                    updated_at INTEGER DEFAULT (unixepoch())
+                );
+                INSERT INTO metadata (key, value) VALUES ('schema_version', json(5));
+                CREATE TABLE sent_messages (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    idempotency_key TEXT UNIQUE,
+                    channel TEXT NOT NULL,
+                    thread_ts TEXT NOT NULL DEFAULT '',
+                    content_hash TEXT NOT NULL,
+                    ts TEXT,
+                    created_at INTEGER NOT NULL DEFAULT (unixepoch())
+                );
+                INSERT INTO sent_messages (idempotency_key, channel, content_hash, ts)
+                    VALUES ('standup', 'C0123ABCD', 'abc', '1.000001');",
+            )
+            .unwrap();
+
+        initialize_schema(&pool).await.unwrap();
+        // Running again on the migrated database is a no-op
+        initialize_schema(&pool).await.unwrap();
+
+        let conn = pool.get().unwrap();
+        let (ts, parts, claimed): (String, String, i64) = conn
+            .query_row(
+                "SELECT ts, parts, claimed FROM sent_messages WHERE idempotency_key = 'standup'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            (ts.as_str(), parts.as_str(), claimed),
+            ("1.000001", "[]", 1)
+        );
+
+        let version: i64 = conn
+            .query_row(
+                "SELECT value FROM metadata WHERE key = 'schema_version'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(version, i64::from(SCHEMA_VERSION));
+    }
+}
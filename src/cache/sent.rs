@@ -0,0 +1,347 @@
+use rusqlite::{OptionalExtension, params};
+
+use super::error::CacheResult;
+use super::sqlite_cache::SqliteCache;
+
+/// A message identified by its channel, thread and content hash
+#[derive(Debug, Clone, Copy)]
+pub struct SentMessageKey<'a> {
+    pub channel: &'a str,
+    pub thread_ts: Option<&'a str>,
+    /// Hash of the text and blocks
+    pub content_hash: &'a str,
+}
+
+/// Result of claiming an idempotency key before sending
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdempotencyClaim {
+    /// Key is new (or expired); the caller should send and then complete it
+    Claimed,
+    /// A message was already sent with this key
+    Sent { channel: String, ts: String },
+    /// Another call with this key hasn't finished yet
+    InFlight,
+    /// An earlier send with this key failed after posting some parts; the caller
+    /// now holds the key and should post the rest
+    Interrupted {
+        content_hash: String,
+        /// ts of the parts already posted, in order
+        posted: Vec<String>,
+    },
+}
+
+impl SqliteCache {
+    /// Reserve `key` for a send; keys older than `ttl_seconds` are forgotten
+    pub fn claim_idempotency_key(
+        &self,
+        key: &str,
+        message: SentMessageKey,
+        ttl_seconds: i64,
+    ) -> CacheResult<IdempotencyClaim> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "DELETE FROM sent_messages WHERE created_at <= unixepoch() - ?",
+            params![ttl_seconds],
+        )?;
+
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO sent_messages (idempotency_key, channel, thread_ts, content_hash)
+             VALUES (?, ?, ?, ?)",
+            params![
+                key,
+                message.channel,
+                message.thread_ts.unwrap_or_default(),
+                message.content_hash
+            ],
+        )?;
+
+        let claim = if inserted == 1 {
+            IdempotencyClaim::Claimed
+        } else {
+            let (channel, ts, claimed, content_hash, parts): (
+                String,
+                Option<String>,
+                bool,
+                String,
+                String,
+            ) = tx.query_row(
+                "SELECT channel, ts, claimed, content_hash, parts
+                 FROM sent_messages WHERE idempotency_key = ?",
+                params![key],
+                |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                    ))
+                },
+            )?;
+            match ts {
+                Some(ts) => IdempotencyClaim::Sent { channel, ts },
+                None if claimed => IdempotencyClaim::InFlight,
+                None => {
+                    tx.execute(
+                        "UPDATE sent_messages SET claimed = 1 WHERE idempotency_key = ?",
+                        params![key],
+                    )?;
+                    IdempotencyClaim::Interrupted {
+                        content_hash,
+                        posted: serde_json::from_str(&parts)?,
+                    }
+                }
+            }
+        };
+
+        tx.commit()?;
+        Ok(claim)
+    }
+
+    /// Give up a claimed key whose send failed, so a retry can use it. Keys with
+    /// parts already posted are kept, so the retry posts only the rest.
+    pub fn release_idempotency_key(&self, key: &str) -> CacheResult<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "DELETE FROM sent_messages
+             WHERE idempotency_key = ? AND ts IS NULL AND json_array_length(parts) = 0",
+            params![key],
+        )?;
+        conn.execute(
+            "UPDATE sent_messages SET claimed = 0 WHERE idempotency_key = ? AND ts IS NULL",
+            params![key],
+        )?;
+        Ok(())
+    }
+
+    /// Note that one more part of a keyed send was posted
+    pub fn record_sent_part(&self, key: &str, ts: &str) -> CacheResult<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "UPDATE sent_messages SET parts = json_insert(parts, '$[#]', ?)
+             WHERE idempotency_key = ? AND ts IS NULL",
+            params![ts, key],
+        )?;
+        Ok(())
+    }
+
+    /// Record a posted message (every part of it, for a keyed send), completing its
+    /// idempotency key if it had one, and forget messages older than `ttl_seconds`
+    pub fn record_sent_message(
+        &self,
+        idempotency_key: Option<&str>,
+        message: SentMessageKey,
+        ts: &str,
+        ttl_seconds: i64,
+    ) -> CacheResult<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "DELETE FROM sent_messages WHERE created_at <= unixepoch() - ? AND ts IS NOT NULL",
+            params![ttl_seconds],
+        )?;
+
+        let updated = match idempotency_key {
+            Some(key) => conn.execute(
+                "UPDATE sent_messages SET ts = ?, created_at = unixepoch()
+                 WHERE idempotency_key = ?",
+                params![ts, key],
+            )?,
+            None => 0,
+        };
+
+        if updated == 0 {
+            conn.execute(
+                "INSERT INTO sent_messages (idempotency_key, channel, thread_ts, content_hash, ts)
+                 VALUES (?, ?, ?, ?, ?)",
+                params![
+                    idempotency_key,
+                    message.channel,
+                    message.thread_ts.unwrap_or_default(),
+                    message.content_hash,
+                    ts
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// ts and age in seconds of the same message sent within the last `window_seconds`
+    pub fn find_recent_duplicate(
+        &self,
+        message: SentMessageKey,
+        window_seconds: i64,
+    ) -> CacheResult<Option<(String, i64)>> {
+        let conn = self.pool.get()?;
+        let duplicate = conn
+            .query_row(
+                "SELECT ts, unixepoch() - created_at FROM sent_messages
+                 WHERE channel = ? AND thread_ts = ? AND content_hash = ?
+                   AND ts IS NOT NULL AND created_at > unixepoch() - ?
+                 ORDER BY id DESC LIMIT 1",
+                params![
+                    message.channel,
+                    message.thread_ts.unwrap_or_default(),
+                    message.content_hash,
+                    window_seconds
+                ],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        Ok(duplicate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TTL: i64 = 3600;
+
+    fn message(thread_ts: Option<&str>) -> SentMessageKey<'_> {
+        SentMessageKey {
+            channel: "C123",
+            thread_ts,
+            content_hash: "hash-a",
+        }
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_lifecycle() {
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+
+        let first = cache
+            .claim_idempotency_key("key-1", message(None), TTL)
+            .unwrap();
+        assert_eq!(first, IdempotencyClaim::Claimed);
+
+        // A retry while the first call is still posting
+        let concurrent = cache
+            .claim_idempotency_key("key-1", message(None), TTL)
+            .unwrap();
+        assert_eq!(concurrent, IdempotencyClaim::InFlight);
+
+        cache
+            .record_sent_message(Some("key-1"), message(None), "1700000000.000100", TTL)
+            .unwrap();
+        let retry = cache
+            .claim_idempotency_key("key-1", message(None), TTL)
+            .unwrap();
+        assert_eq!(
+            retry,
+            IdempotencyClaim::Sent {
+                channel: "C123".to_string(),
+                ts: "1700000000.000100".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_released_key_can_be_claimed_again() {
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+
+        cache
+            .claim_idempotency_key("key-1", message(None), TTL)
+            .unwrap();
+        cache.release_idempotency_key("key-1").unwrap();
+
+        let claim = cache
+            .claim_idempotency_key("key-1", message(None), TTL)
+            .unwrap();
+        assert_eq!(claim, IdempotencyClaim::Claimed);
+    }
+
+    #[tokio::test]
+    async fn test_partly_sent_key_resumes() {
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+        cache
+            .claim_idempotency_key("key-1", message(None), TTL)
+            .unwrap();
+        cache.record_sent_part("key-1", "1.1").unwrap();
+        cache.record_sent_part("key-1", "1.2").unwrap();
+        cache.release_idempotency_key("key-1").unwrap();
+
+        let claim = cache
+            .claim_idempotency_key("key-1", message(None), TTL)
+            .unwrap();
+        assert_eq!(
+            claim,
+            IdempotencyClaim::Interrupted {
+                content_hash: "hash-a".to_string(),
+                posted: vec!["1.1".to_string(), "1.2".to_string()],
+            }
+        );
+
+        // The resumed send holds the key until it finishes
+        let concurrent = cache
+            .claim_idempotency_key("key-1", message(None), TTL)
+            .unwrap();
+        assert_eq!(concurrent, IdempotencyClaim::InFlight);
+    }
+
+    #[tokio::test]
+    async fn test_expired_key_is_forgotten() {
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+        cache
+            .claim_idempotency_key("key-1", message(None), TTL)
+            .unwrap();
+        cache
+            .record_sent_message(Some("key-1"), message(None), "1700000000.000100", TTL)
+            .unwrap();
+
+        let claim = cache
+            .claim_idempotency_key("key-1", message(None), 0)
+            .unwrap();
+        assert_eq!(claim, IdempotencyClaim::Claimed);
+    }
+
+    #[tokio::test]
+    async fn test_find_recent_duplicate() {
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+        cache
+            .record_sent_message(None, message(Some("1.0")), "1700000000.000100", TTL)
+            .unwrap();
+
+        let (ts, age) = cache
+            .find_recent_duplicate(message(Some("1.0")), 60)
+            .unwrap()
+            .unwrap();
+        assert_eq!(ts, "1700000000.000100");
+        assert!(age <= 1);
+
+        // Different thread, different content, or outside the window
+        assert!(
+            cache
+                .find_recent_duplicate(message(None), 60)
+                .unwrap()
+                .is_none()
+        );
+        let other = SentMessageKey {
+            content_hash: "hash-b",
+            ..message(Some("1.0"))
+        };
+        assert!(cache.find_recent_duplicate(other, 60).unwrap().is_none());
+        assert!(
+            cache
+                .find_recent_duplicate(message(Some("1.0")), 0)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_send_is_not_a_duplicate() {
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+        cache
+            .claim_idempotency_key("key-1", message(None), TTL)
+            .unwrap();
+
+        assert!(
+            cache
+                .find_recent_duplicate(message(None), 60)
+                .unwrap()
+                .is_none()
+        );
+    }
+}
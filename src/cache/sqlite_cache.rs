@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use r2d2::Pool;
@@ -11,16 +11,31 @@ use super::schema;
 pub struct SqliteCache {
     pub(super) pool: Pool<SqliteConnectionManager>,
     pub(super) instance_id: String,
+    /// Whether user emails are kept in the cache and its search index
+    pub(super) index_emails: bool,
 }
 
 impl SqliteCache {
     pub async fn new(path: impl AsRef<Path>) -> CacheResult<Self> {
         let path = path.as_ref();
 
-        // Ensure parent directory exists
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
+        // Each pooled ":memory:" connection would otherwise open its own empty database:
+        // the schema lands on whichever connection created it, and queries on the
+        // others fail with "no such table". Give every in-memory cache a named
+        // database shared by its connections instead.
+        let path: PathBuf = if path == Path::new(":memory:") {
+            format!(
+                "file:memdb-{}?mode=memory&cache=shared",
+                uuid::Uuid::new_v4()
+            )
+            .into()
+        } else {
+            // Ensure parent directory exists
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            path.to_path_buf()
+        };
 
         let manager = SqliteConnectionManager::file(path).with_init(|conn| {
             // Enable WAL mode for better concurrency
@@ -42,7 +57,11 @@ impl SqliteCache {
 
         let instance_id = uuid::Uuid::new_v4().to_string();
 
-        let cache = Self { pool, instance_id };
+        let cache = Self {
+            pool,
+            instance_id,
+            index_emails: true,
+        };
 
         schema::initialize_schema(&cache.pool).await?;
         Ok(cache)
@@ -81,6 +100,9 @@ mod tests {
         assert!(tables.contains(&"channels".to_string()));
         assert!(tables.contains(&"locks".to_string()));
         assert!(tables.contains(&"metadata".to_string()));
+        assert!(tables.contains(&"audit_log".to_string()));
+        assert!(tables.contains(&"sent_messages".to_string()));
+        assert!(tables.contains(&"pending_drafts".to_string()));
     }
 
     #[tokio::test]
@@ -0,0 +1,333 @@
+use chrono::{DateTime, Utc};
+use rusqlite::types::Value;
+use rusqlite::{OptionalExtension, params};
+use serde::Serialize;
+
+use super::error::CacheResult;
+use super::schema::SCHEMA_VERSION;
+use super::sqlite_cache::SqliteCache;
+
+/// Snapshot of what the cache currently holds, used for diagnostics
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStatus {
+    pub schema_version: Option<i64>,
+    pub expected_schema_version: i32,
+    pub instance_id: String,
+    pub counts: CacheCounts,
+    pub users_sync: SyncStatus,
+    pub channels_sync: SyncStatus,
+    pub storage: StorageStatus,
+    pub locks: Vec<LockInfo>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CacheCounts {
+    pub users: usize,
+    pub bots: usize,
+    pub deleted_users: usize,
+    pub channels: usize,
+    pub private_channels: usize,
+    pub archived_channels: usize,
+    pub dms: usize,
+    pub mpims: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncStatus {
+    pub last_sync: Option<String>,
+    pub age_seconds: Option<i64>,
+    pub ttl_hours: u64,
+    pub is_stale: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageStatus {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub db_path: Option<String>,
+    pub db_size_bytes: u64,
+    pub wal_size_bytes: u64,
+    pub page_count: i64,
+    pub page_size: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LockInfo {
+    pub key: String,
+    pub instance_id: String,
+    pub acquired_at: i64,
+    pub expires_at: i64,
+    pub held_by_this_instance: bool,
+    pub is_expired: bool,
+}
+
+impl SqliteCache {
+    /// Collect counts, sync times, storage sizes and active locks for diagnostics
+    pub fn get_status(
+        &self,
+        users_ttl_hours: u64,
+        channels_ttl_hours: u64,
+    ) -> CacheResult<CacheStatus> {
+        let conn = self.pool.get()?;
+
+        let schema_version: Option<i64> = conn
+            .query_row(
+                "SELECT value FROM metadata WHERE key = 'schema_version'",
+                [],
+                |row| row.get::<_, Value>(0),
+            )
+            .optional()?
+            .and_then(|value| match value {
+                Value::Integer(v) => Some(v),
+                Value::Text(v) => v.trim_matches('"').parse().ok(),
+                _ => None,
+            });
+
+        let counts = conn.query_row(
+            "SELECT
+                (SELECT COUNT(*) FROM users WHERE (is_bot = 0 OR is_bot IS NULL)
+                    AND (json_extract(data, '$.deleted') = 0 OR json_extract(data, '$.deleted') IS NULL)),
+                (SELECT COUNT(*) FROM users WHERE is_bot = 1),
+                (SELECT COUNT(*) FROM users WHERE json_extract(data, '$.deleted') = 1),
+                (SELECT COUNT(*) FROM channels WHERE (is_im = 0 OR is_im IS NULL)
+                    AND (is_mpim = 0 OR is_mpim IS NULL)),
+                (SELECT COUNT(*) FROM channels WHERE is_private = 1
+                    AND (is_im = 0 OR is_im IS NULL) AND (is_mpim = 0 OR is_mpim IS NULL)),
+                (SELECT COUNT(*) FROM channels WHERE is_archived = 1),
+                (SELECT COUNT(*) FROM channels WHERE is_im = 1),
+                (SELECT COUNT(*) FROM channels WHERE is_mpim = 1)",
+            [],
+            |row| {
+                Ok(CacheCounts {
+                    users: row.get::<_, i64>(0)? as usize,
+                    bots: row.get::<_, i64>(1)? as usize,
+                    deleted_users: row.get::<_, i64>(2)? as usize,
+                    channels: row.get::<_, i64>(3)? as usize,
+                    private_channels: row.get::<_, i64>(4)? as usize,
+                    archived_channels: row.get::<_, i64>(5)? as usize,
+                    dms: row.get::<_, i64>(6)? as usize,
+                    mpims: row.get::<_, i64>(7)? as usize,
+                })
+            },
+        )?;
+
+        let users_sync = self.sync_status(&conn, "last_user_sync", users_ttl_hours)?;
+        let channels_sync = self.sync_status(&conn, "last_channel_sync", channels_ttl_hours)?;
+
+        let page_count: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+        let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+        let db_path: Option<String> = conn
+            .query_row(
+                "SELECT file FROM pragma_database_list WHERE name = 'main'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?
+            .filter(|path: &String| !path.is_empty());
+
+        // In-memory databases have no files, so fall back to the page accounting
+        let (db_size_bytes, wal_size_bytes) = match &db_path {
+            Some(path) => (
+                file_size(path).unwrap_or((page_count * page_size) as u64),
+                file_size(&format!("{}-wal", path)).unwrap_or(0),
+            ),
+            None => ((page_count * page_size) as u64, 0),
+        };
+
+        let now = Utc::now().timestamp();
+        let mut stmt = conn.prepare(
+            "SELECT key, instance_id, acquired_at, expires_at FROM locks ORDER BY acquired_at",
+        )?;
+        let locks = stmt
+            .query_map([], |row| {
+                let instance_id: String = row.get(1)?;
+                let expires_at: i64 = row.get(3)?;
+                Ok(LockInfo {
+                    key: row.get(0)?,
+                    held_by_this_instance: instance_id == self.instance_id,
+                    instance_id,
+                    acquired_at: row.get(2)?,
+                    expires_at,
+                    is_expired: expires_at < now,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(CacheStatus {
+            schema_version,
+            expected_schema_version: SCHEMA_VERSION,
+            instance_id: self.instance_id.clone(),
+            counts,
+            users_sync,
+            channels_sync,
+            storage: StorageStatus {
+                db_path,
+                db_size_bytes,
+                wal_size_bytes,
+                page_count,
+                page_size,
+            },
+            locks,
+        })
+    }
+
+    fn sync_status(
+        &self,
+        conn: &rusqlite::Connection,
+        key: &str,
+        ttl_hours: u64,
+    ) -> CacheResult<SyncStatus> {
+        let last_sync: Option<DateTime<Utc>> = conn
+            .query_row(
+                "SELECT value FROM metadata WHERE key = ?1",
+                params![key],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?
+            .and_then(|value| DateTime::parse_from_rfc3339(value.trim_matches('"')).ok())
+            .map(|dt| dt.with_timezone(&Utc));
+
+        let age_seconds = last_sync.map(|dt| (Utc::now() - dt).num_seconds());
+        let is_stale = match age_seconds {
+            Some(age) => age >= (ttl_hours as i64) * 3600,
+            None => true,
+        };
+
+        Ok(SyncStatus {
+            last_sync: last_sync.map(|dt| dt.to_rfc3339()),
+            age_seconds,
+            ttl_hours,
+            is_stale,
+        })
+    }
+}
+
+fn file_size(path: &str) -> Option<u64> {
+    std::fs::metadata(path).ok().map(|m| m.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slack::types::{SlackChannel, SlackUser, SlackUserProfile};
+
+    async fn setup_cache() -> SqliteCache {
+        SqliteCache::new(":memory:")
+            .await
+            .expect("Failed to create test cache")
+    }
+
+    fn create_test_user(id: &str, name: &str, is_bot: bool, deleted: bool) -> SlackUser {
+        SlackUser {
+            id: id.to_string(),
+            name: name.to_string(),
+            is_bot,
+            is_admin: false,
+            deleted,
+            tz: None,
+            tz_offset: None,
+            profile: Some(SlackUserProfile {
+                real_name: Some(name.to_string()),
+                display_name: Some(name.to_string()),
+                email: None,
+                status_text: None,
+                status_emoji: None,
+            }),
+        }
+    }
+
+    fn create_test_channel(id: &str, name: &str, is_archived: bool, is_im: bool) -> SlackChannel {
+        SlackChannel {
+            id: id.to_string(),
+            name: name.to_string(),
+            is_channel: !is_im,
+            is_private: false,
+            is_archived,
+            is_general: false,
+            is_im,
+            is_mpim: false,
+            is_member: true,
+            created: None,
+            creator: None,
+            topic: None,
+            purpose: None,
+            num_members: Some(10),
+            previous_names: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_status_empty_cache() {
+        let cache = setup_cache().await;
+        let status = cache.get_status(24, 24).unwrap();
+
+        assert_eq!(status.schema_version, Some(SCHEMA_VERSION as i64));
+        assert_eq!(status.counts.users, 0);
+        assert_eq!(status.counts.channels, 0);
+        assert!(status.users_sync.is_stale);
+        assert!(status.users_sync.last_sync.is_none());
+        assert!(status.channels_sync.is_stale);
+        assert!(status.storage.db_path.is_none());
+        assert!(status.storage.db_size_bytes > 0);
+        assert!(status.locks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_status_counts_by_type() {
+        let cache = setup_cache().await;
+        cache
+            .save_users(vec![
+                create_test_user("U1", "alice", false, false),
+                create_test_user("U2", "bob", false, true),
+                create_test_user("B1", "deploybot", true, false),
+            ])
+            .await
+            .unwrap();
+        cache
+            .save_channels(vec![
+                create_test_channel("C1", "general", false, false),
+                create_test_channel("C2", "old-project", true, false),
+                create_test_channel("D1", "dm", false, true),
+            ])
+            .await
+            .unwrap();
+
+        let status = cache.get_status(24, 24).unwrap();
+        assert_eq!(status.counts.users, 1);
+        assert_eq!(status.counts.bots, 1);
+        assert_eq!(status.counts.deleted_users, 1);
+        assert_eq!(status.counts.channels, 2);
+        assert_eq!(status.counts.archived_channels, 1);
+        assert_eq!(status.counts.dms, 1);
+        assert!(!status.users_sync.is_stale);
+        assert!(!status.channels_sync.is_stale);
+        assert!(status.users_sync.age_seconds.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_get_status_zero_ttl_is_stale() {
+        let cache = setup_cache().await;
+        cache
+            .save_users(vec![create_test_user("U1", "alice", false, false)])
+            .await
+            .unwrap();
+
+        let status = cache.get_status(0, 24).unwrap();
+        assert!(status.users_sync.is_stale);
+        assert_eq!(status.users_sync.ttl_hours, 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_status_reports_locks() {
+        let cache = setup_cache().await;
+        cache.acquire_lock("users_update").await.unwrap();
+
+        let status = cache.get_status(24, 24).unwrap();
+        assert_eq!(status.locks.len(), 1);
+        assert_eq!(status.locks[0].key, "users_update");
+        assert_eq!(status.locks[0].instance_id, cache.instance_id);
+        assert!(status.locks[0].held_by_this_instance);
+        assert!(!status.locks[0].is_expired);
+
+        cache.release_lock("users_update").await.unwrap();
+    }
+}
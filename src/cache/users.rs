@@ -1,12 +1,28 @@
 use super::error::{CacheError, CacheResult};
 use chrono::Utc;
-use rusqlite::params;
+use rusqlite::{OptionalExtension, params};
 
 use crate::slack::types::SlackUser;
 
+use super::hangul::HangulQuery;
+use super::ranking::{
+    Candidate, MAX_SCAN_CANDIDATES, Ranking, Scorer, SearchMatch, subsequence_pattern,
+};
 use super::sqlite_cache::SqliteCache;
 
 impl SqliteCache {
+    /// Stop keeping user emails: drop them from cached profiles (and so from
+    /// `users_fts`), and strip them from every later save
+    pub fn exclude_emails(&mut self) -> CacheResult<()> {
+        self.index_emails = false;
+        let conn = self.pool.get()?;
+        conn.execute(
+            "UPDATE users SET data = json_remove(data, '$.profile.email') WHERE email IS NOT NULL",
+            [],
+        )?;
+        Ok(())
+    }
+
     // User operations
     pub async fn save_users(&self, users: Vec<SlackUser>) -> CacheResult<()> {
         if users.is_empty() {
@@ -33,7 +49,12 @@ impl SqliteCache {
             let tx = conn.unchecked_transaction()?;
             let mut successful_count = 0;
 
-            for user in users {
+            for mut user in users {
+                if !self.index_emails
+                    && let Some(profile) = user.profile.as_mut()
+                {
+                    profile.email = None;
+                }
                 if let Ok(json) = serde_json::to_string(&user)
                     && tx.execute(
                         "INSERT INTO users_new (id, data) VALUES (?, ?)",
@@ -67,7 +88,10 @@ impl SqliteCache {
     pub fn get_users(&self) -> CacheResult<Vec<SlackUser>> {
         let conn = self.pool.get()?;
         let mut stmt = conn.prepare_cached(
-            "SELECT data FROM users WHERE is_bot = 0 OR is_bot IS NULL ORDER BY name",
+            "SELECT data FROM users
+             WHERE (is_bot = 0 OR is_bot IS NULL)
+             AND (json_extract(data, '$.deleted') = 0 OR json_extract(data, '$.deleted') IS NULL)
+             ORDER BY name",
         )?;
 
         let users = stmt
@@ -86,6 +110,34 @@ impl SqliteCache {
         Ok(users)
     }
 
+    /// Replace the cached user group handles, keyed by group ID
+    pub fn save_usergroups(&self, usergroups: &[(String, String)]) -> CacheResult<()> {
+        let handles: serde_json::Map<String, serde_json::Value> = usergroups
+            .iter()
+            .map(|(id, handle)| (id.clone(), handle.clone().into()))
+            .collect();
+        let conn = self.pool.get()?;
+        conn.execute(
+            "INSERT OR REPLACE INTO metadata (key, value) VALUES ('usergroups', ?)",
+            params![serde_json::to_string(&handles)?],
+        )?;
+        Ok(())
+    }
+
+    /// Handle (without `@`) of a cached user group
+    pub fn get_usergroup_handle(&self, group_id: &str) -> CacheResult<Option<String>> {
+        let conn = self.pool.get()?;
+        let handle = conn
+            .query_row(
+                "SELECT json_extract(value, '$.' || json_quote(?1)) FROM metadata
+                 WHERE key = 'usergroups'",
+                params![group_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(handle.flatten())
+    }
+
     pub fn get_user_by_id(&self, user_id: &str) -> CacheResult<Option<SlackUser>> {
         let conn = self.pool.get()?;
         let mut stmt = conn.prepare_cached("SELECT data FROM users WHERE id = ?1")?;
@@ -113,20 +165,26 @@ impl SqliteCache {
         query: &str,
         limit: usize,
         include_bots: bool,
-    ) -> CacheResult<Vec<SlackUser>> {
+        include_deleted: bool,
+    ) -> CacheResult<Vec<SearchMatch<SlackUser>>> {
         let conn = self.pool.get()?;
 
+        let mut user_filter = String::new();
+        if !include_bots {
+            user_filter.push_str(" AND (is_bot = 0 OR is_bot IS NULL)");
+        }
+        if !include_deleted {
+            user_filter.push_str(
+                " AND (json_extract(data, '$.deleted') = 0 OR json_extract(data, '$.deleted') IS NULL)",
+            );
+        }
+
         // Handle empty or special queries
         if query.trim().is_empty() {
             // Return all users for empty query
-            let bot_filter = if include_bots {
-                ""
-            } else {
-                "WHERE is_bot = 0 OR is_bot IS NULL"
-            };
             let sql = format!(
-                "SELECT data FROM users {} ORDER BY name LIMIT ?1",
-                bot_filter
+                "SELECT data FROM users WHERE 1=1 {} ORDER BY name LIMIT ?1",
+                user_filter
             );
             let mut stmt = conn.prepare_cached(&sql)?;
 
@@ -143,18 +201,22 @@ impl SqliteCache {
                 })?
                 .collect::<Result<Vec<_>, _>>()?;
 
-            return Ok(users);
+            return Ok(users
+                .into_iter()
+                .map(|item| SearchMatch {
+                    item,
+                    score: 1.0,
+                    matched_field: None,
+                })
+                .collect());
         }
 
-        // Phase 1: LIKE substring match with exact match priority
-        let bot_filter = if include_bots {
-            ""
-        } else {
-            "AND (is_bot = 0 OR is_bot IS NULL)"
-        };
+        let scorer = Scorer::new(query);
+        let mut ranking = Ranking::new(USER_FIELDS);
 
+        // Phase 1: LIKE substring match with exact match priority
         let like_sql = format!(
-            "SELECT data,
+            "SELECT id, name, display_name, real_name, email,
                 CASE
                     WHEN lower(name) = lower(?1) THEN 0
                     WHEN lower(display_name) = lower(?1) THEN 1
@@ -166,63 +228,89 @@ impl SqliteCache {
              AND (name LIKE ?2 OR display_name LIKE ?2 OR real_name LIKE ?2 OR email LIKE ?2)
              ORDER BY priority, name
              LIMIT ?3",
-            bot_filter
+            user_filter
         );
 
         let like_pattern = format!("%{query}%");
         let like_result = conn.prepare_cached(&like_sql).and_then(|mut stmt| {
-            stmt.query_map(params![query, like_pattern, limit], |row| {
-                let json: String = row.get(0)?;
-                serde_json::from_str(&json).map_err(|e| {
-                    rusqlite::Error::FromSqlConversionFailure(
-                        0,
-                        rusqlite::types::Type::Text,
-                        Box::new(e),
-                    )
-                })
-            })?
+            stmt.query_map(
+                params![query, like_pattern, MAX_SCAN_CANDIDATES],
+                Candidate::from_row,
+            )?
             .collect::<Result<Vec<_>, _>>()
         })?;
-
-        if !like_result.is_empty() {
-            return Ok(like_result);
-        }
+        ranking.add_matches(&scorer, like_result);
 
         // Phase 2: FTS5 fuzzy match (only if no LIKE results)
         let processed_query = self.process_fts_query(query);
-        if processed_query.is_empty() {
-            return Ok(vec![]);
+        if ranking.is_empty() && !processed_query.is_empty() {
+            let fts_sql = format!(
+                "SELECT u.id, u.name, u.display_name, u.real_name, u.email
+                 FROM users u
+                 JOIN users_fts f ON u.rowid = f.rowid
+                 WHERE users_fts MATCH ?1
+                 {}
+                 ORDER BY rank
+                 LIMIT ?2",
+                user_filter
+            );
+
+            let fts_result = conn.prepare_cached(&fts_sql).and_then(|mut stmt| {
+                stmt.query_map(
+                    params![processed_query, MAX_SCAN_CANDIDATES],
+                    Candidate::from_row,
+                )?
+                .collect::<Result<Vec<_>, _>>()
+            })?;
+            ranking.add_matches(&scorer, fts_result);
         }
 
-        let fts_sql = format!(
-            "SELECT u.data
-             FROM users u
-             JOIN users_fts f ON u.rowid = f.rowid
-             WHERE users_fts MATCH ?1
-             {}
-             ORDER BY rank
-             LIMIT ?2",
-            bot_filter
-        );
+        // Phases 3 and 4 score capped scans in Rust, so only run them when slots remain
+        if ranking.len() < limit {
+            let hangul_query = HangulQuery::new(query);
+
+            // Fuzzy matches hold the query's characters in order, so SQL narrows
+            // them down however many users the workspace has
+            if let Some(pattern) = subsequence_pattern(query) {
+                let sql = format!(
+                    "SELECT id, name, display_name, real_name, email FROM users
+                     WHERE 1=1 {}
+                     AND (name LIKE ?1 ESCAPE '\\' OR display_name LIKE ?1 ESCAPE '\\'
+                          OR real_name LIKE ?1 ESCAPE '\\' OR email LIKE ?1 ESCAPE '\\')
+                     ORDER BY name
+                     LIMIT ?2",
+                    user_filter
+                );
+                let candidates = conn.prepare_cached(&sql).and_then(|mut stmt| {
+                    stmt.query_map(params![pattern, MAX_SCAN_CANDIDATES], Candidate::from_row)?
+                        .collect::<Result<Vec<_>, _>>()
+                })?;
+                ranking.add_scanned(&scorer, hangul_query.as_ref(), candidates);
+            }
 
-        let fts_result = conn.prepare_cached(&fts_sql).and_then(|mut stmt| {
-            stmt.query_map(params![processed_query, limit], |row| {
-                let json: String = row.get(0)?;
-                serde_json::from_str(&json).map_err(|e| {
-                    rusqlite::Error::FromSqlConversionFailure(
-                        0,
-                        rusqlite::types::Type::Text,
-                        Box::new(e),
-                    )
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()
-        })?;
+            // Hangul (초성, romanization) and typo matches can't be narrowed that
+            // way, so they come from the first users by name
+            let sql = format!(
+                "SELECT id, name, display_name, real_name, email FROM users
+                 WHERE 1=1 {}
+                 ORDER BY name
+                 LIMIT ?1",
+                user_filter
+            );
+            let candidates = conn.prepare_cached(&sql).and_then(|mut stmt| {
+                stmt.query_map(params![MAX_SCAN_CANDIDATES], Candidate::from_row)?
+                    .collect::<Result<Vec<_>, _>>()
+            })?;
+            ranking.add_scanned(&scorer, hangul_query.as_ref(), candidates);
+        }
 
-        Ok(fts_result)
+        ranking.load(&conn, "users", limit)
     }
 }
 
+/// Searchable user columns in priority order
+const USER_FIELDS: [&str; 4] = ["name", "display_name", "real_name", "email"];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -237,6 +325,8 @@ mod tests {
             is_bot,
             is_admin: false,
             deleted: false,
+            tz: None,
+            tz_offset: None,
             profile: Some(SlackUserProfile {
                 real_name: Some(format!("Real {name}")),
                 display_name: Some(name.to_string()),
@@ -265,6 +355,26 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_usergroup_handles() {
+        let cache = setup_cache().await;
+        assert_eq!(cache.get_usergroup_handle("S0OPS").unwrap(), None);
+
+        cache
+            .save_usergroups(&[("S0OPS".to_string(), "ops".to_string())])
+            .unwrap();
+        assert_eq!(
+            cache.get_usergroup_handle("S0OPS").unwrap().as_deref(),
+            Some("ops")
+        );
+
+        // A refresh replaces the whole set
+        cache
+            .save_usergroups(&[("S0DEV".to_string(), "dev".to_string())])
+            .unwrap();
+        assert_eq!(cache.get_usergroup_handle("S0OPS").unwrap(), None);
+    }
+
     #[tokio::test]
     async fn test_save_users_single_user() {
         let cache = setup_cache().await;
@@ -332,6 +442,58 @@ mod tests {
         assert!(bob.is_none()); // Bob should be removed
     }
 
+    #[tokio::test]
+    async fn test_exclude_emails() {
+        let mut cache = setup_cache().await;
+        cache
+            .save_users(vec![create_test_user(
+                "U123",
+                "alice",
+                Some("alice@example.com"),
+                false,
+            )])
+            .await
+            .unwrap();
+        assert_eq!(
+            cache
+                .search_users("alice@example.com", 10, false, false)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        cache.exclude_emails().unwrap();
+        assert!(
+            cache
+                .search_users("alice@example.com", 10, false, false)
+                .unwrap()
+                .is_empty()
+        );
+        let alice = cache.get_user_by_id("U123").unwrap().unwrap();
+        assert!(alice.profile.unwrap().email.is_none());
+
+        // Later syncs don't bring emails back
+        cache
+            .save_users(vec![create_test_user(
+                "U456",
+                "bob",
+                Some("bob@example.com"),
+                false,
+            )])
+            .await
+            .unwrap();
+        assert!(
+            cache
+                .search_users("bob@example.com", 10, false, false)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            cache.search_users("bob", 10, false, false).unwrap().len(),
+            1
+        );
+    }
+
     #[tokio::test]
     async fn test_get_users_filters_bots() {
         let cache = setup_cache().await;
@@ -409,7 +571,7 @@ mod tests {
         ];
         cache.save_users(users).await.unwrap();
 
-        let results = cache.search_users(query, 10, false).unwrap();
+        let results = cache.search_users(query, 10, false, false).unwrap();
         assert_eq!(results.len(), expected_count);
     }
 
@@ -422,7 +584,7 @@ mod tests {
         ];
         cache.save_users(users).await.unwrap();
 
-        let results = cache.search_users("example.com", 10, false).unwrap();
+        let results = cache.search_users("example.com", 10, false, false).unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "alice");
     }
@@ -437,7 +599,7 @@ mod tests {
         cache.save_users(users).await.unwrap();
 
         // Empty query should return all non-bot users
-        let results = cache.search_users("", 10, false).unwrap();
+        let results = cache.search_users("", 10, false, false).unwrap();
         assert_eq!(results.len(), 2);
     }
 
@@ -451,7 +613,7 @@ mod tests {
         ];
         cache.save_users(users).await.unwrap();
 
-        let results = cache.search_users("", 2, false).unwrap();
+        let results = cache.search_users("", 2, false, false).unwrap();
         assert_eq!(results.len(), 2);
     }
 
@@ -465,7 +627,7 @@ mod tests {
         cache.save_users(users).await.unwrap();
 
         // Search should not return bots by default
-        let results = cache.search_users("test", 10, false).unwrap();
+        let results = cache.search_users("test", 10, false, false).unwrap();
         assert_eq!(results.len(), 0);
     }
 
@@ -481,7 +643,7 @@ mod tests {
         cache.save_users(users).await.unwrap();
 
         // Special characters are stripped by process_fts_query, so "alice*@#$" becomes "alice"
-        let results = cache.search_users("alice*@#$", 10, false).unwrap();
+        let results = cache.search_users("alice*@#$", 10, false, false).unwrap();
         // Should find alice since special chars are stripped
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "alice");
@@ -497,10 +659,10 @@ mod tests {
         cache.save_users(users).await.unwrap();
 
         // LIKE search should be case-insensitive
-        let results = cache.search_users("alice", 10, false).unwrap();
+        let results = cache.search_users("alice", 10, false, false).unwrap();
         assert_eq!(results.len(), 1);
 
-        let results = cache.search_users("bob", 10, false).unwrap();
+        let results = cache.search_users("bob", 10, false, false).unwrap();
         assert_eq!(results.len(), 1);
     }
 
@@ -515,12 +677,52 @@ mod tests {
         ];
         cache.save_users(users).await.unwrap();
 
-        let results = cache.search_users("john", 10, false).unwrap();
+        let results = cache.search_users("john", 10, false, false).unwrap();
         // Exact match "john" should be first
         assert_eq!(results.len(), 3);
         assert_eq!(results[0].name, "john");
     }
 
+    #[tokio::test]
+    async fn test_search_users_ranks_substring_matches_by_score() {
+        let cache = setup_cache().await;
+        let users = vec![
+            create_test_user("U1", "a-ops", None, false),
+            create_test_user("U2", "ops-team", None, false),
+        ];
+        cache.save_users(users).await.unwrap();
+
+        // SQL returns these by name; the prefix match must still come first
+        let results = cache.search_users("ops", 10, false, false).unwrap();
+        let ids: Vec<&str> = results.iter().map(|u| u.id.as_str()).collect();
+        assert_eq!(ids, vec!["U2", "U1"]);
+        assert!(results[0].score > results[1].score);
+
+        let results = cache.search_users("ops", 1, false, false).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "U2");
+    }
+
+    #[tokio::test]
+    async fn test_search_users_fuzzy_finds_matches_past_scan_cap() {
+        let cache = setup_cache().await;
+        let mut users: Vec<SlackUser> = (0..=MAX_SCAN_CANDIDATES)
+            .map(|i| create_test_user(&format!("U{i:06}"), &format!("filler-{i:06}"), None, false))
+            .collect();
+        // Stored last and sorted last, past the first MAX_SCAN_CANDIDATES rows
+        users.push(create_test_user(
+            "U999999",
+            "zz-engineering-platform",
+            None,
+            false,
+        ));
+        cache.save_users(users).await.unwrap();
+
+        let results = cache.search_users("eng-plat", 10, false, false).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "U999999");
+    }
+
     // New test: Include bots option
     #[tokio::test]
     async fn test_search_users_with_include_bots() {
@@ -532,7 +734,7 @@ mod tests {
         cache.save_users(users).await.unwrap();
 
         // With include_bots=true, should return bots
-        let results = cache.search_users("test", 10, true).unwrap();
+        let results = cache.search_users("test", 10, true, false).unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "testbot");
         assert!(results[0].is_bot);
@@ -555,7 +757,7 @@ mod tests {
 
         // "junyeong" should match "junyeong.eom" via LIKE, not FTS5
         // so "seungryoung.lee" should not appear
-        let results = cache.search_users("junyeong", 10, false).unwrap();
+        let results = cache.search_users("junyeong", 10, false, false).unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "junyeong.eom");
     }
@@ -571,10 +773,115 @@ mod tests {
         cache.save_users(users).await.unwrap();
 
         // "xyz" has no LIKE match, should fall back to FTS5 and return empty
-        let results = cache.search_users("xyz", 10, false).unwrap();
+        let results = cache.search_users("xyz", 10, false, false).unwrap();
         assert_eq!(results.len(), 0);
     }
 
+    fn create_korean_user(id: &str, name: &str, display_name: &str) -> SlackUser {
+        let mut user = create_test_user(id, name, None, false);
+        if let Some(profile) = &mut user.profile {
+            profile.display_name = Some(display_name.to_string());
+            profile.real_name = Some(display_name.to_string());
+        }
+        user
+    }
+
+    async fn setup_mixed_workspace() -> SqliteCache {
+        let cache = setup_cache().await;
+        let users = vec![
+            create_korean_user("U1", "junyeong.eom", "엄준영"),
+            create_korean_user("U2", "jy.kim", "김준영"),
+            create_korean_user("U3", "seoyeon", "박서연"),
+            create_korean_user("U4", "mlee", "이민수"),
+            create_test_user("U5", "john.smith", Some("john@example.com"), false),
+        ];
+        cache.save_users(users).await.unwrap();
+        cache
+    }
+
+    #[tokio::test]
+    async fn test_search_users_hangul_substring() {
+        let cache = setup_mixed_workspace().await;
+
+        let results = cache.search_users("준영", 10, false, false).unwrap();
+        let ids: Vec<&str> = results.iter().map(|u| u.id.as_str()).collect();
+        assert_eq!(results.len(), 2);
+        assert!(ids.contains(&"U1"));
+        assert!(ids.contains(&"U2"));
+    }
+
+    #[rstest]
+    #[case("ㄱㅈㅇ", "U2")]
+    #[case("ㅇㅈㅇ", "U1")]
+    #[case("ㅂㅅㅇ", "U3")]
+    #[tokio::test]
+    async fn test_search_users_by_choseong(#[case] query: &str, #[case] expected_id: &str) {
+        let cache = setup_mixed_workspace().await;
+
+        let results = cache.search_users(query, 10, false, false).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, expected_id);
+    }
+
+    #[rstest]
+    #[case("kimjunyeong", "U2")]
+    #[case("park", "U3")]
+    #[case("leeminsu", "U4")]
+    #[tokio::test]
+    async fn test_search_users_by_romanization(#[case] query: &str, #[case] expected_id: &str) {
+        let cache = setup_mixed_workspace().await;
+
+        let results = cache.search_users(query, 10, false, false).unwrap();
+        assert_eq!(results[0].id, expected_id);
+    }
+
+    #[tokio::test]
+    async fn test_search_users_latin_matches_rank_before_romanized() {
+        let cache = setup_mixed_workspace().await;
+
+        // "junyeong.eom" matches by LIKE, "김준영" only through romanization
+        let results = cache.search_users("junyeong", 10, false, false).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].id, "U1");
+        assert_eq!(results[1].id, "U2");
+    }
+
+    #[tokio::test]
+    async fn test_search_users_hangul_ranks_exact_before_substring() {
+        let cache = setup_cache().await;
+        let users = vec![
+            create_korean_user("U1", "a", "김준영"),
+            create_korean_user("U2", "b", "준"),
+            create_korean_user("U3", "c", "준호"),
+        ];
+        cache.save_users(users).await.unwrap();
+
+        // Jamo prefix typed mid-syllable: "주" should match all three
+        let results = cache.search_users("주", 10, false, false).unwrap();
+        let ids: Vec<&str> = results.iter().map(|u| u.id.as_str()).collect();
+        assert_eq!(ids, vec!["U2", "U3", "U1"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_users_hangul_respects_limit_and_bots() {
+        let cache = setup_cache().await;
+        let mut bot = create_korean_user("B1", "alertbot", "김알림");
+        bot.is_bot = true;
+        let users = vec![
+            create_korean_user("U1", "a", "김가영"),
+            create_korean_user("U2", "b", "김나영"),
+            bot,
+        ];
+        cache.save_users(users).await.unwrap();
+
+        let results = cache.search_users("ㄱ", 1, false, false).unwrap();
+        assert_eq!(results.len(), 1);
+
+        let results = cache.search_users("ㄱ", 10, false, false).unwrap();
+        assert!(results.iter().all(|u| !u.is_bot));
+        assert_eq!(results.len(), 2);
+    }
+
     #[tokio::test]
     async fn test_concurrent_save_users() {
         let cache = setup_cache().await;
@@ -609,4 +916,64 @@ mod tests {
         // Both should succeed (locking prevents conflicts)
         assert!(result1.is_ok() || result2.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_search_users_fuzzy_typo() {
+        let cache = setup_mixed_workspace().await;
+
+        let results = cache.search_users("jonh", 10, false, false).unwrap();
+        assert_eq!(results[0].id, "U5");
+        assert_eq!(results[0].matched_field, Some("name"));
+        assert!(results[0].score < 0.6);
+    }
+
+    #[tokio::test]
+    async fn test_search_users_scores_exact_above_substring() {
+        let cache = setup_cache().await;
+        let users = vec![
+            create_test_user("U1", "johnny", None, false),
+            create_test_user("U2", "john", None, false),
+        ];
+        cache.save_users(users).await.unwrap();
+
+        let results = cache.search_users("john", 10, false, false).unwrap();
+        assert_eq!(results[0].id, "U2");
+        assert_eq!(results[0].score, 1.0);
+        assert!(results[1].score < results[0].score);
+    }
+
+    #[tokio::test]
+    async fn test_search_users_reports_matched_field() {
+        let cache = setup_mixed_workspace().await;
+
+        let results = cache.search_users("example.com", 10, false, false).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].matched_field, Some("email"));
+
+        let results = cache.search_users("ㅇㅈㅇ", 10, false, false).unwrap();
+        assert_eq!(results[0].id, "U1");
+        assert_eq!(results[0].matched_field, Some("display_name"));
+    }
+
+    #[tokio::test]
+    async fn test_search_users_excludes_deleted_by_default() {
+        let cache = setup_cache().await;
+        let mut departed = create_test_user("U2", "alice.old", None, false);
+        departed.deleted = true;
+        let users = vec![create_test_user("U1", "alice", None, false), departed];
+        cache.save_users(users).await.unwrap();
+
+        let results = cache.search_users("alice", 10, false, false).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "U1");
+
+        let results = cache.search_users("alice", 10, false, true).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().any(|u| u.id == "U2" && u.deleted));
+
+        // Deleted users stay resolvable by ID but are left out of listings
+        assert!(cache.get_user_by_id("U2").unwrap().unwrap().deleted);
+        let active = cache.get_users().unwrap();
+        assert_eq!(active.len(), 1);
+    }
 }
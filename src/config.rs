@@ -1,7 +1,10 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
 
+use crate::tools::response::OutputFormat;
+
 // Default configuration constants
 const DEFAULT_TTL_USERS_HOURS: u64 = 24;
 const DEFAULT_TTL_CHANNELS_HOURS: u64 = 24;
@@ -14,19 +17,53 @@ const DEFAULT_EXPONENTIAL_BASE: f64 = 2.0;
 const DEFAULT_TIMEOUT_SECONDS: u64 = 30;
 const DEFAULT_MAX_IDLE_PER_HOST: i32 = 10;
 const DEFAULT_POOL_IDLE_TIMEOUT_SECONDS: u64 = 90;
+const DEFAULT_OUTPUT_FORMAT: &str = "json";
+const DEFAULT_TIMEZONE: &str = "UTC";
+const DEFAULT_MODE: &str = "normal";
+const DEFAULT_IDEMPOTENCY_TTL_HOURS: u64 = 24;
+const DEFAULT_DUPLICATE_WINDOW_SECONDS: u64 = 60;
+const DEFAULT_DRAFT_TTL_MINUTES: u64 = 60;
+const DEFAULT_CONFIRMATION_TIMEOUT_SECONDS: u64 = 300;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
+    pub mode: ServerMode,
     pub slack: SlackConfig,
     pub cache: CacheConfig,
     pub retry: RetryConfig,
     pub connection: ConnectionConfig,
+    pub output: OutputConfig,
+    pub messaging: MessagingConfig,
+    #[serde(default)]
+    pub policy: PolicyConfig,
+    #[serde(default)]
+    pub scanner: ScannerConfig,
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    #[serde(default)]
+    pub quotas: QuotaConfig,
+}
+
+/// Whether tools may change anything in Slack
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerMode {
+    /// Mutating tools are not registered
+    ReadOnly,
+    /// Mutating tools return the request they would have sent instead of calling Slack
+    DryRun,
+    #[default]
+    Normal,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SlackConfig {
     pub bot_token: Option<String>,
     pub user_token: Option<String>,
+    /// Workspace URL ("https://team.slack.com") for building permalinks locally;
+    /// looked up via `chat.getPermalink` when unset
+    #[serde(default)]
+    pub workspace_url: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -53,12 +90,216 @@ pub struct ConnectionConfig {
     pub pool_idle_timeout_seconds: u64,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OutputConfig {
+    /// Default tool result format, overridable per call with `output_format`
+    pub format: OutputFormat,
+    /// IANA timezone for message times, or "user" for each author's timezone
+    pub timezone: String,
+    /// Add relative times ("3h ago") next to message times
+    pub relative_times: bool,
+}
+
+/// Guards against posting the same message twice
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MessagingConfig {
+    /// How long `send_message` remembers an `idempotency_key`
+    pub idempotency_ttl_hours: u64,
+    /// Refuse identical text to the same channel/thread within this window unless
+    /// `force` is set; 0 disables the check
+    pub duplicate_window_seconds: u64,
+    /// How long a message awaiting confirmation stays available to `confirm_draft`
+    pub draft_ttl_minutes: u64,
+    /// How long to wait for the user to approve a message through the client
+    pub confirmation_timeout_seconds: u64,
+}
+
+/// Which conversations tools may touch; empty lists allow everything
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PolicyConfig {
+    #[serde(default)]
+    pub read: AccessRulesConfig,
+    #[serde(default)]
+    pub write: AccessRulesConfig,
+    /// Allow direct and group direct messages
+    #[serde(default = "default_allow_dms")]
+    pub allow_dms: bool,
+    /// Channels where a person must approve each message before it is posted
+    #[serde(default)]
+    pub confirm: Vec<String>,
+}
+
+impl Default for PolicyConfig {
+    fn default() -> Self {
+        Self {
+            read: AccessRulesConfig::default(),
+            write: AccessRulesConfig::default(),
+            allow_dms: default_allow_dms(),
+            confirm: Vec::new(),
+        }
+    }
+}
+
+fn default_allow_dms() -> bool {
+    true
+}
+
+/// Outgoing message scanning for secrets and PII
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ScannerConfig {
+    #[serde(default = "default_scanner_enabled")]
+    pub enabled: bool,
+    /// Action per rule name, overriding the built-in secret rules (block) or a rule's own
+    #[serde(default)]
+    pub actions: HashMap<String, ScanAction>,
+    /// Extra patterns, typically for PII such as emails or phone numbers
+    #[serde(default)]
+    pub rules: Vec<ScanRuleConfig>,
+}
+
+impl Default for ScannerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_scanner_enabled(),
+            actions: HashMap::new(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+fn default_scanner_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ScanRuleConfig {
+    pub name: String,
+    /// Regular expression matched against message text and block strings
+    pub pattern: String,
+    #[serde(default)]
+    pub action: ScanAction,
+}
+
+/// What to do when a scanner rule matches
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanAction {
+    /// Refuse to send the message
+    Block,
+    /// Replace each match with `[REDACTED:<rule>]` and send
+    Redact,
+    /// Send unchanged and report the finding
+    #[default]
+    Warn,
+}
+
+impl ScanAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScanAction::Block => "block",
+            ScanAction::Redact => "redact",
+            ScanAction::Warn => "warn",
+        }
+    }
+}
+
+/// Masking of sensitive data in tool results
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RedactionConfig {
+    /// Replace email addresses with `[email]`
+    #[serde(default)]
+    pub emails: bool,
+    /// Replace phone numbers with `[phone]`
+    #[serde(default)]
+    pub phone_numbers: bool,
+    /// Extra regexes whose matches become `[redacted]`
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Keep user emails in the cache, where `users_fts` indexes them for search
+    #[serde(default = "default_index_emails")]
+    pub index_emails: bool,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            emails: false,
+            phone_numbers: false,
+            patterns: Vec::new(),
+            index_emails: default_index_emails(),
+        }
+    }
+}
+
+fn default_index_emails() -> bool {
+    true
+}
+
+/// Usage limits that stop a runaway agent; counters are shared by every
+/// server using the same cache database
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct QuotaConfig {
+    /// Slack API calls one MCP session may make per minute; 0 means no limit
+    #[serde(default)]
+    pub api_calls_per_minute: u64,
+    #[serde(default)]
+    pub tools: Vec<ToolQuotaConfig>,
+}
+
+/// At most `limit` calls to `tool` per `window_seconds`, counted separately per `per`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ToolQuotaConfig {
+    pub tool: String,
+    pub limit: u64,
+    #[serde(default = "default_quota_window_seconds")]
+    pub window_seconds: u64,
+    #[serde(default)]
+    pub per: QuotaScope,
+}
+
+fn default_quota_window_seconds() -> u64 {
+    3600
+}
+
+/// What a tool quota is counted per
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaScope {
+    /// One counter for all calls
+    #[default]
+    Global,
+    /// One counter per MCP client name
+    Client,
+    /// One counter per `channel` argument
+    Channel,
+}
+
+impl QuotaScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            QuotaScope::Global => "global",
+            QuotaScope::Client => "client",
+            QuotaScope::Channel => "channel",
+        }
+    }
+}
+
+/// Channel name/ID patterns: globs (`eng-*`, `#eng-*`, `C0123*`) or `re:<regex>`
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AccessRulesConfig {
+    #[serde(default)]
+    pub allow: Vec<String>,
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
 impl Config {
     pub fn load(config_path: Option<&str>, db_path: &str) -> Result<Self> {
         let mut settings = config::Config::builder();
 
         // Default values
         settings = settings
+            .set_default("mode", DEFAULT_MODE)?
             .set_default("cache.data_path", db_path)?
             .set_default("cache.ttl_users_hours", DEFAULT_TTL_USERS_HOURS)?
             .set_default("cache.ttl_channels_hours", DEFAULT_TTL_CHANNELS_HOURS)?
@@ -73,6 +314,22 @@ impl Config {
             .set_default(
                 "connection.pool_idle_timeout_seconds",
                 DEFAULT_POOL_IDLE_TIMEOUT_SECONDS,
+            )?
+            .set_default("output.format", DEFAULT_OUTPUT_FORMAT)?
+            .set_default("output.timezone", DEFAULT_TIMEZONE)?
+            .set_default("output.relative_times", false)?
+            .set_default(
+                "messaging.idempotency_ttl_hours",
+                DEFAULT_IDEMPOTENCY_TTL_HOURS,
+            )?
+            .set_default(
+                "messaging.duplicate_window_seconds",
+                DEFAULT_DUPLICATE_WINDOW_SECONDS,
+            )?
+            .set_default("messaging.draft_ttl_minutes", DEFAULT_DRAFT_TTL_MINUTES)?
+            .set_default(
+                "messaging.confirmation_timeout_seconds",
+                DEFAULT_CONFIRMATION_TIMEOUT_SECONDS,
             )?;
 
         // Load from config file if provided
@@ -107,6 +364,10 @@ impl Config {
             settings = settings.set_override("slack.user_token", Some(token))?;
         }
 
+        if let Ok(url) = std::env::var("SLACK_WORKSPACE_URL") {
+            settings = settings.set_override("slack.workspace_url", Some(url))?;
+        }
+
         let config = settings.build()?.try_deserialize()?;
         Ok(config)
     }
@@ -264,6 +525,289 @@ mod tests {
         );
     }
 
+    #[test]
+    #[serial]
+    fn test_config_messaging_values() {
+        setup_test_env();
+        unsafe {
+            env::set_var("SLACK_BOT_TOKEN", "xoxb-test");
+        }
+
+        let default = Config::load(None, "/tmp/test.db").unwrap();
+
+        unsafe {
+            env::set_var("SLACK_MESSAGING__DUPLICATE_WINDOW_SECONDS", "0");
+        }
+        let overridden = Config::load(None, "/tmp/test.db");
+        unsafe {
+            env::remove_var("SLACK_MESSAGING__DUPLICATE_WINDOW_SECONDS");
+        }
+        cleanup_test_env();
+
+        assert_eq!(
+            default.messaging.idempotency_ttl_hours,
+            DEFAULT_IDEMPOTENCY_TTL_HOURS
+        );
+        assert_eq!(
+            default.messaging.duplicate_window_seconds,
+            DEFAULT_DUPLICATE_WINDOW_SECONDS
+        );
+        assert_eq!(
+            default.messaging.draft_ttl_minutes,
+            DEFAULT_DRAFT_TTL_MINUTES
+        );
+        assert_eq!(
+            default.messaging.confirmation_timeout_seconds,
+            DEFAULT_CONFIRMATION_TIMEOUT_SECONDS
+        );
+        assert_eq!(overridden.unwrap().messaging.duplicate_window_seconds, 0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_output_format() {
+        setup_test_env();
+        unsafe {
+            env::set_var("SLACK_BOT_TOKEN", "xoxb-test");
+        }
+
+        let default = Config::load(None, "/tmp/test.db").unwrap();
+
+        unsafe {
+            env::set_var("SLACK_OUTPUT__FORMAT", "tsv");
+        }
+        let overridden = Config::load(None, "/tmp/test.db");
+        unsafe {
+            env::remove_var("SLACK_OUTPUT__FORMAT");
+        }
+        cleanup_test_env();
+
+        assert_eq!(default.output.format, OutputFormat::Json);
+        assert_eq!(default.output.timezone, DEFAULT_TIMEZONE);
+        assert!(!default.output.relative_times);
+        assert_eq!(overridden.unwrap().output.format, OutputFormat::Tsv);
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_workspace_url() {
+        setup_test_env();
+        unsafe {
+            env::set_var("SLACK_BOT_TOKEN", "xoxb-test");
+        }
+
+        let default = Config::load(None, "/tmp/test.db").unwrap();
+
+        unsafe {
+            env::set_var("SLACK_WORKSPACE_URL", "https://team.slack.com");
+        }
+        let configured = Config::load(None, "/tmp/test.db");
+        unsafe {
+            env::remove_var("SLACK_WORKSPACE_URL");
+        }
+        cleanup_test_env();
+
+        assert_eq!(default.slack.workspace_url, None);
+        assert!(default.policy.allow_dms);
+        assert!(default.policy.read.allow.is_empty());
+        assert_eq!(
+            configured.unwrap().slack.workspace_url.as_deref(),
+            Some("https://team.slack.com")
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_mode() {
+        setup_test_env();
+        unsafe {
+            env::set_var("SLACK_BOT_TOKEN", "xoxb-test");
+        }
+
+        let default = Config::load(None, "/tmp/test.db").unwrap();
+
+        unsafe {
+            env::set_var("SLACK_MODE", "dry_run");
+        }
+        let dry_run = Config::load(None, "/tmp/test.db");
+        unsafe {
+            env::set_var("SLACK_MODE", "read_only");
+        }
+        let read_only = Config::load(None, "/tmp/test.db");
+        unsafe {
+            env::set_var("SLACK_MODE", "yolo");
+        }
+        let invalid = Config::load(None, "/tmp/test.db");
+        unsafe {
+            env::remove_var("SLACK_MODE");
+        }
+        cleanup_test_env();
+
+        assert_eq!(default.mode, ServerMode::Normal);
+        assert_eq!(dry_run.unwrap().mode, ServerMode::DryRun);
+        assert_eq!(read_only.unwrap().mode, ServerMode::ReadOnly);
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_policy_from_file() {
+        setup_test_env();
+        unsafe {
+            env::set_var("SLACK_BOT_TOKEN", "xoxb-test");
+        }
+
+        let path = env::temp_dir().join(format!("mcp-slack-policy-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"
+[policy]
+allow_dms = false
+
+[policy.read]
+allow = ["eng-*"]
+
+[policy.write]
+allow = ["bot-sandbox"]
+deny = ["re:^prod-"]
+"#,
+        )
+        .unwrap();
+
+        let result = Config::load(path.to_str(), "/tmp/test.db");
+        std::fs::remove_file(&path).ok();
+        cleanup_test_env();
+
+        let policy = result.unwrap().policy;
+        assert!(!policy.allow_dms);
+        assert_eq!(policy.read.allow, vec!["eng-*"]);
+        assert!(policy.read.deny.is_empty());
+        assert_eq!(policy.write.allow, vec!["bot-sandbox"]);
+        assert_eq!(policy.write.deny, vec!["re:^prod-"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_scanner_from_file() {
+        setup_test_env();
+        unsafe {
+            env::set_var("SLACK_BOT_TOKEN", "xoxb-test");
+        }
+
+        let default = Config::load(None, "/tmp/test.db").unwrap().scanner;
+
+        let path = env::temp_dir().join(format!("mcp-slack-scanner-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"
+[scanner.actions]
+jwt = "warn"
+
+[[scanner.rules]]
+name = "email"
+pattern = '[\w.+-]+@[\w-]+\.[\w.]+'
+action = "redact"
+
+[[scanner.rules]]
+name = "phone"
+pattern = '\d{3}-\d{4}-\d{4}'
+"#,
+        )
+        .unwrap();
+
+        let result = Config::load(path.to_str(), "/tmp/test.db");
+        std::fs::remove_file(&path).ok();
+        cleanup_test_env();
+
+        assert!(default.enabled);
+        assert!(default.rules.is_empty());
+
+        let scanner = result.unwrap().scanner;
+        assert!(scanner.enabled);
+        assert_eq!(scanner.actions.get("jwt"), Some(&ScanAction::Warn));
+        assert_eq!(scanner.rules.len(), 2);
+        assert_eq!(scanner.rules[0].action, ScanAction::Redact);
+        assert_eq!(scanner.rules[1].pattern, r"\d{3}-\d{4}-\d{4}");
+        assert_eq!(scanner.rules[1].action, ScanAction::Warn);
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_quotas_from_file() {
+        setup_test_env();
+        unsafe {
+            env::set_var("SLACK_BOT_TOKEN", "xoxb-test");
+        }
+
+        let default = Config::load(None, "/tmp/test.db").unwrap().quotas;
+
+        let path = env::temp_dir().join(format!("mcp-slack-quotas-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"
+[quotas]
+api_calls_per_minute = 120
+
+[[quotas.tools]]
+tool = "send_message"
+limit = 20
+per = "channel"
+
+[[quotas.tools]]
+tool = "search_messages"
+limit = 100
+window_seconds = 60
+"#,
+        )
+        .unwrap();
+
+        let result = Config::load(path.to_str(), "/tmp/test.db");
+        std::fs::remove_file(&path).ok();
+        cleanup_test_env();
+
+        assert_eq!(default.api_calls_per_minute, 0);
+        assert!(default.tools.is_empty());
+
+        let quotas = result.unwrap().quotas;
+        assert_eq!(quotas.api_calls_per_minute, 120);
+        assert_eq!(quotas.tools.len(), 2);
+        assert_eq!(quotas.tools[0].window_seconds, 3600);
+        assert_eq!(quotas.tools[0].per, QuotaScope::Channel);
+        assert_eq!(quotas.tools[1].window_seconds, 60);
+        assert_eq!(quotas.tools[1].per, QuotaScope::Global);
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_redaction() {
+        setup_test_env();
+        unsafe {
+            env::set_var("SLACK_BOT_TOKEN", "xoxb-test");
+        }
+
+        let default = Config::load(None, "/tmp/test.db").unwrap().redaction;
+
+        unsafe {
+            env::set_var("SLACK_REDACTION__EMAILS", "true");
+            env::set_var("SLACK_REDACTION__INDEX_EMAILS", "false");
+        }
+        let overridden = Config::load(None, "/tmp/test.db");
+        unsafe {
+            env::remove_var("SLACK_REDACTION__EMAILS");
+            env::remove_var("SLACK_REDACTION__INDEX_EMAILS");
+        }
+        cleanup_test_env();
+
+        assert!(!default.emails);
+        assert!(!default.phone_numbers);
+        assert!(default.index_emails);
+
+        let redaction = overridden.unwrap().redaction;
+        assert!(redaction.emails);
+        assert!(!redaction.phone_numbers);
+        assert!(!redaction.index_emails);
+    }
+
     #[test]
     #[serial]
     fn test_config_with_nonexistent_file() {
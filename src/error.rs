@@ -1,3 +1,4 @@
+use serde::Serialize;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -17,8 +18,34 @@ pub enum McpError {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
+
+    #[error("Quota exceeded: {message}")]
+    QuotaExceeded {
+        message: String,
+        /// Unix time when the budget is available again
+        resets_at: i64,
+    },
+
+    #[error("{message}")]
+    Ambiguous {
+        message: String,
+        candidates: Vec<Candidate>,
+    },
+}
+
+/// A possible match offered back to the caller when input could not be resolved
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Candidate {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub reason: &'static str,
 }
 
 impl From<anyhow::Error> for McpError {
@@ -82,6 +109,12 @@ mod tests {
         assert!(err.to_string().contains("resource"));
     }
 
+    #[test]
+    fn test_permission_denied_error() {
+        let err = McpError::PermissionDenied("#general".to_string());
+        assert_eq!(err.to_string(), "Permission denied: #general");
+    }
+
     #[test]
     fn test_internal_error() {
         let err = McpError::Internal("internal issue".to_string());
@@ -89,6 +122,20 @@ mod tests {
         assert!(err.to_string().contains("internal issue"));
     }
 
+    #[test]
+    fn test_ambiguous_error_displays_message() {
+        let err = McpError::Ambiguous {
+            message: "Did you mean #design?".to_string(),
+            candidates: vec![Candidate {
+                id: "C1".to_string(),
+                name: "design".to_string(),
+                detail: None,
+                reason: "similar",
+            }],
+        };
+        assert_eq!(err.to_string(), "Did you mean #design?");
+    }
+
     #[test]
     fn test_mcp_context_ok() {
         let result: Result<i32, String> = Ok(42);
@@ -1,7 +1,11 @@
+pub mod audit;
 pub mod cache;
 pub mod config;
 pub mod error;
 pub mod mcp;
+pub mod policy;
+pub mod quota;
+pub mod scanner;
 pub mod slack;
 pub mod tools;
 pub mod utils;
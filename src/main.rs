@@ -1,16 +1,22 @@
+mod audit;
 mod cache;
 mod config;
 mod error;
 mod mcp;
+mod policy;
+mod quota;
+mod scanner;
 mod slack;
 mod tools;
 mod utils;
 
 use anyhow::Result;
+use std::io::Write;
 use std::sync::Arc;
 use tracing::error;
 
-use crate::cache::SqliteCache;
+use crate::audit::ExportFormat;
+use crate::cache::{AuditQuery, SqliteCache};
 use crate::config::Config;
 use crate::mcp::server::McpServer;
 use crate::slack::SlackClient;
@@ -38,13 +44,25 @@ async fn main() -> Result<()> {
     }
 
     let db_path = format!("{}/cache.db", data_path);
-    let config = Config::load(config_path.as_deref(), &data_path)?;
 
-    // Initialize Slack client
-    let slack_client = Arc::new(SlackClient::new(config.clone()));
+    // `mcp-slack export-audit-log` only reads the cache, so it runs without Slack config
+    if config_path.as_deref() == Some("export-audit-log") {
+        let args: Vec<String> = std::env::args().skip(2).collect();
+        return export_audit_log(&db_path, &args).await;
+    }
+
+    let config = Config::load(config_path.as_deref(), &data_path)?;
 
     // Initialize SQLite cache
-    let cache = Arc::new(SqliteCache::new(&db_path).await?);
+    let mut cache = SqliteCache::new(&db_path).await?;
+    if !config.redaction.index_emails {
+        cache.exclude_emails()?;
+    }
+    let cache = Arc::new(cache);
+
+    // Initialize Slack client, recording write calls in the cache's audit log
+    let slack_client = Arc::new(SlackClient::new(config.clone()));
+    slack_client.enable_audit_log(cache.clone());
 
     // Create and run MCP server with shared instances
     let mcp_server = McpServer::new(config, cache, slack_client).await?;
@@ -68,6 +86,44 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Write the audit log, oldest first, to stdout or `--output <path>`
+///
+/// Options: `--since <time>` (RFC 3339, YYYY-MM-DD or Unix seconds) and
+/// `--format jsonl|csv` (default jsonl).
+async fn export_audit_log(db_path: &str, args: &[String]) -> Result<()> {
+    let mut since = None;
+    let mut format = ExportFormat::Jsonl;
+    let mut output = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| anyhow::anyhow!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--since" => since = Some(audit::parse_since(value()?)?),
+            "--format" => format = ExportFormat::parse(value()?)?,
+            "--output" => output = Some(value()?.clone()),
+            other => anyhow::bail!("Unknown option for export-audit-log: {}", other),
+        }
+    }
+
+    let cache = SqliteCache::new(db_path).await?;
+    let records = cache.list_audit_log(&AuditQuery {
+        since,
+        oldest_first: true,
+        ..Default::default()
+    })?;
+    let rendered = audit::export(&records, format)?;
+
+    match output {
+        Some(path) => std::fs::write(&path, rendered)?,
+        None => std::io::stdout().write_all(rendered.as_bytes())?,
+    }
+    Ok(())
+}
+
 fn init_logging() -> Result<()> {
     // Support both LOG_LEVEL and RUST_LOG environment variables
     let filter = if let Ok(rust_log) = std::env::var("RUST_LOG") {
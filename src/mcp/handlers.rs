@@ -1,18 +1,27 @@
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tracing::warn;
+use tracing::{info, warn};
 
 use crate::cache::SqliteCache;
-use crate::config::Config;
+use crate::config::{Config, ServerMode};
 use crate::error::McpError;
+use crate::policy::ChannelPolicy;
+use crate::quota::{self, QuotaEnforcer};
+use crate::scanner::ContentScanner;
 use crate::slack::SlackClient;
-use crate::tools::{Tool, cache as cache_tools, messages, search};
+use crate::tools::response::{OutputFormat, Redactor};
+use crate::tools::{Tool, audit, cache as cache_tools, messages, search};
 
+use super::peer::ClientPeer;
 use super::types::{CallToolResult, Property, Tool as McpTool, ToolContent, ToolInputSchema};
 
 pub struct RequestHandler {
     tools: HashMap<String, Box<dyn Tool + Send + Sync>>,
+    output_format: OutputFormat,
+    redactor: Redactor,
+    quotas: QuotaEnforcer,
+    dry_run: bool,
 }
 
 macro_rules! register_tool {
@@ -25,9 +34,11 @@ impl RequestHandler {
     pub async fn new(
         cache: Arc<SqliteCache>,
         slack_client: Arc<SlackClient>,
+        peer: Arc<ClientPeer>,
         _config: Config,
     ) -> anyhow::Result<Self> {
         let mut tools: HashMap<String, Box<dyn Tool + Send + Sync>> = HashMap::new();
+        let policy = Arc::new(ChannelPolicy::from_config(&_config.policy)?);
 
         // Register search tools
         register_tool!(
@@ -38,42 +49,119 @@ impl RequestHandler {
         register_tool!(
             tools,
             "search_channels",
-            search::SearchChannelsTool::new(cache.clone())
+            search::SearchChannelsTool::new(cache.clone(), policy.clone())
         );
         register_tool!(
             tools,
             "search_messages",
-            search::SearchMessagesTool::new(slack_client.clone(), cache.clone())
+            search::SearchMessagesTool::new(
+                slack_client.clone(),
+                cache.clone(),
+                _config.output.clone(),
+                policy.clone()
+            )
         );
 
         // Register message tools
         register_tool!(
             tools,
             "send_message",
-            messages::SendMessageTool::new(slack_client.clone(), cache.clone())
+            messages::SendMessageTool::new(
+                slack_client.clone(),
+                cache.clone(),
+                policy.clone(),
+                Arc::new(ContentScanner::from_config(&_config.scanner)?),
+                peer,
+                _config.messaging.clone(),
+                _config.mode == ServerMode::DryRun
+            )
+        );
+        register_tool!(
+            tools,
+            "confirm_draft",
+            messages::ConfirmDraftTool::new(
+                slack_client.clone(),
+                cache.clone(),
+                policy.clone(),
+                _config.messaging.clone(),
+                _config.mode == ServerMode::DryRun
+            )
         );
         register_tool!(
             tools,
             "read_thread",
-            messages::ReadThreadTool::new(slack_client.clone(), cache.clone())
+            messages::ReadThreadTool::new(
+                slack_client.clone(),
+                cache.clone(),
+                _config.output.clone(),
+                policy.clone()
+            )
+        );
+        register_tool!(
+            tools,
+            "get_message",
+            messages::GetMessageTool::new(
+                slack_client.clone(),
+                cache.clone(),
+                _config.output.clone(),
+                policy.clone()
+            )
+        );
+        register_tool!(
+            tools,
+            "get_message_context",
+            messages::GetMessageContextTool::new(
+                slack_client.clone(),
+                cache.clone(),
+                _config.output.clone(),
+                policy.clone()
+            )
         );
         register_tool!(
             tools,
             "list_channel_members",
-            messages::ListChannelMembersTool::new(slack_client.clone(), cache.clone())
+            messages::ListChannelMembersTool::new(
+                slack_client.clone(),
+                cache.clone(),
+                policy.clone()
+            )
         );
         register_tool!(
             tools,
             "get_channel_messages",
-            messages::GetChannelMessagesTool::new(slack_client.clone(), cache.clone())
+            messages::GetChannelMessagesTool::new(
+                slack_client.clone(),
+                cache.clone(),
+                _config.output.clone(),
+                policy.clone()
+            )
         );
 
-        // Register cache tool
+        // Register cache tools
         register_tool!(
             tools,
             "refresh_cache",
             cache_tools::RefreshCacheTool::new(slack_client.clone(), cache.clone())
         );
+        register_tool!(
+            tools,
+            "cache_status",
+            cache_tools::CacheStatusTool::new(cache.clone(), _config.cache.clone())
+        );
+        register_tool!(
+            tools,
+            "list_audit_log",
+            audit::ListAuditLogTool::new(cache.clone())
+        );
+
+        match _config.mode {
+            ServerMode::ReadOnly => {
+                tools.retain(|_, tool| !tool.is_mutating());
+                info!("Read-only mode: write tools are disabled");
+            }
+            ServerMode::DryRun => info!("Dry-run mode: write tools return requests unsent"),
+            ServerMode::Normal => {}
+        }
 
         // Check Slack token status
         let has_bot_token = _config.slack.bot_token.is_some();
@@ -106,6 +194,14 @@ impl RequestHandler {
                             let _ = cache.save_users(users).await;
                         }
 
+                        // Handles for rendering <!subteam^S…> mentions
+                        match slack_client.users.fetch_usergroups().await {
+                            Ok(usergroups) => {
+                                let _ = cache.save_usergroups(&usergroups);
+                            }
+                            Err(e) => warn!("Failed to fetch user groups: {}", e),
+                        }
+
                         // Fetch channels
                         if let Ok(channels) = slack_client.channels.fetch_all_channels().await {
                             let _ = cache.save_channels(channels).await;
@@ -115,7 +211,13 @@ impl RequestHandler {
             }
         }
 
-        Ok(Self { tools })
+        Ok(Self {
+            tools,
+            output_format: _config.output.format,
+            redactor: Redactor::from_config(&_config.redaction)?,
+            quotas: QuotaEnforcer::new(_config.quotas.clone(), cache),
+            dry_run: _config.mode == ServerMode::DryRun,
+        })
     }
 
     pub async fn list_tools(&self) -> Vec<McpTool> {
@@ -138,7 +240,26 @@ impl RequestHandler {
             .get(name)
             .ok_or_else(|| McpError::NotFound(format!("Tool not found: {}", name)))?;
 
-        let result = tool.execute(arguments).await?;
+        // Per-call output format overrides the configured default
+        let mut arguments = arguments;
+        let output_format = match arguments
+            .as_object_mut()
+            .and_then(|args| args.remove("output_format"))
+        {
+            Some(format) => serde_json::from_value(format)
+                .map_err(|e| McpError::InvalidParameter(format!("Invalid output_format: {}", e)))?,
+            None => self.output_format,
+        };
+
+        // Only calls that go through keep their place in the tool quotas; a dry run
+        // of a write tool doesn't send anything. Slack API calls count either way.
+        let reservation = self.quotas.reserve(name, &arguments).await?;
+        let (result, api_calls) =
+            quota::count_api_calls(reservation.api_budget(), tool.execute(arguments)).await;
+        let went_through = result.is_ok() && !(self.dry_run && tool.is_mutating());
+        self.quotas.settle(reservation, went_through, api_calls);
+        let mut result = result?;
+        self.redactor.redact(&mut result);
 
         // Convert result to tool content
         let content = if let Some(text) = result.as_str() {
@@ -147,7 +268,7 @@ impl RequestHandler {
             }]
         } else {
             vec![ToolContent::Text {
-                text: serde_json::to_string_pretty(&result)?,
+                text: output_format.render(&result)?,
             }]
         };
 
@@ -173,6 +294,24 @@ impl RequestHandler {
         }
     }
 
+    fn create_optional_number_prop(description: &str) -> Property {
+        Property {
+            property_type: "number".to_string(),
+            description: Some(description.to_string()),
+            default: None,
+            enum_values: None,
+        }
+    }
+
+    fn create_bool_prop(description: &str, default: bool) -> Property {
+        Property {
+            property_type: "boolean".to_string(),
+            description: Some(description.to_string()),
+            default: Some(Value::Bool(default)),
+            enum_values: None,
+        }
+    }
+
     fn create_enum_prop(description: &str, default: &str, options: Vec<&str>) -> Property {
         Property {
             property_type: "string".to_string(),
@@ -200,6 +339,14 @@ impl RequestHandler {
                     "limit".to_string(),
                     Self::create_number_prop("Maximum number of results (default: 10)", 10),
                 );
+                props.insert(
+                    "include_bots".to_string(),
+                    Self::create_bool_prop("Include bot users (default: false)", false),
+                );
+                props.insert(
+                    "include_deleted".to_string(),
+                    Self::create_bool_prop("Include deactivated users (default: false)", false),
+                );
                 (props, vec!["query".to_string()])
             }
             "search_channels" => {
@@ -212,6 +359,10 @@ impl RequestHandler {
                     "limit".to_string(),
                     Self::create_number_prop("Maximum number of results (default: 10)", 10),
                 );
+                props.insert(
+                    "include_archived".to_string(),
+                    Self::create_bool_prop("Include archived channels (default: false)", false),
+                );
                 (props, vec!["query".to_string()])
             }
             "send_message" => {
@@ -228,6 +379,57 @@ impl RequestHandler {
                     "thread_ts".to_string(),
                     Self::create_string_prop("Thread timestamp to reply to (optional)", false),
                 );
+                props.insert(
+                    "format".to_string(),
+                    Self::create_enum_prop(
+                        "Text format: Slack mrkdwn as-is, or Markdown converted to mrkdwn and Block Kit",
+                        "mrkdwn",
+                        vec!["mrkdwn", "markdown"],
+                    ),
+                );
+                props.insert(
+                    "idempotency_key".to_string(),
+                    Self::create_string_prop(
+                        "Unique key for this message; retrying with the same key returns the original ts instead of posting again (optional)",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "force".to_string(),
+                    Self::create_bool_prop(
+                        "Send even if the same text was just sent to this channel/thread",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "split".to_string(),
+                    Self::create_bool_prop(
+                        "Split long text into several messages at paragraph or code block boundaries, posting the rest as thread replies to the first",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "as".to_string(),
+                    Self::create_enum_prop(
+                        "Token to post with: the bot, or the user token to post as that person (falls back to the user token when no bot token is configured)",
+                        "bot",
+                        vec!["bot", "user"],
+                    ),
+                );
+                props.insert(
+                    "username".to_string(),
+                    Self::create_string_prop(
+                        "Display name for this message; bot posts only, needs the chat:write.customize scope (optional)",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "icon_emoji".to_string(),
+                    Self::create_string_prop(
+                        "Emoji shown as the avatar, e.g. :robot_face:; bot posts only, needs the chat:write.customize scope (optional)",
+                        false,
+                    ),
+                );
                 (props, vec!["channel".to_string(), "text".to_string()])
             }
             "list_channel_members" => {
@@ -243,8 +445,15 @@ impl RequestHandler {
                 props.insert(
                     "channel".to_string(),
                     Self::create_string_prop(
-                        "Channel ID (C..., G..., D...) or exact channel name",
-                        true,
+                        "Channel ID (C..., G..., D...) or exact channel name (or use permalink)",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "permalink".to_string(),
+                    Self::create_string_prop(
+                        "Slack link to any message in the channel, in place of channel",
+                        false,
                     ),
                 );
                 props.insert(
@@ -255,7 +464,45 @@ impl RequestHandler {
                     "cursor".to_string(),
                     Self::create_string_prop("Pagination cursor (optional)", false),
                 );
-                (props, vec!["channel".to_string()])
+                props.insert(
+                    "text_format".to_string(),
+                    Self::create_enum_prop(
+                        "Message text format: Slack mrkdwn as sent (mentions and links resolved), CommonMark or plain text",
+                        "raw",
+                        vec!["raw", "markdown", "plain"],
+                    ),
+                );
+                props.insert(
+                    "timezone".to_string(),
+                    Self::create_string_prop(
+                        "IANA timezone for message times (e.g. Asia/Seoul), or 'user' for each author's timezone (optional)",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "relative_times".to_string(),
+                    Self::create_bool_prop("Add relative times such as '3h ago'", false),
+                );
+                props.insert(
+                    "max_tokens".to_string(),
+                    Self::create_optional_number_prop(
+                        "Approximate output token budget; long messages are shortened and the rest is paged via next_cursor (optional)",
+                    ),
+                );
+                props.insert(
+                    "max_chars".to_string(),
+                    Self::create_optional_number_prop(
+                        "Output character budget, like max_tokens (optional)",
+                    ),
+                );
+                props.insert(
+                    "exclude_noise".to_string(),
+                    Self::create_bool_prop(
+                        "Skip joins, leaves, topic/purpose changes and pins",
+                        false,
+                    ),
+                );
+                (props, vec![])
             }
             "refresh_cache" => {
                 let mut props = HashMap::new();
@@ -269,6 +516,58 @@ impl RequestHandler {
                 );
                 (props, vec![])
             }
+            "cache_status" => (HashMap::new(), vec![]),
+            "confirm_draft" => {
+                let mut props = HashMap::new();
+                props.insert(
+                    "draft_id".to_string(),
+                    Self::create_string_prop("draft_id returned by send_message", true),
+                );
+                props.insert(
+                    "discard".to_string(),
+                    Self::create_bool_prop(
+                        "Drop the draft instead of posting it (when the user didn't approve)",
+                        false,
+                    ),
+                );
+                (props, vec!["draft_id".to_string()])
+            }
+            "list_audit_log" => {
+                let mut props = HashMap::new();
+                props.insert(
+                    "limit".to_string(),
+                    Self::create_number_prop(
+                        "Maximum number of entries (default: 50, max: 1000)",
+                        50,
+                    ),
+                );
+                props.insert(
+                    "since".to_string(),
+                    Self::create_string_prop(
+                        "Only entries at or after this time: RFC 3339, YYYY-MM-DD or Unix seconds (optional)",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "tool".to_string(),
+                    Self::create_string_prop(
+                        "Only entries made by this tool, e.g. send_message (optional)",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "channel".to_string(),
+                    Self::create_string_prop(
+                        "Only entries for this channel ID or #name (optional)",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "errors_only".to_string(),
+                    Self::create_bool_prop("Only failed calls", false),
+                );
+                (props, vec![])
+            }
             "search_messages" => {
                 let mut props = HashMap::new();
                 props.insert(
@@ -287,27 +586,242 @@ impl RequestHandler {
                     "limit".to_string(),
                     Self::create_number_prop("Maximum number of results (default: 10)", 10),
                 );
+                props.insert(
+                    "cursor".to_string(),
+                    Self::create_string_prop(
+                        "next_cursor from a budgeted response (optional)",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "text_format".to_string(),
+                    Self::create_enum_prop(
+                        "Message text format: Slack mrkdwn as sent (mentions and links resolved), CommonMark or plain text",
+                        "raw",
+                        vec!["raw", "markdown", "plain"],
+                    ),
+                );
+                props.insert(
+                    "timezone".to_string(),
+                    Self::create_string_prop(
+                        "IANA timezone for message times (e.g. Asia/Seoul), or 'user' for each author's timezone (optional)",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "relative_times".to_string(),
+                    Self::create_bool_prop("Add relative times such as '3h ago'", false),
+                );
+                props.insert(
+                    "max_tokens".to_string(),
+                    Self::create_optional_number_prop(
+                        "Approximate output token budget; long messages are shortened and the rest is paged via next_cursor (optional)",
+                    ),
+                );
+                props.insert(
+                    "max_chars".to_string(),
+                    Self::create_optional_number_prop(
+                        "Output character budget, like max_tokens (optional)",
+                    ),
+                );
                 (props, vec!["query".to_string()])
             }
             "read_thread" => {
                 let mut props = HashMap::new();
                 props.insert(
                     "channel".to_string(),
-                    Self::create_string_prop("Channel ID containing the thread", true),
+                    Self::create_string_prop(
+                        "Channel ID containing the thread (or use permalink)",
+                        false,
+                    ),
                 );
                 props.insert(
                     "thread_ts".to_string(),
-                    Self::create_string_prop("Thread timestamp to read", true),
+                    Self::create_string_prop("Thread timestamp to read (or use permalink)", false),
+                );
+                props.insert(
+                    "permalink".to_string(),
+                    Self::create_string_prop(
+                        "Slack link to the thread or any message in it, in place of channel/thread_ts",
+                        false,
+                    ),
                 );
                 props.insert(
                     "limit".to_string(),
                     Self::create_number_prop("Maximum number of messages (default: 100)", 100),
                 );
-                (props, vec!["channel".to_string(), "thread_ts".to_string()])
+                props.insert(
+                    "cursor".to_string(),
+                    Self::create_string_prop(
+                        "next_cursor from a budgeted response (optional)",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "text_format".to_string(),
+                    Self::create_enum_prop(
+                        "Message text format: Slack mrkdwn as sent (mentions and links resolved), CommonMark or plain text",
+                        "raw",
+                        vec!["raw", "markdown", "plain"],
+                    ),
+                );
+                props.insert(
+                    "timezone".to_string(),
+                    Self::create_string_prop(
+                        "IANA timezone for message times (e.g. Asia/Seoul), or 'user' for each author's timezone (optional)",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "relative_times".to_string(),
+                    Self::create_bool_prop("Add relative times such as '3h ago'", false),
+                );
+                props.insert(
+                    "max_tokens".to_string(),
+                    Self::create_optional_number_prop(
+                        "Approximate output token budget; long messages are shortened and the rest is paged via next_cursor (optional)",
+                    ),
+                );
+                props.insert(
+                    "max_chars".to_string(),
+                    Self::create_optional_number_prop(
+                        "Output character budget, like max_tokens (optional)",
+                    ),
+                );
+                props.insert(
+                    "exclude_noise".to_string(),
+                    Self::create_bool_prop(
+                        "Skip joins, leaves, topic/purpose changes and pins",
+                        false,
+                    ),
+                );
+                (props, vec![])
+            }
+            "get_message_context" => {
+                let mut props = HashMap::new();
+                props.insert(
+                    "permalink".to_string(),
+                    Self::create_string_prop(
+                        "Slack message link (https://<workspace>.slack.com/archives/...)",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "channel".to_string(),
+                    Self::create_string_prop("Channel ID or name, when not using permalink", false),
+                );
+                props.insert(
+                    "ts".to_string(),
+                    Self::create_string_prop("Message timestamp, when not using permalink", false),
+                );
+                props.insert(
+                    "thread_ts".to_string(),
+                    Self::create_string_prop(
+                        "Parent timestamp when the message is a thread reply; context then comes from the thread (optional)",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "before".to_string(),
+                    Self::create_number_prop(
+                        "Messages before the anchor (default: 5, max: 100)",
+                        5,
+                    ),
+                );
+                props.insert(
+                    "after".to_string(),
+                    Self::create_number_prop("Messages after the anchor (default: 5, max: 100)", 5),
+                );
+                props.insert(
+                    "text_format".to_string(),
+                    Self::create_enum_prop(
+                        "Message text format: Slack mrkdwn as sent (mentions and links resolved), CommonMark or plain text",
+                        "raw",
+                        vec!["raw", "markdown", "plain"],
+                    ),
+                );
+                props.insert(
+                    "timezone".to_string(),
+                    Self::create_string_prop(
+                        "IANA timezone for message times (e.g. Asia/Seoul), or 'user' for each author's timezone (optional)",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "relative_times".to_string(),
+                    Self::create_bool_prop("Add relative times such as '3h ago'", false),
+                );
+                (props, vec![])
+            }
+            "get_message" => {
+                let mut props = HashMap::new();
+                props.insert(
+                    "permalink".to_string(),
+                    Self::create_string_prop(
+                        "Slack message link (https://<workspace>.slack.com/archives/...)",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "channel".to_string(),
+                    Self::create_string_prop("Channel ID or name, when not using permalink", false),
+                );
+                props.insert(
+                    "ts".to_string(),
+                    Self::create_string_prop("Message timestamp, when not using permalink", false),
+                );
+                props.insert(
+                    "thread_ts".to_string(),
+                    Self::create_string_prop(
+                        "Parent timestamp when the message is a thread reply (optional)",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "include_thread".to_string(),
+                    Self::create_bool_prop("Include the surrounding thread", true),
+                );
+                props.insert(
+                    "thread_limit".to_string(),
+                    Self::create_number_prop(
+                        "Maximum number of thread messages (default: 100)",
+                        100,
+                    ),
+                );
+                props.insert(
+                    "text_format".to_string(),
+                    Self::create_enum_prop(
+                        "Message text format: Slack mrkdwn as sent (mentions and links resolved), CommonMark or plain text",
+                        "raw",
+                        vec!["raw", "markdown", "plain"],
+                    ),
+                );
+                props.insert(
+                    "timezone".to_string(),
+                    Self::create_string_prop(
+                        "IANA timezone for message times (e.g. Asia/Seoul), or 'user' for each author's timezone (optional)",
+                        false,
+                    ),
+                );
+                props.insert(
+                    "relative_times".to_string(),
+                    Self::create_bool_prop("Add relative times such as '3h ago'", false),
+                );
+                (props, vec![])
             }
             _ => (HashMap::new(), vec![]),
         };
 
+        let mut properties = properties;
+        properties.insert(
+            "output_format".to_string(),
+            Self::create_enum_prop(
+                "Result format: pretty JSON, compact JSON, TSV or Markdown table",
+                self.output_format.as_str(),
+                vec!["json", "json_compact", "tsv", "markdown_table"],
+            ),
+        );
+
         McpTool {
             name: name.to_string(),
             description: tool.description().to_string(),
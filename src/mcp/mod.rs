@@ -1,3 +1,4 @@
 pub mod handlers;
+pub mod peer;
 pub mod server;
 pub mod types;
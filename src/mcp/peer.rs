@@ -0,0 +1,346 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tracing::warn;
+
+use crate::error::{McpError, McpResult};
+
+use super::types::{JsonRpcRequest, JsonRpcResponse};
+
+/// The connected MCP client, for requests that go from the server to the client
+/// (such as elicitation) while a tool call is running
+pub struct ClientPeer {
+    outgoing: mpsc::UnboundedSender<String>,
+    pending: Mutex<HashMap<i64, oneshot::Sender<JsonRpcResponse>>>,
+    next_id: AtomicI64,
+    elicitation: AtomicBool,
+}
+
+/// What the user did with an elicitation request
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Elicitation {
+    Accept {
+        #[serde(default)]
+        content: Value,
+    },
+    Decline,
+    Cancel,
+}
+
+impl ClientPeer {
+    /// The receiver yields every message to write to the client, one JSON document each
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<String>) {
+        let (outgoing, receiver) = mpsc::unbounded_channel();
+        let peer = Self {
+            outgoing,
+            pending: Mutex::new(HashMap::new()),
+            next_id: AtomicI64::new(1),
+            elicitation: AtomicBool::new(false),
+        };
+        (peer, receiver)
+    }
+
+    /// Queue a message for the client
+    pub fn send(&self, message: &impl Serialize) -> McpResult<()> {
+        let message = serde_json::to_string(message)?;
+        self.outgoing
+            .send(message)
+            .map_err(|_| McpError::Internal("Client connection is closed".to_string()))
+    }
+
+    /// Record whether the client declared the `elicitation` capability
+    pub fn set_supports_elicitation(&self, supported: bool) {
+        self.elicitation.store(supported, Ordering::Relaxed);
+    }
+
+    pub fn supports_elicitation(&self) -> bool {
+        self.elicitation.load(Ordering::Relaxed)
+    }
+
+    /// Send a request to the client and wait for its response
+    pub async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> McpResult<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = oneshot::channel();
+        self.pending_requests().insert(id, sender);
+
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params: Some(params),
+            id: Some(json!(id)),
+        };
+        if let Err(e) = self.send(&request) {
+            self.pending_requests().remove(&id);
+            return Err(e);
+        }
+
+        let response = match tokio::time::timeout(timeout, receiver).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => {
+                return Err(McpError::Internal(format!(
+                    "Client disconnected before answering {}",
+                    method
+                )));
+            }
+            Err(_) => {
+                self.pending_requests().remove(&id);
+                return Err(McpError::Internal(format!(
+                    "No answer to {} within {}s",
+                    method,
+                    timeout.as_secs()
+                )));
+            }
+        };
+
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(McpError::Internal(format!(
+                "Client rejected {}: {}",
+                method, error.message
+            ))),
+            (Some(result), None) => Ok(result),
+            (None, None) => Ok(Value::Null),
+        }
+    }
+
+    /// Ask the user for input through the client (`elicitation/create`)
+    pub async fn elicit(
+        &self,
+        message: &str,
+        requested_schema: Value,
+        timeout: Duration,
+    ) -> McpResult<Elicitation> {
+        let result = self
+            .request(
+                "elicitation/create",
+                json!({
+                    "message": message,
+                    "requestedSchema": requested_schema,
+                }),
+                timeout,
+            )
+            .await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Hand a response from the client to the request waiting for it
+    pub fn handle_response(&self, response: JsonRpcResponse) {
+        let sender = response
+            .id
+            .as_ref()
+            .and_then(Value::as_i64)
+            .and_then(|id| self.pending_requests().remove(&id));
+        match sender {
+            Some(sender) => {
+                let _ = sender.send(response);
+            }
+            None => warn!("Response to unknown request id: {:?}", response.id),
+        }
+    }
+
+    /// Fail every request still waiting for the client
+    pub fn cancel_pending(&self) {
+        self.pending_requests().clear();
+    }
+
+    fn pending_requests(
+        &self,
+    ) -> std::sync::MutexGuard<'_, HashMap<i64, oneshot::Sender<JsonRpcResponse>>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::types::JsonRpcError;
+    use std::sync::Arc;
+
+    /// The id of the next request the peer sent to the client
+    async fn next_request_id(outgoing: &mut mpsc::UnboundedReceiver<String>) -> Value {
+        let message = outgoing.recv().await.unwrap();
+        let request: JsonRpcRequest = serde_json::from_str(&message).unwrap();
+        request.id.unwrap()
+    }
+
+    fn pending_count(peer: &ClientPeer) -> usize {
+        peer.pending_requests().len()
+    }
+
+    #[tokio::test]
+    async fn test_request_resolves_with_matching_response() {
+        let (peer, mut outgoing) = ClientPeer::new();
+        let peer = Arc::new(peer);
+
+        let first = tokio::spawn({
+            let peer = peer.clone();
+            async move {
+                peer.request("ping", json!({}), Duration::from_secs(5))
+                    .await
+            }
+        });
+        let first_id = next_request_id(&mut outgoing).await;
+        let second = tokio::spawn({
+            let peer = peer.clone();
+            async move {
+                peer.request("ping", json!({}), Duration::from_secs(5))
+                    .await
+            }
+        });
+        let second_id = next_request_id(&mut outgoing).await;
+        assert_ne!(first_id, second_id);
+
+        // Answered out of order, each response reaches the request with its id
+        peer.handle_response(JsonRpcResponse::success(Some(second_id), json!({"n": 2})));
+        peer.handle_response(JsonRpcResponse::success(Some(first_id), json!({"n": 1})));
+
+        assert_eq!(first.await.unwrap().unwrap(), json!({"n": 1}));
+        assert_eq!(second.await.unwrap().unwrap(), json!({"n": 2}));
+        assert_eq!(pending_count(&peer), 0);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_response_ids_are_ignored() {
+        let (peer, mut outgoing) = ClientPeer::new();
+        let peer = Arc::new(peer);
+
+        let request = tokio::spawn({
+            let peer = peer.clone();
+            async move {
+                peer.request("ping", json!({}), Duration::from_secs(5))
+                    .await
+            }
+        });
+        let id = next_request_id(&mut outgoing).await;
+
+        peer.handle_response(JsonRpcResponse::success(Some(json!(9999)), json!("stray")));
+        peer.handle_response(JsonRpcResponse::success(Some(json!("abc")), json!("stray")));
+        peer.handle_response(JsonRpcResponse::success(None, json!("stray")));
+        assert_eq!(pending_count(&peer), 1);
+
+        peer.handle_response(JsonRpcResponse::success(Some(id), json!("answer")));
+        assert_eq!(request.await.unwrap().unwrap(), json!("answer"));
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_request_is_removed() {
+        let (peer, mut outgoing) = ClientPeer::new();
+
+        let err = peer
+            .request("ping", json!({}), Duration::from_millis(10))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("No answer to ping"));
+        assert_eq!(pending_count(&peer), 0);
+
+        // A late answer finds nothing waiting for it
+        let id = next_request_id(&mut outgoing).await;
+        peer.handle_response(JsonRpcResponse::success(Some(id), json!({})));
+        assert_eq!(pending_count(&peer), 0);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_pending_fails_waiting_requests() {
+        let (peer, mut outgoing) = ClientPeer::new();
+        let peer = Arc::new(peer);
+
+        let request = tokio::spawn({
+            let peer = peer.clone();
+            async move {
+                peer.request("elicitation/create", json!({}), Duration::from_secs(60))
+                    .await
+            }
+        });
+        next_request_id(&mut outgoing).await;
+
+        peer.cancel_pending();
+
+        let err = request.await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("disconnected"));
+        assert_eq!(pending_count(&peer), 0);
+    }
+
+    #[tokio::test]
+    async fn test_client_error_response_fails_request() {
+        let (peer, mut outgoing) = ClientPeer::new();
+        let peer = Arc::new(peer);
+
+        let request = tokio::spawn({
+            let peer = peer.clone();
+            async move {
+                peer.request("ping", json!({}), Duration::from_secs(5))
+                    .await
+            }
+        });
+        let id = next_request_id(&mut outgoing).await;
+        peer.handle_response(JsonRpcResponse::error(
+            Some(id),
+            JsonRpcError {
+                code: -32601,
+                message: "Method not found".to_string(),
+                data: None,
+            },
+        ));
+
+        let err = request.await.unwrap().unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Client rejected ping: Method not found")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_fails_when_client_is_gone() {
+        let (peer, outgoing) = ClientPeer::new();
+        drop(outgoing);
+
+        let err = peer
+            .request("ping", json!({}), Duration::from_secs(5))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Client connection is closed"));
+        assert_eq!(pending_count(&peer), 0);
+    }
+
+    #[tokio::test]
+    async fn test_elicit_parses_user_action() {
+        let (peer, mut outgoing) = ClientPeer::new();
+        let peer = Arc::new(peer);
+
+        let elicitation = tokio::spawn({
+            let peer = peer.clone();
+            async move {
+                peer.elicit(
+                    "Send it?",
+                    json!({"type": "object"}),
+                    Duration::from_secs(5),
+                )
+                .await
+            }
+        });
+        let message = outgoing.recv().await.unwrap();
+        let request: JsonRpcRequest = serde_json::from_str(&message).unwrap();
+        assert_eq!(request.method, "elicitation/create");
+        assert_eq!(request.params.unwrap()["message"], "Send it?");
+
+        peer.handle_response(JsonRpcResponse::success(
+            request.id,
+            json!({"action": "accept", "content": {"approve": true}}),
+        ));
+        assert_eq!(
+            elicitation.await.unwrap().unwrap(),
+            Elicitation::Accept {
+                content: json!({"approve": true})
+            }
+        );
+    }
+}
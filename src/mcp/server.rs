@@ -2,21 +2,30 @@ use anyhow::Result;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::sync::RwLock;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Stdout};
+use tokio::sync::{Mutex, RwLock, mpsc};
+use tokio::task::JoinSet;
 use tracing::{error, warn};
 
+use crate::audit::AuditContext;
 use crate::cache::SqliteCache;
 use crate::config::Config;
+use crate::error::McpError;
 use crate::slack::SlackClient;
 
 use super::handlers::RequestHandler;
+use super::peer::ClientPeer;
 use super::types::*;
 
 pub struct McpServer {
     _config: Config,
     handler: Arc<RequestHandler>,
     initialized: Arc<RwLock<bool>>,
+    /// Client name from `initialize`, recorded in the audit log
+    client_name: RwLock<Option<String>>,
+    peer: Arc<ClientPeer>,
+    /// Messages for stdout; taken by `run`
+    outgoing: Mutex<Option<mpsc::UnboundedReceiver<String>>>,
 }
 
 impl McpServer {
@@ -25,32 +34,56 @@ impl McpServer {
         cache: Arc<SqliteCache>,
         slack_client: Arc<SlackClient>,
     ) -> Result<Self> {
+        let (peer, outgoing) = ClientPeer::new();
+        let peer = Arc::new(peer);
+
         // Create handler with tools
-        let handler =
-            RequestHandler::new(cache.clone(), slack_client.clone(), config.clone()).await?;
+        let handler = RequestHandler::new(
+            cache.clone(),
+            slack_client.clone(),
+            peer.clone(),
+            config.clone(),
+        )
+        .await?;
 
         Ok(Self {
             _config: config,
             handler: Arc::new(handler),
             initialized: Arc::new(RwLock::new(false)),
+            client_name: RwLock::new(None),
+            peer,
+            outgoing: Mutex::new(Some(outgoing)),
         })
     }
 
+    /// Serve stdin/stdout. Tool calls run as separate tasks, so the loop keeps
+    /// reading while a tool waits for the client to answer one of our requests.
     pub async fn run(&self) -> Result<()> {
-        let stdin = tokio::io::stdin();
-        let stdout = tokio::io::stdout();
-        let mut reader = BufReader::new(stdin);
-        let mut stdout = stdout;
+        let mut outgoing = self
+            .outgoing
+            .lock()
+            .await
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("Server is already running"))?;
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        let mut stdout = tokio::io::stdout();
+        let mut tool_calls = JoinSet::new();
 
-        let mut buffer = String::new();
         let mut empty_reads = 0;
 
         loop {
-            buffer.clear();
+            let line = tokio::select! {
+                Some(message) = outgoing.recv() => {
+                    write_message(&mut stdout, &message).await?;
+                    continue;
+                }
+                Some(_) = tool_calls.join_next(), if !tool_calls.is_empty() => continue,
+                line = lines.next_line() => line,
+            };
 
             // Read a line from stdin
-            match reader.read_line(&mut buffer).await {
-                Ok(0) => {
+            match line {
+                Ok(None) => {
                     empty_reads += 1;
 
                     // Give it a few chances before exiting
@@ -61,40 +94,32 @@ impl McpServer {
                     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
                     continue;
                 }
-                Ok(_) => {
+                Ok(Some(line)) => {
                     empty_reads = 0; // Reset counter on successful read
-                    let trimmed = buffer.trim();
+                    let trimmed = line.trim();
                     if trimmed.is_empty() {
                         continue;
                     }
 
                     // Process the request
-                    match self.process_request(trimmed).await {
-                        Ok(Some(response)) => {
-                            let response_str = serde_json::to_string(&response)?;
-
-                            stdout.write_all(response_str.as_bytes()).await?;
-                            stdout.write_all(b"\n").await?;
-                            stdout.flush().await?;
-                        }
+                    let response = match self.process_message(trimmed, &mut tool_calls).await {
+                        Ok(Some(response)) => response,
                         Ok(None) => {
-                            // This was a notification, no response needed
+                            // A notification, a client response or a tool call
+                            // that answers on its own
+                            continue;
                         }
                         Err(e) => {
                             error!("Error processing request: {}", e);
 
                             // Send error response
-                            let error_response = JsonRpcResponse::error(
+                            JsonRpcResponse::error(
                                 None,
                                 JsonRpcError::internal_error(e.to_string()),
-                            );
-
-                            let response_str = serde_json::to_string(&error_response)?;
-                            stdout.write_all(response_str.as_bytes()).await?;
-                            stdout.write_all(b"\n").await?;
-                            stdout.flush().await?;
+                            )
                         }
-                    }
+                    };
+                    write_message(&mut stdout, &serde_json::to_string(&response)?).await?;
                 }
                 Err(e) => {
                     error!("Error reading from stdin: {}", e);
@@ -103,9 +128,67 @@ impl McpServer {
             }
         }
 
+        // Nobody is left to answer our requests; let running tool calls finish
+        self.peer.cancel_pending();
+        while tool_calls.join_next().await.is_some() {}
+        while let Ok(message) = outgoing.try_recv() {
+            write_message(&mut stdout, &message).await?;
+        }
+
         Ok(())
     }
 
+    /// Route a message from the client: responses go to the request waiting for
+    /// them, tool calls are spawned, and everything else is answered in order
+    async fn process_message(
+        &self,
+        input: &str,
+        tool_calls: &mut JoinSet<()>,
+    ) -> Result<Option<JsonRpcResponse>> {
+        let Ok(message) = serde_json::from_str::<Value>(input) else {
+            return self.process_request(input).await;
+        };
+
+        if message.get("method").is_none() && message.get("id").is_some() {
+            match serde_json::from_value::<JsonRpcResponse>(message) {
+                Ok(response) => self.peer.handle_response(response),
+                Err(e) => warn!("Failed to parse client response: {}", e),
+            }
+            return Ok(None);
+        }
+
+        if message.get("method").and_then(Value::as_str) == Some("tools/call")
+            && message.get("jsonrpc").and_then(Value::as_str) == Some("2.0")
+        {
+            let request: JsonRpcRequest = serde_json::from_value(message)?;
+            if !*self.initialized.read().await {
+                return Ok(Some(JsonRpcResponse::error(
+                    request.id,
+                    JsonRpcError::internal_error("Server not initialized".to_string()),
+                )));
+            }
+
+            let handler = self.handler.clone();
+            let peer = self.peer.clone();
+            let client = self.client_name.read().await.clone();
+            tool_calls.spawn(async move {
+                let id = request.id.clone();
+                let response = call_tool(handler, client, request)
+                    .await
+                    .unwrap_or_else(|e| {
+                        error!("Error processing request: {}", e);
+                        JsonRpcResponse::error(id, JsonRpcError::internal_error(e.to_string()))
+                    });
+                if let Err(e) = peer.send(&response) {
+                    error!("Failed to send tool result: {}", e);
+                }
+            });
+            return Ok(None);
+        }
+
+        self.process_request(input).await
+    }
+
     async fn process_request(&self, input: &str) -> Result<Option<JsonRpcResponse>> {
         // Parse JSON-RPC request
         let request: JsonRpcRequest = match serde_json::from_str(input) {
@@ -132,7 +215,6 @@ impl McpServer {
             "initialize" => self.handle_initialize(request).await.map(Some),
             "initialized" | "notifications/initialized" => self.handle_initialized(request).await,
             "tools/list" => self.handle_list_tools(request).await.map(Some),
-            "tools/call" => self.handle_call_tool(request).await.map(Some),
             "prompts/list" => self.handle_list_prompts(request).await.map(Some),
             "resources/list" => self.handle_list_resources(request).await.map(Some),
             _ => {
@@ -157,6 +239,10 @@ impl McpServer {
             }
         };
 
+        *self.client_name.write().await = Some(params.client_info.name.clone());
+        self.peer
+            .set_supports_elicitation(params.capabilities.elicitation.is_some());
+
         // Support both protocol versions
         let protocol_version = if params.protocol_version.starts_with("2025") {
             PROTOCOL_VERSION_2025.to_string()
@@ -216,43 +302,6 @@ impl McpServer {
         ))
     }
 
-    async fn handle_call_tool(&self, request: JsonRpcRequest) -> Result<JsonRpcResponse> {
-        // Check if initialized
-        let initialized = self.initialized.read().await;
-        if !*initialized {
-            return Ok(JsonRpcResponse::error(
-                request.id,
-                JsonRpcError::internal_error("Server not initialized".to_string()),
-            ));
-        }
-
-        // Parse call tool params
-        let params: CallToolRequest = match request.params {
-            Some(p) => serde_json::from_value(p)?,
-            None => {
-                return Ok(JsonRpcResponse::error(
-                    request.id,
-                    JsonRpcError::invalid_params("Missing params".to_string()),
-                ));
-            }
-        };
-
-        // Execute tool
-        match self.handler.call_tool(&params.name, params.arguments).await {
-            Ok(result) => Ok(JsonRpcResponse::success(
-                request.id,
-                serde_json::to_value(result)?,
-            )),
-            Err(e) => {
-                error!("Tool execution failed: {}", e);
-                Ok(JsonRpcResponse::error(
-                    request.id,
-                    JsonRpcError::internal_error(e.to_string()),
-                ))
-            }
-        }
-    }
-
     async fn handle_list_prompts(&self, request: JsonRpcRequest) -> Result<JsonRpcResponse> {
         // We don't have prompts, return empty list
         let result = serde_json::json!({
@@ -271,3 +320,68 @@ impl McpServer {
         Ok(JsonRpcResponse::success(request.id, result))
     }
 }
+
+async fn write_message(stdout: &mut Stdout, message: &str) -> Result<()> {
+    stdout.write_all(message.as_bytes()).await?;
+    stdout.write_all(b"\n").await?;
+    stdout.flush().await?;
+    Ok(())
+}
+
+async fn call_tool(
+    handler: Arc<RequestHandler>,
+    client: Option<String>,
+    request: JsonRpcRequest,
+) -> Result<JsonRpcResponse> {
+    // Parse call tool params
+    let params: CallToolRequest = match request.params {
+        Some(p) => serde_json::from_value(p)?,
+        None => {
+            return Ok(JsonRpcResponse::error(
+                request.id,
+                JsonRpcError::invalid_params("Missing params".to_string()),
+            ));
+        }
+    };
+
+    // Execute tool
+    let context = AuditContext {
+        client,
+        tool: Some(params.name.clone()),
+    };
+    let result = context
+        .scope(handler.call_tool(&params.name, params.arguments))
+        .await;
+    match result {
+        Ok(result) => Ok(JsonRpcResponse::success(
+            request.id,
+            serde_json::to_value(result)?,
+        )),
+        Err(e @ McpError::QuotaExceeded { resets_at, .. }) => {
+            warn!("Tool call refused: {}", e);
+            let retry_after = (resets_at - chrono::Utc::now().timestamp()).max(0);
+            Ok(JsonRpcResponse::error(
+                request.id,
+                JsonRpcError::internal_error(e.to_string()).with_data(serde_json::json!({
+                    "resets_at": resets_at,
+                    "retry_after_seconds": retry_after,
+                })),
+            ))
+        }
+        Err(McpError::Ambiguous {
+            message,
+            candidates,
+        }) => Ok(JsonRpcResponse::error(
+            request.id,
+            JsonRpcError::invalid_params(message)
+                .with_data(serde_json::json!({ "candidates": candidates })),
+        )),
+        Err(e) => {
+            error!("Tool execution failed: {}", e);
+            Ok(JsonRpcResponse::error(
+                request.id,
+                JsonRpcError::internal_error(e.to_string()),
+            ))
+        }
+    }
+}
@@ -50,6 +50,9 @@ pub struct InitializeRequest {
 pub struct ClientCapabilities {
     #[serde(default)]
     pub experimental: HashMap<String, Value>,
+    /// Present when the client can ask the user for input (`elicitation/create`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elicitation: Option<Value>,
 }
 
 /// Client Information
@@ -194,6 +197,11 @@ impl JsonRpcError {
             data: None,
         }
     }
+
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
 }
 
 impl JsonRpcResponse {
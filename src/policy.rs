@@ -0,0 +1,565 @@
+use regex::{Regex, RegexBuilder};
+use tracing::warn;
+
+use crate::cache::SqliteCache;
+use crate::config::{AccessRulesConfig, PolicyConfig};
+use crate::error::{McpError, McpResult};
+use crate::slack::SlackClient;
+use crate::slack::types::SlackChannel;
+
+/// What a tool wants to do with a conversation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+impl Access {
+    fn as_str(self) -> &'static str {
+        match self {
+            Access::Read => "read",
+            Access::Write => "write",
+        }
+    }
+}
+
+/// The parts of a conversation the policy looks at
+#[derive(Debug, Clone, Copy)]
+pub struct ChannelRef<'a> {
+    pub id: &'a str,
+    pub name: Option<&'a str>,
+    /// Direct or group direct message
+    pub is_dm: bool,
+}
+
+impl<'a> ChannelRef<'a> {
+    /// Describe a channel that isn't in the cache
+    pub fn uncached(id: &'a str, name: Option<&'a str>) -> Self {
+        Self {
+            id,
+            name,
+            is_dm: id.starts_with('D') || name.is_some_and(|n| n.starts_with("mpdm-")),
+        }
+    }
+}
+
+impl<'a> From<&'a SlackChannel> for ChannelRef<'a> {
+    fn from(channel: &'a SlackChannel) -> Self {
+        Self {
+            id: &channel.id,
+            name: Some(&channel.name),
+            is_dm: channel.is_im || channel.is_mpim || channel.id.starts_with('D'),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Rules {
+    allow: Vec<Regex>,
+    deny: Vec<Regex>,
+}
+
+impl Rules {
+    fn compile(config: &AccessRulesConfig) -> McpResult<Self> {
+        let compile_all = |patterns: &[String]| -> McpResult<Vec<Regex>> {
+            patterns.iter().map(|p| compile_pattern(p)).collect()
+        };
+        Ok(Self {
+            allow: compile_all(&config.allow)?,
+            deny: compile_all(&config.deny)?,
+        })
+    }
+
+    /// Deny wins; an empty allow list allows everything else. A channel whose name
+    /// is unknown is refused while any deny rule exists, since it might match the name.
+    fn permits(&self, channel: &ChannelRef) -> bool {
+        let matches = |pattern: &Regex| {
+            pattern.is_match(channel.id) || channel.name.is_some_and(|name| pattern.is_match(name))
+        };
+        if self.deny.iter().any(matches) || (name_unknown(channel) && !self.deny.is_empty()) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(matches)
+    }
+}
+
+/// Which conversations tools may read from and write to
+#[derive(Debug)]
+pub struct ChannelPolicy {
+    read: Rules,
+    write: Rules,
+    allow_dms: bool,
+    /// Channels whose messages need a person's approval
+    confirm: Vec<Regex>,
+}
+
+impl Default for ChannelPolicy {
+    fn default() -> Self {
+        Self {
+            read: Rules::default(),
+            write: Rules::default(),
+            allow_dms: true,
+            confirm: Vec::new(),
+        }
+    }
+}
+
+impl ChannelPolicy {
+    pub fn from_config(config: &PolicyConfig) -> McpResult<Self> {
+        Ok(Self {
+            read: Rules::compile(&config.read)?,
+            write: Rules::compile(&config.write)?,
+            allow_dms: config.allow_dms,
+            confirm: config
+                .confirm
+                .iter()
+                .map(|p| compile_pattern(p))
+                .collect::<McpResult<_>>()?,
+        })
+    }
+
+    pub fn allows_dms(&self) -> bool {
+        self.allow_dms
+    }
+
+    fn rules(&self, access: Access) -> &Rules {
+        match access {
+            Access::Read => &self.read,
+            Access::Write => &self.write,
+        }
+    }
+
+    /// Whether the policy hides any conversations from `access`
+    pub fn restricts(&self, access: Access) -> bool {
+        let rules = self.rules(access);
+        !self.allow_dms || !rules.allow.is_empty() || !rules.deny.is_empty()
+    }
+
+    pub fn permits(&self, channel: &ChannelRef, access: Access) -> bool {
+        if channel.is_dm && !self.allow_dms {
+            return false;
+        }
+        self.rules(access).permits(channel)
+    }
+
+    /// Whether `access` to a channel ID is allowed, using cached details for its
+    /// name and type; `name` is used when the channel isn't cached
+    pub fn is_permitted(
+        &self,
+        channel_id: &str,
+        name: Option<&str>,
+        cache: &SqliteCache,
+        access: Access,
+    ) -> bool {
+        match cache.get_channel_by_id(channel_id).ok().flatten() {
+            Some(channel) => self.permits(&ChannelRef::from(&channel), access),
+            None => self.permits(&ChannelRef::uncached(channel_id, name), access),
+        }
+    }
+
+    /// Whether messages to a channel must be approved by a person before posting.
+    /// Channels whose name can't be found need approval, like the deny rules.
+    pub async fn requires_confirmation(
+        &self,
+        channel_id: &str,
+        cache: &SqliteCache,
+        slack_client: Option<&SlackClient>,
+    ) -> bool {
+        if self.confirm.is_empty() {
+            return false;
+        }
+        let details = ChannelDetails::lookup(channel_id, cache, slack_client, &self.confirm).await;
+        let channel = details.as_ref(channel_id);
+        self.confirm.iter().any(|pattern| {
+            pattern.is_match(channel_id)
+                || channel.name.is_some_and(|n| pattern.is_match(n))
+                || name_unknown(&channel)
+        })
+    }
+
+    /// Check the policy grants `access` to a channel ID, failing with
+    /// `PermissionDenied`. Uncached channels are looked up with
+    /// `conversations.info` when an allow or deny rule might match their name.
+    pub async fn check(
+        &self,
+        channel_id: &str,
+        cache: &SqliteCache,
+        slack_client: Option<&SlackClient>,
+        access: Access,
+    ) -> McpResult<()> {
+        let rules = self.rules(access);
+        let details = ChannelDetails::lookup(
+            channel_id,
+            cache,
+            slack_client,
+            rules.allow.iter().chain(&rules.deny),
+        )
+        .await;
+        let channel = details.as_ref(channel_id);
+        if self.permits(&channel, access) {
+            return Ok(());
+        }
+
+        let described = match channel.name {
+            Some(name) => format!("#{} ({})", name, channel_id),
+            None if name_unknown(&channel) => format!(
+                "{} (its name could not be looked up to check the policy rules)",
+                channel_id
+            ),
+            None => channel_id.to_string(),
+        };
+        Err(McpError::PermissionDenied(format!(
+            "Policy does not allow {} access to {}",
+            access.as_str(),
+            described
+        )))
+    }
+}
+
+/// Whether a conversation's name is needed but unknown (DMs have no name)
+fn name_unknown(channel: &ChannelRef) -> bool {
+    channel.name.is_none() && !channel.is_dm
+}
+
+/// The name and type of a channel, from the cache or Slack
+struct ChannelDetails {
+    name: Option<String>,
+    is_dm: bool,
+}
+
+impl ChannelDetails {
+    /// Look a channel up in the cache, falling back to `conversations.info` when one
+    /// of `rules` doesn't match the ID and so might match the name
+    async fn lookup(
+        channel_id: &str,
+        cache: &SqliteCache,
+        slack_client: Option<&SlackClient>,
+        rules: impl IntoIterator<Item = &Regex>,
+    ) -> Self {
+        if let Some(channel) = cache.get_channel_by_id(channel_id).ok().flatten() {
+            return Self {
+                is_dm: ChannelRef::from(&channel).is_dm,
+                name: Some(channel.name),
+            };
+        }
+
+        let uncached = ChannelRef::uncached(channel_id, None);
+        if !uncached.is_dm
+            && rules
+                .into_iter()
+                .any(|pattern| !pattern.is_match(channel_id))
+            && let Some(slack_client) = slack_client
+        {
+            match slack_client.channels.get_channel_info(channel_id).await {
+                Ok(info) => {
+                    let flag = |key: &str| info[key].as_bool().unwrap_or(false);
+                    return Self {
+                        name: info["name"].as_str().map(str::to_string),
+                        is_dm: flag("is_im") || flag("is_mpim"),
+                    };
+                }
+                Err(e) => warn!("Failed to look up channel {}: {}", channel_id, e),
+            }
+        }
+
+        Self {
+            name: None,
+            is_dm: uncached.is_dm,
+        }
+    }
+
+    fn as_ref<'a>(&'a self, channel_id: &'a str) -> ChannelRef<'a> {
+        ChannelRef {
+            id: channel_id,
+            name: self.name.as_deref(),
+            is_dm: self.is_dm,
+        }
+    }
+}
+
+/// Compile a policy pattern: `re:<regex>`, or a glob on a channel name or ID
+/// (`eng-*`, `#eng-*`, `C0123*`). Globs are case-insensitive.
+fn compile_pattern(pattern: &str) -> McpResult<Regex> {
+    let invalid = |e: regex::Error| {
+        McpError::InvalidParameter(format!("Invalid policy pattern '{}': {}", pattern, e))
+    };
+
+    if let Some(regex) = pattern.strip_prefix("re:") {
+        return Regex::new(regex).map_err(invalid);
+    }
+
+    let glob = pattern.trim().trim_start_matches('#');
+    let regex = regex::escape(glob).replace(r"\*", ".*").replace(r"\?", ".");
+    RegexBuilder::new(&format!("^{}$", regex))
+        .case_insensitive(true)
+        .build()
+        .map_err(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn rules(allow: &[&str], deny: &[&str]) -> AccessRulesConfig {
+        AccessRulesConfig {
+            allow: allow.iter().map(|s| s.to_string()).collect(),
+            deny: deny.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn channel<'a>(id: &'a str, name: &'a str) -> ChannelRef<'a> {
+        ChannelRef {
+            id,
+            name: Some(name),
+            is_dm: false,
+        }
+    }
+
+    fn example_policy() -> ChannelPolicy {
+        ChannelPolicy::from_config(&PolicyConfig {
+            read: rules(&["#eng-*", "C0PUBLIC1"], &["eng-secret"]),
+            write: rules(&["#bot-sandbox"], &[]),
+            allow_dms: false,
+            confirm: vec!["#announce*".to_string()],
+        })
+        .unwrap()
+    }
+
+    #[rstest]
+    #[case("eng-*", "eng-backend", true)]
+    #[case("#eng-*", "ENG-Backend", true)]
+    #[case("eng-*", "design", false)]
+    #[case("eng-?", "eng-1", true)]
+    #[case("eng-?", "eng-12", false)]
+    #[case("C0123*", "C0123ABC", true)]
+    #[case("re:^(ops|sre)-", "sre-oncall", true)]
+    #[case("re:^(ops|sre)-", "dev-ops-x", false)]
+    #[case("a.b", "axb", false)]
+    fn test_compile_pattern(#[case] pattern: &str, #[case] input: &str, #[case] expected: bool) {
+        assert_eq!(compile_pattern(pattern).unwrap().is_match(input), expected);
+    }
+
+    #[test]
+    fn test_invalid_regex_is_rejected() {
+        assert!(matches!(
+            compile_pattern("re:(unclosed"),
+            Err(McpError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_default_policy_allows_everything() {
+        let policy = ChannelPolicy::default();
+        let dm = ChannelRef::uncached("D0123ABC", None);
+
+        assert!(policy.permits(&channel("C1", "anything"), Access::Read));
+        assert!(policy.permits(&channel("C1", "anything"), Access::Write));
+        assert!(policy.permits(&dm, Access::Write));
+    }
+
+    #[rstest]
+    #[case(channel("C1", "eng-backend"), Access::Read, true)]
+    #[case(channel("C1", "eng-backend"), Access::Write, false)]
+    #[case(channel("C2", "eng-secret"), Access::Read, false)]
+    #[case(channel("C0PUBLIC1", "general"), Access::Read, true)]
+    #[case(channel("C3", "bot-sandbox"), Access::Write, true)]
+    #[case(channel("C3", "bot-sandbox"), Access::Read, false)]
+    #[case(ChannelRef::uncached("D0123ABC", None), Access::Read, false)]
+    fn test_policy_permits(
+        #[case] channel: ChannelRef<'static>,
+        #[case] access: Access,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(example_policy().permits(&channel, access), expected);
+    }
+
+    #[test]
+    fn test_group_dm_detected_by_name() {
+        let policy = example_policy();
+        let group_dm = ChannelRef::uncached("C0MPIM001", Some("mpdm-alice--bob-1"));
+        assert!(group_dm.is_dm);
+        assert!(!policy.permits(&group_dm, Access::Read));
+    }
+
+    #[tokio::test]
+    async fn test_check_uses_cached_channel_name() {
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+        let mut eng: SlackChannel = serde_json::from_value(serde_json::json!({
+            "id": "C0ENG0001",
+            "name": "eng-backend",
+        }))
+        .unwrap();
+        let mut group_dm = eng.clone();
+        group_dm.id = "G0MPIM001".to_string();
+        group_dm.name = "mpdm-alice--bob-1".to_string();
+        group_dm.is_mpim = true;
+        eng.is_member = true;
+        cache.save_channels(vec![eng, group_dm]).await.unwrap();
+
+        let policy = example_policy();
+        let check = |id, access| policy.check(id, &cache, None, access);
+        assert!(check("C0ENG0001", Access::Read).await.is_ok());
+
+        let denied = check("C0ENG0001", Access::Write).await;
+        assert!(matches!(denied, Err(McpError::PermissionDenied(_))));
+        assert!(denied.unwrap_err().to_string().contains("#eng-backend"));
+
+        // Group DMs count as DMs even though their IDs don't start with D
+        assert!(check("G0MPIM001", Access::Read).await.is_err());
+        // Unknown channels are matched by ID only
+        assert!(check("C0UNKNOWN", Access::Read).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_requires_confirmation() {
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+        let announcements: SlackChannel = serde_json::from_value(serde_json::json!({
+            "id": "C0ANNOUNCE",
+            "name": "announcements",
+        }))
+        .unwrap();
+        let mut eng = announcements.clone();
+        eng.id = "C0ENG0001".to_string();
+        eng.name = "eng-backend".to_string();
+        cache.save_channels(vec![announcements, eng]).await.unwrap();
+
+        let policy = example_policy();
+        assert!(
+            policy
+                .requires_confirmation("C0ANNOUNCE", &cache, None)
+                .await
+        );
+        assert!(
+            !policy
+                .requires_confirmation("C0ENG0001", &cache, None)
+                .await
+        );
+        assert!(
+            !ChannelPolicy::default()
+                .requires_confirmation("C0ANNOUNCE", &cache, None)
+                .await
+        );
+        // A channel whose name can't be found might be an announcement channel
+        assert!(
+            policy
+                .requires_confirmation("C0UNKNOWN", &cache, None)
+                .await
+        );
+        assert!(!policy.requires_confirmation("D0123ABC", &cache, None).await);
+    }
+
+    fn deny_prod_writes() -> ChannelPolicy {
+        ChannelPolicy::from_config(&PolicyConfig {
+            write: rules(&[], &["prod-*", "re:^ops-"]),
+            ..PolicyConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_name_deny_rules_fail_closed_for_unknown_names() {
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+        let policy = deny_prod_writes();
+
+        let denied = policy
+            .check("C0UNKNOWN", &cache, None, Access::Write)
+            .await
+            .unwrap_err();
+        assert!(matches!(denied, McpError::PermissionDenied(_)));
+        assert!(denied.to_string().contains("could not be looked up"));
+
+        // DMs have no name for the rules to match
+        assert!(
+            policy
+                .check("D0123ABC", &cache, None, Access::Write)
+                .await
+                .is_ok()
+        );
+        // Reads have no deny rules
+        assert!(
+            policy
+                .check("C0UNKNOWN", &cache, None, Access::Read)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[rstest]
+    #[case("prod-db", false)]
+    #[case("ops-alerts", false)]
+    #[case("dev", true)]
+    #[tokio::test]
+    async fn test_name_deny_rules_look_up_uncached_channels(
+        #[case] name: &str,
+        #[case] permitted: bool,
+    ) {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let slack = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/conversations.info"))
+            .and(query_param("channel", "C0UNCACHED"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "ok": true,
+                "channel": {"id": "C0UNCACHED", "name": name, "is_channel": true},
+            })))
+            .expect(1)
+            .mount(&slack)
+            .await;
+        let client = SlackClient::for_api_url(&slack.uri());
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+
+        let result = deny_prod_writes()
+            .check("C0UNCACHED", &cache, Some(&client), Access::Write)
+            .await;
+        assert_eq!(result.is_ok(), permitted);
+        if !permitted {
+            assert!(result.unwrap_err().to_string().contains(name));
+        }
+    }
+
+    #[rstest]
+    #[case(Some("eng-platform"), true)]
+    #[case(Some("design"), false)]
+    #[case(None, false)]
+    #[tokio::test]
+    async fn test_name_allow_rules_look_up_uncached_channels(
+        #[case] name: Option<&str>,
+        #[case] permitted: bool,
+    ) {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let slack = MockServer::start().await;
+        let response = match name {
+            Some(name) => ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "ok": true,
+                "channel": {"id": "C0ENG9999", "name": name, "is_channel": true},
+            })),
+            None => ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({"ok": false, "error": "channel_not_found"})),
+        };
+        Mock::given(method("GET"))
+            .and(path("/conversations.info"))
+            .and(query_param("channel", "C0ENG9999"))
+            .respond_with(response)
+            .expect(1)
+            .mount(&slack)
+            .await;
+        let client = SlackClient::for_api_url(&slack.uri());
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+
+        // Only an allow rule on the name, which the ID can't match
+        let policy = ChannelPolicy::from_config(&PolicyConfig {
+            read: rules(&["#eng-*"], &[]),
+            ..Default::default()
+        })
+        .unwrap();
+        let result = policy
+            .check("C0ENG9999", &cache, Some(&client), Access::Read)
+            .await;
+        assert_eq!(result.is_ok(), permitted);
+    }
+}
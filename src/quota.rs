@@ -0,0 +1,546 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::cell::Cell;
+use std::future::Future;
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::audit::AuditContext;
+use crate::cache::{QuotaCheck, SqliteCache};
+use crate::config::{QuotaConfig, QuotaScope, ToolQuotaConfig};
+use crate::error::{IntoMcpError, McpError, McpResult};
+use crate::utils::resolve_channel_id;
+
+const API_CALL_WINDOW_SECONDS: u64 = 60;
+
+/// A session's Slack API call budget, shared through SQLite by its concurrent
+/// tool calls. Each call is counted as it is made, beyond the `reserved` calls
+/// already counted when the tool call started.
+#[derive(Clone)]
+pub struct ApiBudget {
+    cache: Arc<SqliteCache>,
+    key: String,
+    limit: u64,
+    reserved: u64,
+}
+
+impl std::fmt::Debug for ApiBudget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiBudget")
+            .field("key", &self.key)
+            .field("limit", &self.limit)
+            .field("reserved", &self.reserved)
+            .finish()
+    }
+}
+
+impl ApiBudget {
+    fn spend_one(&self) -> McpResult<()> {
+        let check = self
+            .cache
+            .consume_quota(&self.key, Some(self.limit), API_CALL_WINDOW_SECONDS, 1)
+            .mcp_context("Failed to check quota")?;
+        match check {
+            QuotaCheck::Allowed(_) => Ok(()),
+            QuotaCheck::Exceeded(usage) => Err(exceeded(
+                "this session used up its Slack API calls for this minute in the middle of a tool call".to_string(),
+                usage.resets_at,
+            )),
+        }
+    }
+}
+
+struct ApiCalls {
+    made: Cell<u64>,
+    budget: Option<ApiBudget>,
+}
+
+tokio::task_local! {
+    static API_CALLS: ApiCalls;
+}
+
+/// Count a Slack API call against the tool call running on this task, if any.
+/// Fails instead once its session has used up its budget.
+pub fn record_api_call() -> McpResult<()> {
+    API_CALLS
+        .try_with(|calls| {
+            let made = calls.made.get();
+            if let Some(budget) = &calls.budget
+                && made >= budget.reserved
+            {
+                budget.spend_one()?;
+            }
+            calls.made.set(made + 1);
+            Ok(())
+        })
+        .unwrap_or(Ok(()))
+}
+
+/// Run `fut` with its Slack API calls spent from `budget`, returning its output and
+/// the number of calls it made
+pub async fn count_api_calls<F: Future>(budget: Option<ApiBudget>, fut: F) -> (F::Output, u64) {
+    let calls = ApiCalls {
+        made: Cell::new(0),
+        budget,
+    };
+    API_CALLS
+        .scope(calls, async {
+            let output = fut.await;
+            (output, API_CALLS.with(|calls| calls.made.get()))
+        })
+        .await
+}
+
+/// Enforces the configured tool and Slack API quotas for one MCP session
+pub struct QuotaEnforcer {
+    config: QuotaConfig,
+    cache: Arc<SqliteCache>,
+    session_id: String,
+}
+
+/// Quota counted for a tool call before it runs, settled with
+/// [`QuotaEnforcer::settle`] once it has finished
+#[derive(Debug)]
+pub struct Reservation {
+    /// Tool quota keys counted for this call, with their window lengths
+    counted: Vec<(String, u64)>,
+    api_budget: Option<ApiBudget>,
+}
+
+impl Reservation {
+    pub fn api_budget(&self) -> Option<ApiBudget> {
+        self.api_budget.clone()
+    }
+}
+
+impl QuotaEnforcer {
+    pub fn new(config: QuotaConfig, cache: Arc<SqliteCache>) -> Self {
+        Self {
+            config,
+            cache,
+            session_id: uuid::Uuid::new_v4().to_string(),
+        }
+    }
+
+    /// Count a tool call against its quotas, refusing it when a budget is spent.
+    /// The count is only provisional: [`Self::settle`] gives it back when the call
+    /// doesn't go through. The session's first Slack API call is reserved up front
+    /// so concurrent calls can't all start on the last one.
+    pub async fn reserve(&self, tool: &str, arguments: &Value) -> McpResult<Reservation> {
+        let api_limit = self.config.api_calls_per_minute;
+        let api_budget = if api_limit > 0 {
+            let key = self.session_key();
+            let check = self
+                .cache
+                .consume_quota(&key, Some(api_limit), API_CALL_WINDOW_SECONDS, 1)
+                .mcp_context("Failed to check quota")?;
+            if let QuotaCheck::Exceeded(usage) = check {
+                return Err(exceeded(
+                    format!(
+                        "this session made {} Slack API calls this minute (limit {})",
+                        usage.used, api_limit
+                    ),
+                    usage.resets_at,
+                ));
+            }
+            Some(ApiBudget {
+                cache: self.cache.clone(),
+                key,
+                limit: api_limit,
+                reserved: 1,
+            })
+        } else {
+            None
+        };
+
+        let mut reservation = Reservation {
+            counted: Vec::new(),
+            api_budget,
+        };
+        for rule in self.config.tools.iter().filter(|rule| rule.tool == tool) {
+            // Calls without a channel argument aren't counted by per-channel rules
+            let Some(scope) = self.scope_of(rule.per, arguments).await else {
+                continue;
+            };
+            let key = format!("tool:{}:{}:{}", tool, rule.per.as_str(), scope);
+            let check = self
+                .cache
+                .consume_quota(&key, Some(rule.limit), rule.window_seconds, 1)
+                .mcp_context("Failed to check quota")?;
+            if let QuotaCheck::Exceeded(usage) = check {
+                // Rules checked before this one don't keep the refused call
+                self.settle(reservation, false, 0);
+                return Err(exceeded(describe_rule(rule, &scope), usage.resets_at));
+            }
+            reservation.counted.push((key, rule.window_seconds));
+        }
+
+        Ok(reservation)
+    }
+
+    /// Finish a reserved call: give its tool quota back unless it `went_through`,
+    /// and give back reserved Slack API calls it didn't make. The calls it made
+    /// count either way.
+    pub fn settle(&self, reservation: Reservation, went_through: bool, api_calls: u64) {
+        if !went_through {
+            for (key, window_seconds) in &reservation.counted {
+                if let Err(e) = self.cache.release_quota(key, *window_seconds, 1) {
+                    warn!("Failed to release quota {}: {}", key, e);
+                }
+            }
+        }
+
+        if let Some(budget) = &reservation.api_budget
+            && api_calls < budget.reserved
+            && let Err(e) = self.cache.release_quota(
+                &budget.key,
+                API_CALL_WINDOW_SECONDS,
+                budget.reserved - api_calls,
+            )
+        {
+            warn!("Failed to release Slack API budget: {}", e);
+        }
+    }
+
+    fn session_key(&self) -> String {
+        format!("api_calls:session:{}", self.session_id)
+    }
+
+    /// What a call is counted under for a rule's scope
+    async fn scope_of(&self, per: QuotaScope, arguments: &Value) -> Option<String> {
+        match per {
+            QuotaScope::Global => Some("*".to_string()),
+            QuotaScope::Client => Some(
+                AuditContext::current()
+                    .client
+                    .unwrap_or_else(|| "unknown".to_string()),
+            ),
+            QuotaScope::Channel => {
+                let channel = arguments.get("channel")?.as_str()?.trim();
+                // Every way of writing a cached channel shares its ID's counter
+                Some(
+                    resolve_channel_id(channel, &self.cache, None)
+                        .await
+                        .unwrap_or_else(|_| channel.trim_start_matches('#').to_lowercase()),
+                )
+            }
+        }
+    }
+}
+
+fn describe_rule(rule: &ToolQuotaConfig, scope: &str) -> String {
+    let per = match rule.per {
+        QuotaScope::Global => String::new(),
+        QuotaScope::Client => format!(" per client ({} has used them all)", scope),
+        QuotaScope::Channel => format!(" per channel ({} has used them all)", scope),
+    };
+    format!(
+        "{} is limited to {} call{} per {}{}",
+        rule.tool,
+        rule.limit,
+        if rule.limit == 1 { "" } else { "s" },
+        describe_window(rule.window_seconds),
+        per
+    )
+}
+
+fn describe_window(seconds: u64) -> String {
+    match seconds {
+        60 => "minute".to_string(),
+        3600 => "hour".to_string(),
+        86400 => "day".to_string(),
+        s if s % 3600 == 0 => format!("{} hours", s / 3600),
+        s if s % 60 == 0 => format!("{} minutes", s / 60),
+        s => format!("{} seconds", s),
+    }
+}
+
+fn exceeded(reason: String, resets_at: i64) -> McpError {
+    let wait = (resets_at - Utc::now().timestamp()).max(0);
+    let at = DateTime::from_timestamp(resets_at, 0)
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_else(|| resets_at.to_string());
+    McpError::QuotaExceeded {
+        message: format!("{}; the budget resets at {} (in {}s)", reason, at, wait),
+        resets_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slack::types::SlackChannel;
+    use rstest::rstest;
+    use serde_json::json;
+
+    fn rule(tool: &str, limit: u64, per: QuotaScope) -> ToolQuotaConfig {
+        ToolQuotaConfig {
+            tool: tool.to_string(),
+            limit,
+            // A window that can't roll over while the test runs
+            window_seconds: 1 << 40,
+            per,
+        }
+    }
+
+    async fn enforcer(config: QuotaConfig) -> QuotaEnforcer {
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+        QuotaEnforcer::new(config, Arc::new(cache))
+    }
+
+    #[tokio::test]
+    async fn test_per_channel_tool_quota() {
+        let quotas = enforcer(QuotaConfig {
+            tools: vec![rule("send_message", 2, QuotaScope::Channel)],
+            ..Default::default()
+        })
+        .await;
+        let to = |channel: &str| json!({"channel": channel, "text": "hi"});
+
+        quotas
+            .reserve("send_message", &to("C0123ABCD"))
+            .await
+            .unwrap();
+        quotas
+            .reserve("send_message", &to("C0123ABCD"))
+            .await
+            .unwrap();
+        let err = quotas
+            .reserve("send_message", &to("C0123ABCD"))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, McpError::QuotaExceeded { .. }));
+        let message = err.to_string();
+        assert!(message.contains("send_message is limited to 2 calls per"));
+        assert!(message.contains("C0123ABCD has used them all"));
+        assert!(message.contains("the budget resets at"));
+
+        // Other channels and other tools are unaffected
+        quotas
+            .reserve("send_message", &to("C0456EFGH"))
+            .await
+            .unwrap();
+        quotas
+            .reserve("read_thread", &to("C0123ABCD"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_per_client_quota_uses_audit_context() {
+        let quotas = enforcer(QuotaConfig {
+            tools: vec![rule("search_messages", 1, QuotaScope::Client)],
+            ..Default::default()
+        })
+        .await;
+        let as_client = |client: &str| AuditContext {
+            client: Some(client.to_string()),
+            tool: Some("search_messages".to_string()),
+        };
+
+        let args = json!({"query": "x"});
+        let reserve = || quotas.reserve("search_messages", &args);
+        as_client("a")
+            .scope(async { reserve().await.unwrap() })
+            .await;
+        as_client("b")
+            .scope(async { reserve().await.unwrap() })
+            .await;
+        assert!(
+            as_client("a")
+                .scope(async { reserve().await })
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_channel_name_and_id_share_a_counter() {
+        let cache = SqliteCache::new(":memory:").await.unwrap();
+        cache
+            .save_channels(vec![SlackChannel {
+                id: "C0GENERAL1".to_string(),
+                name: "general".to_string(),
+                is_channel: true,
+                is_private: false,
+                is_archived: false,
+                is_general: true,
+                is_im: false,
+                is_mpim: false,
+                is_member: true,
+                created: None,
+                creator: None,
+                topic: None,
+                purpose: None,
+                num_members: None,
+                previous_names: vec![],
+            }])
+            .await
+            .unwrap();
+        let quotas = QuotaEnforcer::new(
+            QuotaConfig {
+                tools: vec![rule("send_message", 4, QuotaScope::Channel)],
+                ..Default::default()
+            },
+            Arc::new(cache),
+        );
+        let to = |channel: &str| json!({"channel": channel, "text": "hi"});
+
+        for channel in ["general", "#general", "GENERAL", " C0GENERAL1 "] {
+            quotas.reserve("send_message", &to(channel)).await.unwrap();
+        }
+        let err = quotas
+            .reserve("send_message", &to("#General"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("C0GENERAL1 has used them all"));
+
+        // Uncached names share a counter however they are written
+        for channel in ["#New-Team", "new-team", "NEW-TEAM", " #new-team"] {
+            quotas.reserve("send_message", &to(channel)).await.unwrap();
+        }
+        let err = quotas
+            .reserve("send_message", &to("new-team"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("new-team has used them all"));
+    }
+
+    #[tokio::test]
+    async fn test_calls_that_dont_go_through_are_not_charged() {
+        let quotas = enforcer(QuotaConfig {
+            tools: vec![
+                rule("send_message", 1, QuotaScope::Global),
+                rule("send_message", 5, QuotaScope::Channel),
+            ],
+            ..Default::default()
+        })
+        .await;
+        let args = json!({"channel": "C0123ABCD", "text": "hi"});
+
+        // Refused by policy, the scanner, ...: the budget is given back
+        let reservation = quotas.reserve("send_message", &args).await.unwrap();
+        quotas.settle(reservation, false, 0);
+
+        let reservation = quotas.reserve("send_message", &args).await.unwrap();
+        quotas.settle(reservation, true, 0);
+        assert!(quotas.reserve("send_message", &args).await.is_err());
+
+        // The refused call didn't keep its place in the per-channel counter either
+        let usage = quotas
+            .cache
+            .quota_usage("tool:send_message:channel:C0123ABCD", 1 << 40)
+            .unwrap();
+        assert_eq!(usage.used, 1);
+    }
+
+    #[tokio::test]
+    async fn test_session_api_call_budget() {
+        let quotas = enforcer(QuotaConfig {
+            api_calls_per_minute: 3,
+            ..Default::default()
+        })
+        .await;
+
+        let reservation = quotas.reserve("search_users", &json!({})).await.unwrap();
+        let (result, calls) = count_api_calls(reservation.api_budget(), async {
+            record_api_call()?;
+            record_api_call()
+        })
+        .await;
+        result.unwrap();
+        assert_eq!(calls, 2);
+        quotas.settle(reservation, true, calls);
+
+        // One call left: a tool call that needs more is stopped midway
+        let reservation = quotas.reserve("search_users", &json!({})).await.unwrap();
+        let (result, calls) = count_api_calls(reservation.api_budget(), async {
+            record_api_call()?;
+            record_api_call()
+        })
+        .await;
+        assert!(matches!(result, Err(McpError::QuotaExceeded { .. })));
+        assert_eq!(calls, 1);
+        quotas.settle(reservation, false, calls);
+
+        let err = quotas
+            .reserve("search_users", &json!({}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("3 Slack API calls this minute"));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_calls_share_the_api_budget() {
+        let quotas = Arc::new(
+            enforcer(QuotaConfig {
+                api_calls_per_minute: 5,
+                ..Default::default()
+            })
+            .await,
+        );
+
+        // Every call starts before any has finished, each wanting 3 API calls
+        let mut tasks = tokio::task::JoinSet::new();
+        for _ in 0..10 {
+            let quotas = quotas.clone();
+            tasks.spawn(async move {
+                let Ok(reservation) = quotas.reserve("search_users", &json!({})).await else {
+                    return 0;
+                };
+                let (_, calls) = count_api_calls(reservation.api_budget(), async {
+                    for _ in 0..3 {
+                        tokio::task::yield_now().await;
+                        record_api_call()?;
+                    }
+                    Ok::<_, McpError>(())
+                })
+                .await;
+                quotas.settle(reservation, true, calls);
+                calls
+            });
+        }
+        let mut made = 0;
+        while let Some(calls) = tasks.join_next().await {
+            made += calls.unwrap();
+        }
+
+        assert_eq!(made, 5);
+        let usage = quotas
+            .cache
+            .quota_usage(&quotas.session_key(), API_CALL_WINDOW_SECONDS)
+            .unwrap();
+        assert_eq!(usage.used, 5);
+    }
+
+    #[tokio::test]
+    async fn test_unused_reserved_api_call_is_given_back() {
+        let quotas = enforcer(QuotaConfig {
+            api_calls_per_minute: 1,
+            ..Default::default()
+        })
+        .await;
+
+        // A cache-only tool makes no Slack API calls
+        let reservation = quotas.reserve("search_users", &json!({})).await.unwrap();
+        quotas.settle(reservation, true, 0);
+
+        let reservation = quotas.reserve("search_users", &json!({})).await.unwrap();
+        let (result, calls) =
+            count_api_calls(reservation.api_budget(), async { record_api_call() }).await;
+        result.unwrap();
+        quotas.settle(reservation, true, calls);
+        assert!(quotas.reserve("search_users", &json!({})).await.is_err());
+    }
+
+    #[rstest]
+    #[case(60, "minute")]
+    #[case(3600, "hour")]
+    #[case(7200, "2 hours")]
+    #[case(300, "5 minutes")]
+    #[case(45, "45 seconds")]
+    fn test_describe_window(#[case] seconds: u64, #[case] expected: &str) {
+        assert_eq!(describe_window(seconds), expected);
+    }
+}
@@ -11,6 +11,8 @@ pub enum ApiMethod {
 pub struct ApiConfig {
     pub method: ApiMethod,
     pub prefer_user_token: bool,
+    /// Changes workspace state; calls are recorded in the audit log
+    pub mutating: bool,
 }
 
 impl ApiConfig {
@@ -18,8 +20,14 @@ impl ApiConfig {
         Self {
             method,
             prefer_user_token,
+            mutating: false,
         }
     }
+
+    pub const fn mutating(mut self) -> Self {
+        self.mutating = true;
+        self
+    }
 }
 
 // Centralized API method configuration
@@ -39,30 +47,36 @@ pub static API_CONFIGS: LazyLock<HashMap<&'static str, ApiConfig>> = LazyLock::n
         "conversations.members",
         ApiConfig::new(ApiMethod::Get, true),
     ); // Prefer user token for private channel members
+    m.insert("conversations.info", ApiConfig::new(ApiMethod::Get, true)); // Prefer user token for private channel details
     m.insert("users.list", ApiConfig::new(ApiMethod::Get, false));
+    m.insert("usergroups.list", ApiConfig::new(ApiMethod::Get, false));
+    m.insert("chat.getPermalink", ApiConfig::new(ApiMethod::Get, false));
     m.insert("conversations.list", ApiConfig::new(ApiMethod::Get, true)); // Prefer user token for private channels
 
     // POST JSON methods
     m.insert(
         "chat.postMessage",
-        ApiConfig::new(ApiMethod::PostJson, false),
+        ApiConfig::new(ApiMethod::PostJson, false).mutating(),
     );
     m.insert(
         "chat.scheduleMessage",
-        ApiConfig::new(ApiMethod::PostJson, false),
+        ApiConfig::new(ApiMethod::PostJson, false).mutating(),
     );
     m.insert(
         "conversations.open",
         ApiConfig::new(ApiMethod::PostJson, false),
     );
-    m.insert("reactions.add", ApiConfig::new(ApiMethod::PostJson, false));
+    m.insert(
+        "reactions.add",
+        ApiConfig::new(ApiMethod::PostJson, false).mutating(),
+    );
     m.insert(
         "reactions.remove",
-        ApiConfig::new(ApiMethod::PostJson, false),
+        ApiConfig::new(ApiMethod::PostJson, false).mutating(),
     );
     m.insert(
         "users.profile.set",
-        ApiConfig::new(ApiMethod::PostJson, true),
+        ApiConfig::new(ApiMethod::PostJson, true).mutating(),
     ); // Requires user token
 
     // POST Form methods
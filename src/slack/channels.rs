@@ -1,5 +1,5 @@
 use anyhow::Result;
-use serde_json::json;
+use serde_json::{Value, json};
 use std::sync::Arc;
 
 use super::core::SlackCore;
@@ -92,6 +92,20 @@ impl SlackChannelClient {
         Ok(total_fetched)
     }
 
+    /// Get a conversation's details (name, type) with `conversations.info`
+    pub async fn get_channel_info(&self, channel: &str) -> Result<Value> {
+        let params = json!({
+            "channel": channel,
+        });
+
+        let response = self
+            .core
+            .api_call("conversations.info", params, None, true)
+            .await?;
+
+        Ok(response["channel"].clone())
+    }
+
     /// Get channel members
     pub async fn get_channel_members(
         &self,
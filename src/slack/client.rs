@@ -1,9 +1,10 @@
 use std::sync::Arc;
 
+use crate::cache::SqliteCache;
 use crate::config::Config;
 
 use super::channels::SlackChannelClient;
-use super::core::SlackCore;
+use super::core::{SlackCore, TokenKind};
 use super::messages::SlackMessageClient;
 use super::users::SlackUserClient;
 
@@ -17,16 +18,80 @@ pub struct SlackClient {
     pub messages: SlackMessageClient,
     pub users: SlackUserClient,
     pub channels: SlackChannelClient,
+    core: Arc<SlackCore>,
 }
 
 impl SlackClient {
     pub fn new(config: Config) -> Self {
-        let core = Arc::new(SlackCore::new(config));
+        Self::from_core(SlackCore::new(config))
+    }
+
+    fn from_core(core: SlackCore) -> Self {
+        let core = Arc::new(core);
 
         Self {
             messages: SlackMessageClient::new(core.clone()),
             users: SlackUserClient::new(core.clone()),
-            channels: SlackChannelClient::new(core),
+            channels: SlackChannelClient::new(core.clone()),
+            core,
         }
     }
+
+    /// Record mutating Slack calls made by any sub-client in `cache`
+    pub fn enable_audit_log(&self, cache: Arc<SqliteCache>) {
+        self.core.enable_audit_log(cache);
+    }
+
+    /// Whether a token of this kind is configured
+    pub fn has_token(&self, kind: TokenKind) -> bool {
+        self.core.has_token(kind)
+    }
+
+    /// Token messages are posted with when the caller doesn't choose one
+    pub fn default_post_token(&self) -> Option<TokenKind> {
+        self.core.default_token_kind(false)
+    }
+}
+
+#[cfg(test)]
+impl SlackClient {
+    /// Client for a Slack API at `api_url`, with both tokens configured
+    pub(crate) fn for_api_url(api_url: &str) -> Arc<Self> {
+        let config = serde_json::from_value(serde_json::json!({
+            "mode": "normal",
+            "slack": {
+                "bot_token": "xoxb-test",
+                "user_token": "xoxp-test",
+            },
+            "cache": {
+                "data_path": ":memory:",
+                "ttl_users_hours": 24,
+                "ttl_channels_hours": 24,
+                "ttl_members_hours": 12,
+                "compression": "none",
+            },
+            "retry": {
+                "max_attempts": 1,
+                "initial_delay_ms": 1,
+                "max_delay_ms": 1,
+                "exponential_base": 2.0,
+            },
+            "connection": {
+                "timeout_seconds": 1,
+                "max_idle_per_host": 1,
+                "pool_idle_timeout_seconds": 1,
+            },
+            "output": {"format": "json", "timezone": "UTC", "relative_times": false},
+            "messaging": {
+                "idempotency_ttl_hours": 24,
+                "duplicate_window_seconds": 60,
+                "draft_ttl_minutes": 60,
+                "confirmation_timeout_seconds": 1,
+            },
+        }))
+        .unwrap();
+        Arc::new(Self::from_core(
+            SlackCore::new(config).with_api_url(api_url),
+        ))
+    }
 }
@@ -4,23 +4,55 @@ use governor::{
     state::InMemoryState,
 };
 use reqwest::{Client as HttpClient, StatusCode};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::num::NonZeroU32;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 use tracing::warn;
 
+use crate::audit;
+use crate::cache::SqliteCache;
 use crate::config::Config;
-use crate::slack::api_config::{ApiMethod, get_api_config};
+use crate::quota;
+use crate::slack::api_config::{ApiConfig, ApiMethod, get_api_config};
 
 type SimpleRateLimiter = Arc<RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>>;
 use governor::state::NotKeyed;
 
+const DEFAULT_API_URL: &str = "https://slack.com/api";
+
+/// Which Slack token a call is made with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenKind {
+    Bot,
+    User,
+}
+
+impl TokenKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TokenKind::Bot => "bot",
+            TokenKind::User => "user",
+        }
+    }
+
+    fn env_var(self) -> &'static str {
+        match self {
+            TokenKind::Bot => "SLACK_BOT_TOKEN",
+            TokenKind::User => "SLACK_USER_TOKEN",
+        }
+    }
+}
+
 /// Core Slack API client with shared functionality
 pub struct SlackCore {
     pub(crate) config: Config,
     pub(crate) http_client: HttpClient,
     pub(crate) rate_limiter: SimpleRateLimiter,
+    audit_log: OnceLock<Arc<SqliteCache>>,
+    api_url: String,
 }
 
 impl SlackCore {
@@ -39,24 +71,66 @@ impl SlackCore {
             config,
             http_client,
             rate_limiter,
+            audit_log: OnceLock::new(),
+            api_url: DEFAULT_API_URL.to_string(),
         }
     }
 
+    /// Send calls to a mock Slack API at `api_url` instead
+    #[cfg(test)]
+    pub(crate) fn with_api_url(mut self, api_url: &str) -> Self {
+        self.api_url = api_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Record every mutating API call in the cache's audit log
+    pub fn enable_audit_log(&self, cache: Arc<SqliteCache>) {
+        let _ = self.audit_log.set(cache);
+    }
+
     /// Get appropriate token based on preference
     pub(crate) fn get_token(&self, prefer_user: bool) -> Result<&str> {
-        if prefer_user && let Some(token) = &self.config.slack.user_token {
-            return Ok(token);
-        }
+        let kind = self
+            .default_token_kind(prefer_user)
+            .ok_or_else(|| anyhow::anyhow!("No Slack token available"))?;
+        self.token(kind)
+    }
 
-        if let Some(token) = &self.config.slack.bot_token {
-            return Ok(token);
-        }
+    /// Web API URL of a method
+    fn url(&self, method: &str) -> String {
+        format!("{}/{}", self.api_url, method)
+    }
 
-        if let Some(token) = &self.config.slack.user_token {
-            return Ok(token);
-        }
+    /// A specific token, failing when it isn't configured
+    pub(crate) fn token(&self, kind: TokenKind) -> Result<&str> {
+        let token = match kind {
+            TokenKind::Bot => &self.config.slack.bot_token,
+            TokenKind::User => &self.config.slack.user_token,
+        };
+        token.as_deref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "No {} token configured (set {})",
+                kind.as_str(),
+                kind.env_var()
+            )
+        })
+    }
+
+    /// Whether a token of this kind is configured
+    pub fn has_token(&self, kind: TokenKind) -> bool {
+        self.token(kind).is_ok()
+    }
 
-        Err(anyhow::anyhow!("No Slack token available"))
+    /// Token used when a caller doesn't choose one: the user token if preferred
+    /// and configured, otherwise the bot token, otherwise the user token
+    pub fn default_token_kind(&self, prefer_user: bool) -> Option<TokenKind> {
+        let slack = &self.config.slack;
+        match (slack.bot_token.is_some(), slack.user_token.is_some()) {
+            (_, true) if prefer_user => Some(TokenKind::User),
+            (true, _) => Some(TokenKind::Bot),
+            (false, true) => Some(TokenKind::User),
+            (false, false) => None,
+        }
     }
 
     /// Core API call method shared by all specialized clients
@@ -75,6 +149,49 @@ impl SlackCore {
         let actual_prefer_user = prefer_user_token || api_config.prefer_user_token;
         let token = self.get_token(actual_prefer_user)?;
 
+        self.call(method, api_config, &params, files, token).await
+    }
+
+    /// Like [`Self::api_call`], with the token chosen by the caller instead of
+    /// the method's preference
+    pub async fn api_call_as(&self, method: &str, params: Value, kind: TokenKind) -> Result<Value> {
+        let api_config = get_api_config(method)
+            .ok_or_else(|| anyhow::anyhow!("Unknown API method: {}", method))?;
+        let token = self.token(kind)?;
+
+        self.call(method, api_config, &params, None, token).await
+    }
+
+    async fn call(
+        &self,
+        method: &str,
+        api_config: &ApiConfig,
+        params: &Value,
+        files: Option<Vec<(&str, Vec<u8>)>>,
+        token: &str,
+    ) -> Result<Value> {
+        quota::record_api_call()?;
+
+        let audit_log = self.audit_log.get().filter(|_| api_config.mutating);
+        let result = self.send(method, api_config, params, files, token).await;
+
+        if let Some(cache) = audit_log
+            && let Err(e) = cache.record_audit(&audit::entry(method, params, &result))
+        {
+            warn!("Failed to record {} in audit log: {}", method, e);
+        }
+
+        result
+    }
+
+    async fn send(
+        &self,
+        method: &str,
+        api_config: &ApiConfig,
+        params: &Value,
+        files: Option<Vec<(&str, Vec<u8>)>>,
+        token: &str,
+    ) -> Result<Value> {
         // Rate limiting
         self.rate_limiter
             .until_ready_with_jitter(Jitter::up_to(Duration::from_millis(100)))
@@ -88,13 +205,13 @@ impl SlackCore {
         loop {
             let response = match &api_config.method {
                 ApiMethod::Get => {
-                    let mut url = format!("https://slack.com/api/{}", method);
+                    let mut url = self.url(method);
                     if !params
                         .as_object()
                         .unwrap_or(&serde_json::Map::new())
                         .is_empty()
                     {
-                        let query_string = serde_urlencoded::to_string(&params)?;
+                        let query_string = serde_urlencoded::to_string(params)?;
                         url.push_str(&format!("?{}", query_string));
                     }
 
@@ -106,10 +223,10 @@ impl SlackCore {
                 }
                 ApiMethod::PostJson => {
                     self.http_client
-                        .post(format!("https://slack.com/api/{}", method))
+                        .post(self.url(method))
                         .header("Authorization", format!("Bearer {}", token))
                         .header("Content-Type", "application/json")
-                        .json(&params)
+                        .json(params)
                         .send()
                         .await
                 }
@@ -143,7 +260,7 @@ impl SlackCore {
                         }
 
                         self.http_client
-                            .post(format!("https://slack.com/api/{}", method))
+                            .post(self.url(method))
                             .header("Authorization", format!("Bearer {}", token))
                             .multipart(form)
                             .send()
@@ -151,10 +268,10 @@ impl SlackCore {
                     } else {
                         // Regular form data
                         self.http_client
-                            .post(format!("https://slack.com/api/{}", method))
+                            .post(self.url(method))
                             .header("Authorization", format!("Bearer {}", token))
                             .header("Content-Type", "application/x-www-form-urlencoded")
-                            .form(&params)
+                            .form(params)
                             .send()
                             .await
                     }
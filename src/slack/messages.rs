@@ -1,33 +1,64 @@
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use std::sync::Arc;
+use tokio::sync::OnceCell;
+use tracing::warn;
 
-use super::core::SlackCore;
+use super::core::{SlackCore, TokenKind};
 use crate::slack::SlackMessage;
+use crate::utils::Permalink;
+
+/// Who a message is posted as
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostIdentity {
+    /// Token to post with; by default the bot token when one is configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<TokenKind>,
+    /// Display name for a bot post (needs the `chat:write.customize` scope)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Emoji avatar for a bot post, e.g. `:robot_face:`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_emoji: Option<String>,
+}
 
 pub struct SlackMessageClient {
     core: Arc<SlackCore>,
+    /// Workspace URL learned from the first `chat.getPermalink` call
+    workspace_url: OnceCell<String>,
 }
 
 impl SlackMessageClient {
     pub fn new(core: Arc<SlackCore>) -> Self {
-        Self { core }
+        Self {
+            core,
+            workspace_url: OnceCell::new(),
+        }
     }
 
-    /// Send a message to a channel
-    pub async fn post_message(
-        &self,
+    /// Build the `chat.postMessage` request body
+    pub fn post_message_payload(
         channel: &str,
         text: Option<&str>,
         blocks: Option<&Vec<Value>>,
         thread_ts: Option<&str>,
         reply_broadcast: bool,
-    ) -> Result<String> {
+        identity: &PostIdentity,
+    ) -> Value {
         let mut params = json!({
             "channel": channel,
             "reply_broadcast": reply_broadcast,
         });
 
+        if let Some(username) = &identity.username {
+            params["username"] = json!(username);
+        }
+
+        if let Some(icon_emoji) = &identity.icon_emoji {
+            params["icon_emoji"] = json!(icon_emoji);
+        }
+
         if let Some(text) = text {
             params["text"] = json!(text);
         }
@@ -40,10 +71,34 @@ impl SlackMessageClient {
             params["thread_ts"] = json!(ts);
         }
 
-        let response = self
-            .core
-            .api_call("chat.postMessage", params, None, false)
-            .await?;
+        params
+    }
+
+    /// Send a message to a channel
+    pub async fn post_message(
+        &self,
+        channel: &str,
+        text: Option<&str>,
+        blocks: Option<&Vec<Value>>,
+        thread_ts: Option<&str>,
+        reply_broadcast: bool,
+        identity: &PostIdentity,
+    ) -> Result<String> {
+        let params =
+            Self::post_message_payload(channel, text, blocks, thread_ts, reply_broadcast, identity);
+
+        let response = match identity.token {
+            Some(kind) => {
+                self.core
+                    .api_call_as("chat.postMessage", params, kind)
+                    .await?
+            }
+            None => {
+                self.core
+                    .api_call("chat.postMessage", params, None, false)
+                    .await?
+            }
+        };
 
         let timestamp = response["ts"]
             .as_str()
@@ -58,6 +113,7 @@ impl SlackMessageClient {
         channel: &str,
         limit: usize,
         cursor: Option<&str>,
+        latest: Option<&str>,
     ) -> Result<(Vec<SlackMessage>, Option<String>)> {
         let mut params = json!({
             "channel": channel,
@@ -68,6 +124,11 @@ impl SlackMessageClient {
             params["cursor"] = json!(cursor);
         }
 
+        // Only messages strictly older than `latest`
+        if let Some(latest) = latest {
+            params["latest"] = json!(latest);
+        }
+
         let response = self
             .core
             .api_call("conversations.history", params, None, false)
@@ -88,19 +149,65 @@ impl SlackMessageClient {
         Ok((messages, next_cursor))
     }
 
+    /// Get channel history between `oldest` and `latest`, newest first
+    pub async fn get_history_range(
+        &self,
+        channel: &str,
+        oldest: Option<&str>,
+        latest: Option<&str>,
+        inclusive: bool,
+        limit: usize,
+    ) -> Result<(Vec<SlackMessage>, bool)> {
+        let mut params = json!({
+            "channel": channel,
+            "inclusive": inclusive,
+            "limit": limit,
+        });
+
+        if let Some(oldest) = oldest {
+            params["oldest"] = json!(oldest);
+        }
+
+        if let Some(latest) = latest {
+            params["latest"] = json!(latest);
+        }
+
+        let response = self
+            .core
+            .api_call("conversations.history", params, None, false)
+            .await?;
+
+        let messages: Vec<SlackMessage> = response["messages"]
+            .as_array()
+            .unwrap_or(&Vec::new())
+            .iter()
+            .filter_map(|m| serde_json::from_value(m.clone()).ok())
+            .collect();
+
+        let has_more = response["has_more"].as_bool().unwrap_or(false);
+
+        Ok((messages, has_more))
+    }
+
     /// Get thread replies
     pub async fn get_thread_replies(
         &self,
         channel: &str,
         thread_ts: &str,
         limit: usize,
+        oldest: Option<&str>,
     ) -> Result<(Vec<SlackMessage>, bool)> {
-        let params = json!({
+        let mut params = json!({
             "channel": channel,
             "ts": thread_ts,
             "limit": limit,
         });
 
+        // Only replies strictly newer than `oldest` (the parent is always included)
+        if let Some(oldest) = oldest {
+            params["oldest"] = json!(oldest);
+        }
+
         let response = self
             .core
             .api_call("conversations.replies", params, None, false)
@@ -118,6 +225,86 @@ impl SlackMessageClient {
         Ok((messages, has_more))
     }
 
+    /// Get a single message; replies are looked up inside their thread
+    pub async fn get_message(
+        &self,
+        channel: &str,
+        ts: &str,
+        thread_ts: Option<&str>,
+    ) -> Result<Option<SlackMessage>> {
+        let (method, params) = match thread_ts.filter(|thread_ts| *thread_ts != ts) {
+            // The parent is always returned first, so ask for two
+            Some(thread_ts) => (
+                "conversations.replies",
+                json!({
+                    "channel": channel,
+                    "ts": thread_ts,
+                    "oldest": ts,
+                    "inclusive": true,
+                    "limit": 2,
+                }),
+            ),
+            None => (
+                "conversations.history",
+                json!({
+                    "channel": channel,
+                    "latest": ts,
+                    "inclusive": true,
+                    "limit": 1,
+                }),
+            ),
+        };
+
+        let response = self.core.api_call(method, params, None, false).await?;
+
+        Ok(response["messages"]
+            .as_array()
+            .unwrap_or(&Vec::new())
+            .iter()
+            .filter_map(|m| serde_json::from_value::<SlackMessage>(m.clone()).ok())
+            .find(|m| m.ts == ts))
+    }
+
+    /// Get the permalink of a message
+    pub async fn get_permalink(&self, channel: &str, ts: &str) -> Result<String> {
+        let params = json!({
+            "channel": channel,
+            "message_ts": ts,
+        });
+
+        let response = self
+            .core
+            .api_call("chat.getPermalink", params, None, false)
+            .await?;
+
+        response["permalink"]
+            .as_str()
+            .map(|p| p.to_string())
+            .ok_or_else(|| anyhow::anyhow!("Missing permalink in response"))
+    }
+
+    /// Workspace URL for building permalinks locally.
+    ///
+    /// Uses `slack.workspace_url` when configured; otherwise asks Slack for the
+    /// permalink of the given message once and remembers its workspace.
+    pub async fn workspace_url(&self, channel: &str, ts: &str) -> Option<String> {
+        if let Some(url) = &self.core.config.slack.workspace_url {
+            return Some(url.trim_end_matches('/').to_string());
+        }
+
+        self.workspace_url
+            .get_or_try_init(|| async {
+                let permalink = self.get_permalink(channel, ts).await?;
+                Permalink::parse(&permalink)
+                    .map(|link| link.workspace_url)
+                    .map_err(|e| anyhow::anyhow!(e.to_string()))
+            })
+            .await
+            .inspect_err(|e| warn!("Could not determine workspace URL: {}", e))
+            .ok()
+            .cloned()
+    }
+
     /// Search messages
     pub async fn search_messages(
         &self,
@@ -24,6 +24,12 @@ pub struct SlackUser {
     pub is_admin: bool,
     #[serde(default)]
     pub deleted: bool,
+    /// IANA timezone name, e.g. "Asia/Seoul"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tz: Option<String>,
+    /// Offset from UTC in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tz_offset: Option<i32>,
     pub profile: Option<SlackUserProfile>,
 }
 
@@ -66,6 +72,8 @@ pub struct SlackChannel {
     pub topic: Option<ChannelTopic>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub purpose: Option<ChannelPurpose>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub previous_names: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -118,6 +126,29 @@ pub struct SlackMessage {
     pub blocks: Option<Vec<serde_json::Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub attachments: Option<Vec<serde_json::Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<SlackFile>>,
+    /// Sender name on bot and integration messages, which have no `user`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Message URL; only included by search results
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permalink: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackFile {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mimetype: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permalink: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,8 +1,8 @@
 use anyhow::Result;
-use serde_json::json;
+use serde_json::{Value, json};
 use std::sync::Arc;
 
-use super::core::SlackCore;
+use super::core::{SlackCore, TokenKind};
 use crate::slack::SlackUser;
 
 const SLACK_API_LIMIT: u32 = 200;
@@ -29,6 +29,27 @@ impl SlackUserClient {
         Ok(all_users)
     }
 
+    /// Fetch the workspace's user groups as (ID, handle) pairs
+    pub async fn fetch_usergroups(&self) -> Result<Vec<(String, String)>> {
+        let response = self
+            .core
+            .api_call("usergroups.list", json!({}), None, false)
+            .await?;
+
+        let usergroups = response["usergroups"]
+            .as_array()
+            .unwrap_or(&Vec::new())
+            .iter()
+            .filter_map(|group| {
+                let id = group["id"].as_str()?;
+                let handle = group["handle"].as_str().filter(|h| !h.is_empty())?;
+                Some((id.to_string(), handle.to_string()))
+            })
+            .collect();
+
+        Ok(usergroups)
+    }
+
     /// Stream fetch users with callback for immediate processing of each page
     pub async fn fetch_all_users_streaming<F>(&self, mut callback: F) -> Result<usize>
     where
@@ -56,11 +77,10 @@ impl SlackUserClient {
             let mut page_users = Vec::new();
             if let Some(members) = response["members"].as_array() {
                 for member in members {
+                    // Deleted users are kept so old messages still resolve to a name
                     match serde_json::from_value::<SlackUser>(member.clone()) {
                         Ok(user) => {
-                            if !user.deleted {
-                                page_users.push(user);
-                            }
+                            page_users.push(user);
                         }
                         Err(_) => {
                             // Skip malformed user
@@ -101,6 +121,24 @@ impl SlackUserClient {
             .api_call("conversations.open", params, None, false)
             .await?;
 
+        Self::conversation_id(&response)
+    }
+
+    /// Open a direct message conversation between the owner of a token and a user
+    pub async fn open_conversation_as(&self, user_id: &str, kind: TokenKind) -> Result<String> {
+        let params = json!({
+            "users": user_id,
+        });
+
+        let response = self
+            .core
+            .api_call_as("conversations.open", params, kind)
+            .await?;
+
+        Self::conversation_id(&response)
+    }
+
+    fn conversation_id(response: &Value) -> Result<String> {
         let channel_id = response["channel"]["id"]
             .as_str()
             .ok_or_else(|| anyhow::anyhow!("Missing channel ID in response"))?;
@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+use super::{IntoToolResponse, Tool, ToolResponse};
+use crate::audit::parse_since;
+use crate::cache::{AuditOutcome, AuditQuery, SqliteCache};
+use crate::error::{IntoMcpError, McpResult};
+use crate::utils::{parse_params, resolve_channel_name};
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 1000;
+
+pub struct ListAuditLogTool {
+    cache: Arc<SqliteCache>,
+}
+
+impl ListAuditLogTool {
+    pub fn new(cache: Arc<SqliteCache>) -> Self {
+        Self { cache }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ListAuditLogParams {
+    #[serde(default = "default_limit")]
+    limit: usize,
+    #[serde(default)]
+    since: Option<String>,
+    #[serde(default)]
+    tool: Option<String>,
+    #[serde(default)]
+    channel: Option<String>,
+    #[serde(default)]
+    errors_only: bool,
+}
+
+fn default_limit() -> usize {
+    DEFAULT_LIMIT
+}
+
+#[async_trait]
+impl Tool for ListAuditLogTool {
+    fn description(&self) -> &str {
+        "List recent write actions this server made in Slack, newest first"
+    }
+
+    async fn execute(&self, params: Value) -> McpResult<Value> {
+        let params: ListAuditLogParams = parse_params(params)?;
+
+        // '#name' is resolved through the cache; anything else is taken as a channel ID
+        let channel = match params.channel.as_deref() {
+            Some(name) if name.starts_with('#') => {
+                Some(resolve_channel_name(&name[1..], name, &self.cache)?)
+            }
+            other => other.map(str::to_string),
+        };
+
+        let query = AuditQuery {
+            limit: Some(params.limit.clamp(1, MAX_LIMIT)),
+            since: params.since.as_deref().map(parse_since).transpose()?,
+            tool: params.tool,
+            channel,
+            outcome: params.errors_only.then_some(AuditOutcome::Error),
+            oldest_first: false,
+        };
+
+        let records = self
+            .cache
+            .list_audit_log(&query)
+            .mcp_context("Failed to read audit log")?;
+
+        ToolResponse::data(json!({
+            "count": records.len(),
+            "entries": records,
+        }))
+        .into_response()
+    }
+}
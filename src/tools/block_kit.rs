@@ -0,0 +1,416 @@
+use serde_json::Value;
+
+use crate::error::{McpError, McpResult};
+
+/// Slack truncates or rejects message text longer than this
+pub const MAX_TEXT_CHARS: usize = 40_000;
+pub const MAX_BLOCKS: usize = 50;
+const MAX_SECTION_TEXT_CHARS: usize = 3000;
+const MAX_SECTION_FIELDS: usize = 10;
+const MAX_FIELD_TEXT_CHARS: usize = 2000;
+const MAX_HEADER_TEXT_CHARS: usize = 150;
+const MAX_CONTEXT_ELEMENTS: usize = 10;
+const MAX_ACTIONS_ELEMENTS: usize = 25;
+const MAX_BLOCK_ID_CHARS: usize = 255;
+
+/// Target size of each part with `split: true`; one part fits in a single section block
+pub const SPLIT_MAX_CHARS: usize = MAX_SECTION_TEXT_CHARS;
+
+/// Check a message against Slack's size limits before posting, so the caller gets
+/// every violation with its location instead of `msg_too_long` or `invalid_blocks`
+pub fn validate(text: Option<&str>, blocks: Option<&[Value]>) -> McpResult<()> {
+    let mut problems = Vec::new();
+
+    if let Some(text) = text {
+        let len = text.chars().count();
+        if len > MAX_TEXT_CHARS {
+            problems.push(format!(
+                "text is {} characters (max {}); pass split: true to send it as several messages",
+                len, MAX_TEXT_CHARS
+            ));
+        }
+    }
+
+    if let Some(blocks) = blocks {
+        if blocks.len() > MAX_BLOCKS {
+            problems.push(format!("{} blocks (max {})", blocks.len(), MAX_BLOCKS));
+        }
+        for (i, block) in blocks.iter().enumerate() {
+            validate_block(&format!("blocks[{}]", i), block, &mut problems);
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(McpError::InvalidParameter(format!(
+            "Message exceeds Slack limits: {}",
+            problems.join("; ")
+        )))
+    }
+}
+
+fn validate_block(path: &str, block: &Value, problems: &mut Vec<String>) {
+    let Some(block_type) = block.get("type").and_then(|t| t.as_str()) else {
+        problems.push(format!("{} has no type", path));
+        return;
+    };
+
+    let mut check_len = |field: &str, value: Option<&Value>, max: usize| {
+        if let Some(len) = value.and_then(|v| v.as_str()).map(|s| s.chars().count())
+            && len > max
+        {
+            problems.push(format!(
+                "{}.{} is {} characters (max {} in a {} block)",
+                path, field, len, max, block_type
+            ));
+        }
+    };
+
+    check_len("block_id", block.get("block_id"), MAX_BLOCK_ID_CHARS);
+
+    match block_type {
+        "section" => {
+            check_len(
+                "text.text",
+                block.pointer("/text/text"),
+                MAX_SECTION_TEXT_CHARS,
+            );
+            let fields = block.get("fields").and_then(|f| f.as_array());
+            for (i, field) in fields.into_iter().flatten().enumerate() {
+                check_len(
+                    &format!("fields[{}].text", i),
+                    field.get("text"),
+                    MAX_FIELD_TEXT_CHARS,
+                );
+            }
+            if let Some(fields) = fields
+                && fields.len() > MAX_SECTION_FIELDS
+            {
+                problems.push(format!(
+                    "{}.fields has {} items (max {})",
+                    path,
+                    fields.len(),
+                    MAX_SECTION_FIELDS
+                ));
+            }
+            if block.get("text").is_none() && fields.is_none() {
+                problems.push(format!("{} section needs text or fields", path));
+            }
+        }
+        "header" => {
+            check_len(
+                "text.text",
+                block.pointer("/text/text"),
+                MAX_HEADER_TEXT_CHARS,
+            );
+        }
+        "context" | "actions" => {
+            let max = if block_type == "context" {
+                MAX_CONTEXT_ELEMENTS
+            } else {
+                MAX_ACTIONS_ELEMENTS
+            };
+            let count = block
+                .get("elements")
+                .and_then(|e| e.as_array())
+                .map_or(0, Vec::len);
+            if count > max {
+                problems.push(format!(
+                    "{}.elements has {} items (max {} in a {} block)",
+                    path, count, max, block_type
+                ));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Split text into parts of at most `max_chars` characters.
+///
+/// Breaks between paragraphs (blank lines) where possible, keeps fenced code blocks
+/// whole, and re-fences a code block that has to be broken up. Oversized paragraphs
+/// fall back to line breaks, then to the last space before the limit.
+pub fn split_text(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut parts: Vec<String> = Vec::new();
+    let mut current = String::new();
+
+    for unit in split_units(text) {
+        let pieces = if char_len(&unit.text) > max_chars {
+            split_unit(&unit, max_chars)
+        } else {
+            vec![unit.text]
+        };
+        for piece in pieces {
+            let joined = char_len(&current) + 2 + char_len(&piece);
+            if !current.is_empty() && joined > max_chars {
+                parts.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push_str("\n\n");
+            }
+            current.push_str(&piece);
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// A paragraph, or a fenced code block with its fence lines
+struct Unit {
+    text: String,
+    fence: Option<String>,
+}
+
+fn split_units(text: &str) -> Vec<Unit> {
+    let mut units = Vec::new();
+    let mut lines: Vec<&str> = Vec::new();
+    let mut fence: Option<String> = None;
+
+    let flush = |lines: &mut Vec<&str>, fence: Option<String>, units: &mut Vec<Unit>| {
+        if !lines.is_empty() {
+            units.push(Unit {
+                text: lines.join("\n"),
+                fence,
+            });
+            lines.clear();
+        }
+    };
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        match &fence {
+            Some(marker) => {
+                lines.push(line);
+                if trimmed.trim_end() == marker.as_str() {
+                    flush(&mut lines, fence.take(), &mut units);
+                }
+            }
+            None if trimmed.starts_with("```") || trimmed.starts_with("~~~") => {
+                flush(&mut lines, None, &mut units);
+                fence = Some(trimmed[..3].to_string());
+                lines.push(line);
+            }
+            None if line.trim().is_empty() => flush(&mut lines, None, &mut units),
+            None => lines.push(line),
+        }
+    }
+    // An unclosed fence is treated as code to the end of the text
+    flush(&mut lines, fence, &mut units);
+    units
+}
+
+fn split_unit(unit: &Unit, max_chars: usize) -> Vec<String> {
+    let Some(marker) = &unit.fence else {
+        return pack_lines(unit.text.lines(), max_chars);
+    };
+
+    // Re-fence each piece of the code block; the fence lines take part of the budget
+    let mut lines: Vec<&str> = unit.text.lines().collect();
+    let opening = lines.remove(0);
+    if lines.last().is_some_and(|l| l.trim() == marker.as_str()) {
+        lines.pop();
+    }
+    let overhead = char_len(opening) + char_len(marker) + 2;
+    let budget = max_chars.saturating_sub(overhead).max(1);
+    pack_lines(lines.into_iter(), budget)
+        .into_iter()
+        .map(|code| format!("{}\n{}\n{}", opening, code, marker))
+        .collect()
+}
+
+fn pack_lines<'a>(lines: impl Iterator<Item = &'a str>, max_chars: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+
+    for line in lines {
+        for segment in split_long_line(line, max_chars) {
+            let joined = char_len(&current) + 1 + char_len(&segment);
+            if !current.is_empty() && joined > max_chars {
+                pieces.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push('\n');
+            }
+            current.push_str(&segment);
+        }
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
+
+fn split_long_line(line: &str, max_chars: usize) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut rest: Vec<char> = line.chars().collect();
+
+    while rest.len() > max_chars {
+        let cut = rest[..max_chars]
+            .iter()
+            .rposition(|c| c.is_whitespace())
+            .filter(|&i| i > 0)
+            .unwrap_or(max_chars);
+        segments.push(
+            rest[..cut]
+                .iter()
+                .collect::<String>()
+                .trim_end()
+                .to_string(),
+        );
+        rest = rest[cut..].to_vec();
+        while rest.first().is_some_and(|c| c.is_whitespace()) {
+            rest.remove(0);
+        }
+    }
+    segments.push(rest.into_iter().collect());
+    segments
+}
+
+fn char_len(s: &str) -> usize {
+    s.chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    fn problems(text: Option<&str>, blocks: &[Value]) -> String {
+        validate(text, Some(blocks)).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_validate_accepts_normal_message() {
+        let blocks = vec![
+            json!({"type": "header", "text": {"type": "plain_text", "text": "Release"}}),
+            json!({"type": "section", "text": {"type": "mrkdwn", "text": "All *green*"}}),
+            json!({"type": "divider"}),
+        ];
+        assert!(validate(Some("All green"), Some(&blocks)).is_ok());
+        assert!(validate(Some("plain"), None).is_ok());
+    }
+
+    #[test]
+    fn test_validate_text_length() {
+        let text = "a".repeat(MAX_TEXT_CHARS + 1);
+        let err = validate(Some(&text), None).unwrap_err().to_string();
+        assert!(err.contains("text is 40001 characters (max 40000)"));
+        assert!(err.contains("split: true"));
+    }
+
+    #[test]
+    fn test_validate_block_count() {
+        let blocks = vec![json!({"type": "divider"}); 51];
+        assert!(problems(None, &blocks).contains("51 blocks (max 50)"));
+    }
+
+    #[rstest]
+    #[case(
+        json!({"type": "section", "text": {"type": "mrkdwn", "text": "x".repeat(3001)}}),
+        "blocks[0].text.text is 3001 characters (max 3000 in a section block)"
+    )]
+    #[case(
+        json!({"type": "header", "text": {"type": "plain_text", "text": "x".repeat(151)}}),
+        "blocks[0].text.text is 151 characters (max 150 in a header block)"
+    )]
+    #[case(
+        json!({"type": "section", "fields": vec![json!({"type": "mrkdwn", "text": "f"}); 11]}),
+        "blocks[0].fields has 11 items (max 10)"
+    )]
+    #[case(
+        json!({"type": "section", "fields": [{"type": "mrkdwn", "text": "x".repeat(2001)}]}),
+        "blocks[0].fields[0].text is 2001 characters (max 2000 in a section block)"
+    )]
+    #[case(
+        json!({"type": "context", "elements": vec![json!({"type": "mrkdwn", "text": "c"}); 11]}),
+        "blocks[0].elements has 11 items (max 10 in a context block)"
+    )]
+    #[case(json!({"type": "section"}), "blocks[0] section needs text or fields")]
+    #[case(json!({"text": "no type"}), "blocks[0] has no type")]
+    #[case(
+        json!({"type": "divider", "block_id": "b".repeat(256)}),
+        "blocks[0].block_id is 256 characters (max 255 in a divider block)"
+    )]
+    fn test_validate_block_limits(#[case] block: Value, #[case] expected: &str) {
+        let err = problems(None, &[block]);
+        assert!(err.contains(expected), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_reports_every_problem() {
+        let blocks = vec![
+            json!({"type": "divider"}),
+            json!({"type": "section"}),
+            json!({"type": "header", "text": {"type": "plain_text", "text": "x".repeat(200)}}),
+        ];
+        let err = problems(None, &blocks);
+        assert!(err.contains("blocks[1] section"));
+        assert!(err.contains("blocks[2].text.text"));
+    }
+
+    #[test]
+    fn test_split_text_short_text_is_one_part() {
+        assert_eq!(split_text("hello\n\nworld", 100), vec!["hello\n\nworld"]);
+        assert!(split_text("", 100).is_empty());
+    }
+
+    #[test]
+    fn test_split_text_at_paragraphs() {
+        let text = "first paragraph\n\nsecond paragraph\n\nthird paragraph";
+        assert_eq!(
+            split_text(text, 35),
+            vec!["first paragraph\n\nsecond paragraph", "third paragraph"]
+        );
+    }
+
+    #[test]
+    fn test_split_text_keeps_code_fence_whole() {
+        let text = "intro\n\n```\nfn a() {}\n\nfn b() {}\n```\n\noutro";
+        let parts = split_text(text, 30);
+        assert_eq!(
+            parts,
+            vec!["intro", "```\nfn a() {}\n\nfn b() {}\n```", "outro"]
+        );
+    }
+
+    #[test]
+    fn test_split_text_refences_long_code_block() {
+        let code: Vec<String> = (0..6).map(|i| format!("line {}", i)).collect();
+        let text = format!("```rust\n{}\n```", code.join("\n"));
+
+        let parts = split_text(&text, 30);
+
+        assert!(parts.len() > 1);
+        for part in &parts {
+            assert!(part.starts_with("```rust\n"), "{}", part);
+            assert!(part.ends_with("\n```"), "{}", part);
+            assert!(part.chars().count() <= 30);
+        }
+        let lines: Vec<&str> = parts
+            .iter()
+            .flat_map(|p| p.lines().filter(|l| l.starts_with("line")))
+            .collect();
+        assert_eq!(lines, code);
+    }
+
+    #[test]
+    fn test_split_text_breaks_long_lines_at_spaces() {
+        let text = "alpha beta gamma delta epsilon";
+        let parts = split_text(text, 12);
+        assert_eq!(parts, vec!["alpha beta", "gamma delta", "epsilon"]);
+    }
+
+    #[test]
+    fn test_split_text_hard_splits_unbroken_text() {
+        let parts = split_text(&"가".repeat(25), 10);
+        assert_eq!(
+            parts.iter().map(|p| p.chars().count()).collect::<Vec<_>>(),
+            vec![10, 10, 5]
+        );
+    }
+}
@@ -0,0 +1,432 @@
+use serde::Serialize;
+use serde_json::{Value, json};
+
+/// Rough characters-per-token ratio used to turn `max_tokens` into a character budget
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Long text fields are never cut shorter than this
+const MIN_FIELD_CHARS: usize = 200;
+
+/// Names listed in a join/leave summary before "+N more"
+const SUMMARY_NAME_LIMIT: usize = 5;
+
+const JOIN_SUBTYPES: [&str; 2] = ["channel_join", "group_join"];
+const LEAVE_SUBTYPES: [&str; 2] = ["channel_leave", "group_leave"];
+
+/// Text fields shortened when a single message is too long
+const TRUNCATED_FIELDS: [&str; 2] = ["text", "rendered_text"];
+
+/// Output size limit for message-returning tools
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Budget {
+    max_chars: usize,
+}
+
+/// What a budget removed from a response, reported in `ResponseMetadata`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Elided {
+    /// Messages whose text was shortened
+    #[serde(skip_serializing_if = "is_zero")]
+    pub truncated_messages: usize,
+    /// Join/leave messages folded into summaries
+    #[serde(skip_serializing_if = "is_zero")]
+    pub collapsed_messages: usize,
+    /// Messages left out because the budget ran out
+    #[serde(skip_serializing_if = "is_zero")]
+    pub omitted_messages: usize,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+impl Elided {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Messages that fit the budget
+#[derive(Debug)]
+pub struct Budgeted {
+    pub messages: Vec<Value>,
+    pub elided: Elided,
+    /// `ts` of the last message returned, when later messages were omitted
+    pub resume_after: Option<String>,
+}
+
+/// Position to resume a budgeted listing from, encoded in `next_cursor`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Continuation {
+    /// Channel history older than this `ts`
+    Before(String),
+    /// Thread replies newer than this `ts`
+    After(String),
+    /// Search results after skipping this many
+    Offset(usize),
+}
+
+impl Continuation {
+    /// Parse a cursor produced by [`Continuation::to_cursor`]; Slack cursors yield `None`
+    pub fn parse(cursor: &str) -> Option<Self> {
+        let (kind, value) = cursor.split_once(':')?;
+        match kind {
+            "before" => Some(Self::Before(value.to_string())),
+            "after" => Some(Self::After(value.to_string())),
+            "offset" => value.parse().ok().map(Self::Offset),
+            _ => None,
+        }
+    }
+
+    pub fn to_cursor(&self) -> String {
+        match self {
+            Self::Before(ts) => format!("before:{}", ts),
+            Self::After(ts) => format!("after:{}", ts),
+            Self::Offset(n) => format!("offset:{}", n),
+        }
+    }
+}
+
+/// A formatted message, or a summary standing in for several
+struct Entry {
+    value: Value,
+    last_ts: String,
+    count: usize,
+}
+
+impl Budget {
+    /// Build a budget from `max_tokens` and/or `max_chars`; the tighter one wins
+    pub fn new(max_tokens: Option<usize>, max_chars: Option<usize>) -> Option<Self> {
+        let from_tokens = max_tokens.map(|t| t.saturating_mul(CHARS_PER_TOKEN));
+        let max_chars = match (from_tokens, max_chars) {
+            (Some(a), Some(b)) => a.min(b),
+            (a, b) => a.or(b)?,
+        };
+        (max_chars > 0).then_some(Self { max_chars })
+    }
+
+    /// Fit formatted messages into the budget.
+    ///
+    /// Runs of join/leave messages are collapsed into one summary, long text is
+    /// shortened with a marker, and messages past the budget are dropped. At least
+    /// one message is always returned so a continuation makes progress. `reserved`
+    /// accounts for output outside the message list (such as thread info).
+    pub fn apply(&self, messages: Vec<Value>, reserved: usize) -> Budgeted {
+        let mut elided = Elided::default();
+        let entries = collapse_membership(messages, &mut elided);
+        let field_cap = (self.max_chars / 4).max(MIN_FIELD_CHARS);
+
+        let mut used = reserved;
+        let mut kept: Vec<Value> = Vec::new();
+        let mut last_ts: Option<String> = None;
+        let mut entries = entries.into_iter();
+
+        for mut entry in entries.by_ref() {
+            let truncated = truncate_fields(&mut entry.value, field_cap);
+            let size = entry.value.to_string().len();
+
+            if !kept.is_empty() && used + size > self.max_chars {
+                elided.omitted_messages += entry.count;
+                break;
+            }
+
+            used += size;
+            if truncated {
+                elided.truncated_messages += 1;
+            }
+            last_ts = Some(entry.last_ts);
+            kept.push(entry.value);
+        }
+
+        elided.omitted_messages += entries.map(|e| e.count).sum::<usize>();
+        let resume_after = (elided.omitted_messages > 0).then_some(last_ts).flatten();
+
+        Budgeted {
+            messages: kept,
+            elided,
+            resume_after,
+        }
+    }
+}
+
+fn subtype(message: &Value) -> Option<&str> {
+    message["subtype"].as_str()
+}
+
+fn is_membership(message: &Value) -> bool {
+    subtype(message).is_some_and(|s| JOIN_SUBTYPES.contains(&s) || LEAVE_SUBTYPES.contains(&s))
+}
+
+fn collapse_membership(messages: Vec<Value>, elided: &mut Elided) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut run: Vec<Value> = Vec::new();
+
+    let mut flush = |run: &mut Vec<Value>, entries: &mut Vec<Entry>| match run.len() {
+        0 => {}
+        1 => entries.push(single(run.remove(0))),
+        n => {
+            elided.collapsed_messages += n;
+            entries.push(Entry {
+                last_ts: run[n - 1]["ts"].as_str().unwrap_or_default().to_string(),
+                value: membership_summary(run),
+                count: n,
+            });
+            run.clear();
+        }
+    };
+
+    for message in messages {
+        if is_membership(&message) {
+            run.push(message);
+        } else {
+            flush(&mut run, &mut entries);
+            entries.push(single(message));
+        }
+    }
+    flush(&mut run, &mut entries);
+
+    entries
+}
+
+fn single(value: Value) -> Entry {
+    Entry {
+        last_ts: value["ts"].as_str().unwrap_or_default().to_string(),
+        value,
+        count: 1,
+    }
+}
+
+fn membership_summary(run: &[Value]) -> Value {
+    let names = |subtypes: &[&str]| -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for message in run
+            .iter()
+            .filter(|m| subtype(m).is_some_and(|s| subtypes.contains(&s)))
+        {
+            let name = message["user_name"]
+                .as_str()
+                .or_else(|| message["user_id"].as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    };
+
+    let describe = |verb: &str, names: Vec<String>| {
+        let extra = names.len().saturating_sub(SUMMARY_NAME_LIMIT);
+        let mut listed = names
+            .into_iter()
+            .take(SUMMARY_NAME_LIMIT)
+            .collect::<Vec<_>>()
+            .join(", ");
+        if extra > 0 {
+            listed.push_str(&format!(" +{} more", extra));
+        }
+        format!("{}: {}", verb, listed)
+    };
+
+    let mut parts = Vec::new();
+    let joined = names(&JOIN_SUBTYPES);
+    if !joined.is_empty() {
+        parts.push(describe("joined", joined));
+    }
+    let left = names(&LEAVE_SUBTYPES);
+    if !left.is_empty() {
+        parts.push(describe("left", left));
+    }
+
+    let mut summary = json!({
+        "ts": run[0]["ts"],
+        "subtype": "membership_summary",
+        "text": parts.join("; "),
+        "collapsed_count": run.len(),
+    });
+    if let Some(datetime) = run[0].get("datetime") {
+        summary["datetime"] = datetime.clone();
+    }
+    summary
+}
+
+/// Shorten long text fields in place, returning whether anything was cut
+fn truncate_fields(message: &mut Value, cap: usize) -> bool {
+    let mut truncated = false;
+    for field in TRUNCATED_FIELDS {
+        if let Some(text) = message[field].as_str()
+            && let Some(short) = truncate_text(text, cap)
+        {
+            message[field] = json!(short);
+            truncated = true;
+        }
+    }
+    truncated
+}
+
+fn truncate_text(text: &str, cap: usize) -> Option<String> {
+    let total = text.chars().count();
+    if total <= cap {
+        return None;
+    }
+
+    let cut: String = text.chars().take(cap).collect();
+    // Prefer ending on a word boundary when one is close by
+    let cut = match cut.rfind(char::is_whitespace) {
+        Some(pos) if cut[pos..].chars().count() <= cap / 5 => cut[..pos].to_string(),
+        _ => cut,
+    };
+    let removed = total - cut.chars().count();
+    Some(format!("{}… [truncated {} chars]", cut.trim_end(), removed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn message(ts: &str, text: &str) -> Value {
+        json!({"ts": ts, "text": text})
+    }
+
+    fn membership(ts: &str, subtype: &str, name: &str) -> Value {
+        json!({"ts": ts, "text": "", "subtype": subtype, "user_name": name})
+    }
+
+    #[rstest]
+    #[case(None, None, None)]
+    #[case(Some(100), None, Some(400))]
+    #[case(None, Some(500), Some(500))]
+    #[case(Some(100), Some(300), Some(300))]
+    #[case(Some(0), None, None)]
+    fn test_budget_new(
+        #[case] max_tokens: Option<usize>,
+        #[case] max_chars: Option<usize>,
+        #[case] expected: Option<usize>,
+    ) {
+        assert_eq!(
+            Budget::new(max_tokens, max_chars),
+            expected.map(|max_chars| Budget { max_chars })
+        );
+    }
+
+    #[rstest]
+    #[case("before:1700000000.000100", Some(Continuation::Before("1700000000.000100".to_string())))]
+    #[case("after:1700000000.000100", Some(Continuation::After("1700000000.000100".to_string())))]
+    #[case("offset:20", Some(Continuation::Offset(20)))]
+    #[case("offset:x", None)]
+    #[case("dXNlcjpVMDYxTkZUVDI=", None)]
+    fn test_continuation_parse(#[case] cursor: &str, #[case] expected: Option<Continuation>) {
+        let parsed = Continuation::parse(cursor);
+        assert_eq!(parsed, expected);
+        if let Some(continuation) = parsed {
+            assert_eq!(continuation.to_cursor(), cursor);
+        }
+    }
+
+    #[test]
+    fn test_apply_within_budget_is_unchanged() {
+        let messages = vec![message("3", "c"), message("2", "b"), message("1", "a")];
+        let result = Budget::new(None, Some(10_000))
+            .unwrap()
+            .apply(messages.clone(), 0);
+
+        assert_eq!(result.messages, messages);
+        assert!(result.elided.is_empty());
+        assert!(result.resume_after.is_none());
+    }
+
+    #[test]
+    fn test_apply_omits_messages_past_budget() {
+        let messages: Vec<Value> = (0..10)
+            .map(|i| message(&i.to_string(), &"x".repeat(50)))
+            .collect();
+        let result = Budget::new(None, Some(200)).unwrap().apply(messages, 0);
+
+        assert_eq!(result.messages.len(), 2);
+        assert_eq!(result.elided.omitted_messages, 8);
+        assert_eq!(result.resume_after.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn test_apply_always_returns_one_message() {
+        let messages = vec![message("1", "short"), message("2", "short")];
+        let result = Budget::new(None, Some(10)).unwrap().apply(messages, 100);
+
+        assert_eq!(result.messages.len(), 1);
+        assert_eq!(result.elided.omitted_messages, 1);
+        assert_eq!(result.resume_after.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn test_apply_truncates_long_text() {
+        let long = format!("{} tail", "word ".repeat(200));
+        let mut msg = message("1", &long);
+        msg["rendered_text"] = json!(long);
+        let result = Budget::new(None, Some(2_000)).unwrap().apply(vec![msg], 0);
+
+        let text = result.messages[0]["text"].as_str().unwrap();
+        assert!(text.len() < long.len());
+        assert!(text.ends_with(" chars]"));
+        assert!(text.starts_with("word word"));
+        assert!(text.contains("word… [truncated"));
+        assert_eq!(result.messages[0]["rendered_text"], text);
+        assert_eq!(result.elided.truncated_messages, 1);
+    }
+
+    #[test]
+    fn test_apply_collapses_membership_runs() {
+        let messages = vec![
+            message("6", "hello"),
+            membership("5", "channel_join", "alice"),
+            membership("4", "channel_join", "bob"),
+            membership("3", "channel_leave", "carol"),
+            message("2", "between"),
+            membership("1", "channel_join", "dave"),
+        ];
+        let result = Budget::new(None, Some(10_000)).unwrap().apply(messages, 0);
+
+        assert_eq!(result.messages.len(), 4);
+        let summary = &result.messages[1];
+        assert_eq!(summary["subtype"], "membership_summary");
+        assert_eq!(summary["text"], "joined: alice, bob; left: carol");
+        assert_eq!(summary["collapsed_count"], 3);
+        assert_eq!(summary["ts"], "5");
+        // A lone join is kept as-is
+        assert_eq!(result.messages[3]["subtype"], "channel_join");
+        assert_eq!(result.elided.collapsed_messages, 3);
+    }
+
+    #[test]
+    fn test_membership_summary_limits_names() {
+        let messages: Vec<Value> = (0..8)
+            .map(|i| membership(&i.to_string(), "channel_join", &format!("user{}", i)))
+            .collect();
+        let result = Budget::new(None, Some(10_000)).unwrap().apply(messages, 0);
+
+        assert_eq!(
+            result.messages[0]["text"],
+            "joined: user0, user1, user2, user3, user4 +3 more"
+        );
+    }
+
+    #[test]
+    fn test_omitted_count_includes_collapsed_messages() {
+        let mut messages = vec![message("9", &"x".repeat(150))];
+        messages.extend((0..4).map(|i| membership(&i.to_string(), "channel_join", "a")));
+        let result = Budget::new(None, Some(100)).unwrap().apply(messages, 0);
+
+        assert_eq!(result.messages.len(), 1);
+        assert_eq!(result.elided.omitted_messages, 4);
+        assert_eq!(result.resume_after.as_deref(), Some("9"));
+    }
+
+    #[test]
+    fn test_elided_serializes_non_zero_fields() {
+        let elided = Elided {
+            omitted_messages: 3,
+            ..Default::default()
+        };
+        assert_eq!(json!(elided), json!({"omitted_messages": 3}));
+    }
+}
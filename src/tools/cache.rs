@@ -2,10 +2,12 @@ use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::{Value, json};
 use std::sync::Arc;
+use tracing::warn;
 
 use super::{IntoToolResponse, Tool, ToolResponse};
 use crate::cache::{CacheRefreshType, SqliteCache};
-use crate::error::{McpError, McpResult};
+use crate::config::CacheConfig;
+use crate::error::{IntoMcpError, McpError, McpResult};
 use crate::slack::SlackClient;
 use crate::utils::parse_params;
 
@@ -23,6 +25,20 @@ impl RefreshCacheTool {
     }
 }
 
+pub struct CacheStatusTool {
+    cache: Arc<SqliteCache>,
+    cache_config: CacheConfig,
+}
+
+impl CacheStatusTool {
+    pub fn new(cache: Arc<SqliteCache>, cache_config: CacheConfig) -> Self {
+        Self {
+            cache,
+            cache_config,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct RefreshCacheParams {
     #[serde(default = "default_all")]
@@ -82,6 +98,16 @@ impl Tool for RefreshCacheTool {
                             errors.push(error_msg);
                         }
                     }
+
+                    // User group handles are optional (they need `usergroups:read`)
+                    match self.slack_client.users.fetch_usergroups().await {
+                        Ok(usergroups) => {
+                            if let Err(e) = self.cache.save_usergroups(&usergroups) {
+                                warn!("Failed to save user groups: {}", e);
+                            }
+                        }
+                        Err(e) => warn!("Failed to fetch user groups: {}", e),
+                    }
                 }
                 _ => {}
             }
@@ -122,3 +148,22 @@ impl Tool for RefreshCacheTool {
         .into_response()?)
     }
 }
+
+#[async_trait]
+impl Tool for CacheStatusTool {
+    fn description(&self) -> &str {
+        "Show cache contents, sync times, staleness, storage size and locks"
+    }
+
+    async fn execute(&self, _params: Value) -> McpResult<Value> {
+        let status = self
+            .cache
+            .get_status(
+                self.cache_config.ttl_users_hours,
+                self.cache_config.ttl_channels_hours,
+            )
+            .mcp_context("Failed to read cache status")?;
+
+        Ok(ToolResponse::data(serde_json::to_value(status)?).into_response()?)
+    }
+}
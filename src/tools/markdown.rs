@@ -0,0 +1,901 @@
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use super::mrkdwn::escape;
+
+/// How outgoing message text is written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageFormat {
+    /// Slack mrkdwn, posted as-is
+    #[default]
+    Mrkdwn,
+    /// GitHub-flavored Markdown, converted to mrkdwn and Block Kit
+    Markdown,
+}
+
+/// Resolves an outgoing `@name` (`'@'`) or `#channel` (`'#'`) reference to a Slack ID
+pub type MentionResolver<'a> = dyn FnMut(char, &str) -> Option<String> + 'a;
+
+/// Slack limits header block text to 150 characters
+const HEADER_MAX_CHARS: usize = 150;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mark {
+    Bold,
+    Italic,
+    Strike,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Inline {
+    Text(String),
+    Code(String),
+    Styled(Mark, Vec<Inline>),
+    Link { url: String, label: String },
+    User { id: String, label: String },
+    Channel { id: String, label: String },
+    Broadcast(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ListItem {
+    indent: usize,
+    checked: Option<bool>,
+    content: Vec<Inline>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Block {
+    Paragraph(Vec<Vec<Inline>>),
+    Heading(Vec<Inline>),
+    List { ordered: bool, items: Vec<ListItem> },
+    Code(String),
+    Quote(Vec<Vec<Inline>>),
+    Rule,
+    Table(Vec<Vec<String>>),
+}
+
+/// Convert Markdown to Slack message content.
+///
+/// Returns mrkdwn text, plus Block Kit blocks when the Markdown has structure that
+/// mrkdwn can't express well (headings, lists, code blocks, tables or rules). The
+/// text then serves as the notification fallback.
+pub fn to_slack(markdown: &str, resolve: &mut MentionResolver) -> (String, Option<Vec<Value>>) {
+    let blocks = parse_blocks(markdown, resolve);
+    let text = blocks
+        .iter()
+        .map(block_to_mrkdwn)
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    let structured = blocks
+        .iter()
+        .any(|b| !matches!(b, Block::Paragraph(_) | Block::Quote(_)));
+
+    let block_kit = structured.then(|| blocks.iter().map(block_to_block_kit).collect());
+    (text, block_kit)
+}
+
+// Block-level parsing
+
+fn parse_blocks(markdown: &str, resolve: &mut MentionResolver) -> Vec<Block> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<Vec<Inline>> = Vec::new();
+    let mut i = 0;
+
+    let flush = |paragraph: &mut Vec<Vec<Inline>>, blocks: &mut Vec<Block>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(std::mem::take(paragraph)));
+        }
+    };
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+
+        if trimmed.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+            i += 1;
+            continue;
+        }
+
+        // Fenced code block (the language tag is dropped, Slack has no highlighting)
+        if let Some(fence) = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f)) {
+            flush(&mut paragraph, &mut blocks);
+            let mut code = Vec::new();
+            i += 1;
+            while i < lines.len() && !lines[i].trim_start().starts_with(fence) {
+                code.push(lines[i]);
+                i += 1;
+            }
+            blocks.push(Block::Code(code.join("\n")));
+            i += 1;
+            continue;
+        }
+
+        if let Some(text) = heading_text(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Heading(parse_inline(text, resolve)));
+            i += 1;
+            continue;
+        }
+
+        if is_rule(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Rule);
+            i += 1;
+            continue;
+        }
+
+        if trimmed.starts_with('|') && lines.get(i + 1).is_some_and(|l| is_table_separator(l)) {
+            flush(&mut paragraph, &mut blocks);
+            let mut rows = vec![table_cells(trimmed)];
+            i += 2;
+            while i < lines.len() && lines[i].trim_start().starts_with('|') {
+                rows.push(table_cells(lines[i].trim_start()));
+                i += 1;
+            }
+            blocks.push(Block::Table(rows));
+            continue;
+        }
+
+        if let Some(ListLine { ordered, .. }) = list_marker(line) {
+            flush(&mut paragraph, &mut blocks);
+            let mut items = Vec::new();
+            while i < lines.len()
+                && let Some(item) = list_marker(lines[i])
+                && item.ordered == ordered
+            {
+                items.push(ListItem {
+                    indent: item.indent,
+                    checked: item.checked,
+                    content: parse_inline(item.content, resolve),
+                });
+                i += 1;
+            }
+            blocks.push(Block::List { ordered, items });
+            continue;
+        }
+
+        if trimmed.starts_with('>') {
+            flush(&mut paragraph, &mut blocks);
+            let mut quote = Vec::new();
+            while i < lines.len()
+                && let Some(rest) = lines[i].trim_start().strip_prefix('>')
+            {
+                quote.push(parse_inline(
+                    rest.strip_prefix(' ').unwrap_or(rest),
+                    resolve,
+                ));
+                i += 1;
+            }
+            blocks.push(Block::Quote(quote));
+            continue;
+        }
+
+        paragraph.push(parse_inline(line.trim_end(), resolve));
+        i += 1;
+    }
+
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+fn heading_text(line: &str) -> Option<&str> {
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&hashes) {
+        return None;
+    }
+    let rest = &line[hashes..];
+    if rest.is_empty() {
+        Some(rest)
+    } else {
+        rest.strip_prefix(' ')
+            .map(|text| text.trim().trim_end_matches('#').trim_end())
+    }
+}
+
+fn is_rule(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|m| compact.chars().all(|c| c == *m))
+}
+
+fn is_table_separator(line: &str) -> bool {
+    let line = line.trim();
+    line.contains('-')
+        && line.contains('|')
+        && line
+            .chars()
+            .all(|c| matches!(c, '|' | '-' | ':' | ' ' | '\t'))
+}
+
+fn table_cells(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    line.split('|').map(|c| c.trim().to_string()).collect()
+}
+
+/// A list item line before its content is parsed
+struct ListLine<'a> {
+    ordered: bool,
+    indent: usize,
+    checked: Option<bool>,
+    content: &'a str,
+}
+
+fn list_marker(line: &str) -> Option<ListLine<'_>> {
+    let spaces = line.len() - line.trim_start().len();
+    let rest = line.trim_start();
+
+    let (ordered, content) =
+        if let Some(content) = ["- ", "* ", "+ "].iter().find_map(|m| rest.strip_prefix(m)) {
+            (false, content)
+        } else {
+            let digits = rest.chars().take_while(char::is_ascii_digit).count();
+            let after = &rest[digits..];
+            let content = (digits > 0)
+                .then(|| {
+                    after
+                        .strip_prefix(". ")
+                        .or_else(|| after.strip_prefix(") "))
+                })
+                .flatten()?;
+            (true, content)
+        };
+
+    let (checked, content) = if let Some(c) = content.strip_prefix("[ ] ") {
+        (Some(false), c)
+    } else if let Some(c) = content
+        .strip_prefix("[x] ")
+        .or_else(|| content.strip_prefix("[X] "))
+    {
+        (Some(true), c)
+    } else {
+        (None, content)
+    };
+
+    Some(ListLine {
+        ordered,
+        indent: spaces / 2,
+        checked,
+        content,
+    })
+}
+
+// Inline parsing
+
+fn parse_inline(text: &str, resolve: &mut MentionResolver) -> Vec<Inline> {
+    let chars: Vec<char> = text.chars().collect();
+    parse_span(&chars, resolve)
+}
+
+fn parse_span(chars: &[char], resolve: &mut MentionResolver) -> Vec<Inline> {
+    let mut out = Vec::new();
+    let mut buf = String::new();
+    let mut i = 0;
+
+    let flush = |buf: &mut String, out: &mut Vec<Inline>| {
+        if !buf.is_empty() {
+            out.push(Inline::Text(std::mem::take(buf)));
+        }
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+
+        // Backslash escapes
+        if c == '\\' && chars.get(i + 1).is_some_and(|n| n.is_ascii_punctuation()) {
+            buf.push(chars[i + 1]);
+            i += 2;
+            continue;
+        }
+
+        // Code spans
+        if c == '`' {
+            let run = run_length(chars, i, '`');
+            if let Some(close) = find_run(chars, i + run, '`', run) {
+                flush(&mut buf, &mut out);
+                let code: String = chars[i + run..close].iter().collect();
+                out.push(Inline::Code(code));
+                i = close + run;
+                continue;
+            }
+            buf.extend(&chars[i..i + run]);
+            i += run;
+            continue;
+        }
+
+        // Links and images: [label](url) / ![alt](url)
+        let link_start = if c == '!' && chars.get(i + 1) == Some(&'[') {
+            Some(i + 1)
+        } else if c == '[' {
+            Some(i)
+        } else {
+            None
+        };
+        if let Some(start) = link_start
+            && let Some((label, url, end)) = parse_link(chars, start)
+        {
+            flush(&mut buf, &mut out);
+            out.push(Inline::Link { url, label });
+            i = end;
+            continue;
+        }
+
+        // Autolinks: <https://...>
+        if c == '<'
+            && let Some(len) = chars[i + 1..].iter().position(|c| *c == '>')
+        {
+            let url: String = chars[i + 1..i + 1 + len].iter().collect();
+            if url.starts_with("http://")
+                || url.starts_with("https://")
+                || url.starts_with("mailto:")
+            {
+                flush(&mut buf, &mut out);
+                out.push(Inline::Link {
+                    label: url.trim_start_matches("mailto:").to_string(),
+                    url,
+                });
+                i += len + 2;
+                continue;
+            }
+        }
+
+        // Emphasis: *, **, ***, _, __, ___ and ~~
+        if matches!(c, '*' | '_' | '~') && !prev.is_some_and(|p| c == '_' && p.is_alphanumeric()) {
+            let run = run_length(chars, i, c).min(3);
+            let valid_run = c != '~' || run == 2;
+            let opens = chars.get(i + run).is_some_and(|n| !n.is_whitespace());
+            if valid_run
+                && opens
+                && let Some(close) = find_closing(chars, i + run, c, run)
+            {
+                flush(&mut buf, &mut out);
+                let inner = parse_span(&chars[i + run..close], resolve);
+                let styled = match (c, run) {
+                    ('~', _) => Inline::Styled(Mark::Strike, inner),
+                    (_, 1) => Inline::Styled(Mark::Italic, inner),
+                    (_, 2) => Inline::Styled(Mark::Bold, inner),
+                    _ => Inline::Styled(Mark::Bold, vec![Inline::Styled(Mark::Italic, inner)]),
+                };
+                out.push(styled);
+                i = close + run;
+                continue;
+            }
+            buf.extend(&chars[i..i + run.max(1)]);
+            i += run.max(1);
+            continue;
+        }
+
+        // Mentions: @name, @here, #channel
+        if matches!(c, '@' | '#') && !prev.is_some_and(|p| p.is_alphanumeric() || p == '&') {
+            let len = chars[i + 1..]
+                .iter()
+                .take_while(|ch| ch.is_alphanumeric() || matches!(ch, '.' | '_' | '-'))
+                .count();
+            // Trailing punctuation ends a sentence rather than the name
+            let len = len
+                - chars[i + 1..i + 1 + len]
+                    .iter()
+                    .rev()
+                    .take_while(|ch| matches!(ch, '.' | '-' | '_'))
+                    .count();
+            if len > 0 {
+                let name: String = chars[i + 1..i + 1 + len].iter().collect();
+                let mention = match (c, name.as_str()) {
+                    ('@', "here" | "channel" | "everyone") => Some(Inline::Broadcast(name.clone())),
+                    ('@', _) => resolve('@', &name).map(|id| Inline::User {
+                        id,
+                        label: format!("@{}", name),
+                    }),
+                    _ => resolve('#', &name).map(|id| Inline::Channel {
+                        id,
+                        label: format!("#{}", name),
+                    }),
+                };
+                if let Some(mention) = mention {
+                    flush(&mut buf, &mut out);
+                    out.push(mention);
+                    i += len + 1;
+                    continue;
+                }
+            }
+        }
+
+        buf.push(c);
+        i += 1;
+    }
+
+    flush(&mut buf, &mut out);
+    out
+}
+
+fn run_length(chars: &[char], start: usize, c: char) -> usize {
+    chars[start..].iter().take_while(|ch| **ch == c).count()
+}
+
+/// Find the next run of exactly `len` `c` characters at or after `from`
+fn find_run(chars: &[char], from: usize, c: char, len: usize) -> Option<usize> {
+    let mut j = from;
+    while j < chars.len() {
+        if chars[j] == c {
+            let run = run_length(chars, j, c);
+            if run == len {
+                return Some(j);
+            }
+            j += run;
+        } else {
+            j += 1;
+        }
+    }
+    None
+}
+
+/// Find a closing emphasis run that follows non-whitespace (and ends a word for `_`)
+fn find_closing(chars: &[char], from: usize, c: char, len: usize) -> Option<usize> {
+    let mut j = from;
+    while let Some(close) = find_run(chars, j, c, len) {
+        let after = chars.get(close + len);
+        let closes = close > from
+            && !chars[close - 1].is_whitespace()
+            && !(c == '_' && after.is_some_and(|a| a.is_alphanumeric()));
+        if closes {
+            return Some(close);
+        }
+        j = close + len;
+    }
+    None
+}
+
+fn parse_link(chars: &[char], start: usize) -> Option<(String, String, usize)> {
+    let mut depth = 0;
+    let mut label_end = None;
+    for (j, ch) in chars.iter().enumerate().skip(start) {
+        match ch {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    label_end = Some(j);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let label_end = label_end?;
+    if chars.get(label_end + 1) != Some(&'(') {
+        return None;
+    }
+    let url_len = chars[label_end + 2..].iter().position(|c| *c == ')')?;
+    let url: String = chars[label_end + 2..label_end + 2 + url_len]
+        .iter()
+        .collect::<String>()
+        .trim()
+        .to_string();
+    if url.is_empty() || url.contains(char::is_whitespace) {
+        return None;
+    }
+    let label: String = chars[start + 1..label_end].iter().collect();
+    Some((label, url, label_end + 3 + url_len))
+}
+
+// Rendering
+
+fn inline_to_mrkdwn(inlines: &[Inline]) -> String {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(text) => escape(text),
+            Inline::Code(code) => format!("`{}`", escape(code)),
+            Inline::Styled(mark, inner) => {
+                let marker = match mark {
+                    Mark::Bold => "*",
+                    Mark::Italic => "_",
+                    Mark::Strike => "~",
+                };
+                format!("{}{}{}", marker, inline_to_mrkdwn(inner), marker)
+            }
+            Inline::Link { url, label } if label.is_empty() || label == url => {
+                format!("<{}>", url)
+            }
+            Inline::Link { url, label } => format!("<{}|{}>", url, escape(label)),
+            Inline::User { id, .. } => format!("<@{}>", id),
+            Inline::Channel { id, .. } => format!("<#{}>", id),
+            Inline::Broadcast(range) => format!("<!{}>", range),
+        })
+        .collect()
+}
+
+fn inline_to_plain(inlines: &[Inline]) -> String {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(text) | Inline::Code(text) => text.clone(),
+            Inline::Styled(_, inner) => inline_to_plain(inner),
+            Inline::Link { url, label } if label.is_empty() => url.clone(),
+            Inline::Link { label, .. } => label.clone(),
+            Inline::User { label, .. } | Inline::Channel { label, .. } => label.clone(),
+            Inline::Broadcast(range) => format!("@{}", range),
+        })
+        .collect()
+}
+
+fn block_to_mrkdwn(block: &Block) -> String {
+    match block {
+        Block::Paragraph(lines) => lines
+            .iter()
+            .map(|l| inline_to_mrkdwn(l))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Block::Heading(inline) => format!("*{}*", inline_to_mrkdwn(inline)),
+        Block::List { ordered, items } => {
+            let mut numbers = [0usize; 16];
+            items
+                .iter()
+                .map(|item| {
+                    let level = item.indent.min(numbers.len() - 1);
+                    numbers[level] += 1;
+                    numbers[level + 1..].iter_mut().for_each(|n| *n = 0);
+                    format!(
+                        "{}{} {}",
+                        "    ".repeat(level),
+                        list_bullet(*ordered, item, numbers[level]),
+                        inline_to_mrkdwn(&item.content)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        Block::Code(code) => format!("```\n{}\n```", escape(code)),
+        Block::Quote(lines) => lines
+            .iter()
+            .map(|l| format!("&gt; {}", inline_to_mrkdwn(l)))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Block::Rule => "──────────".to_string(),
+        Block::Table(rows) => format!("```\n{}\n```", escape(&table_to_text(rows))),
+    }
+}
+
+fn list_bullet(ordered: bool, item: &ListItem, number: usize) -> String {
+    match (item.checked, ordered) {
+        (Some(true), _) => "☑".to_string(),
+        (Some(false), _) => "☐".to_string(),
+        (None, true) => format!("{}.", number),
+        (None, false) => "•".to_string(),
+    }
+}
+
+/// Lay out a table as aligned monospace columns
+fn table_to_text(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|c| {
+            rows.iter()
+                .filter_map(|r| r.get(c))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let format_row = |row: &Vec<String>| {
+        (0..columns)
+            .map(|c| {
+                let cell = row.get(c).map(String::as_str).unwrap_or("");
+                format!("{:width$}", cell, width = widths[c])
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = Vec::new();
+    if let Some((header, body)) = rows.split_first() {
+        lines.push(format_row(header));
+        lines.push(
+            widths
+                .iter()
+                .map(|w| "-".repeat(*w))
+                .collect::<Vec<_>>()
+                .join("-+-"),
+        );
+        lines.extend(body.iter().map(format_row));
+    }
+    lines.join("\n")
+}
+
+fn block_to_block_kit(block: &Block) -> Value {
+    match block {
+        Block::Heading(inline) => {
+            let text: String = inline_to_plain(inline)
+                .chars()
+                .take(HEADER_MAX_CHARS)
+                .collect();
+            json!({
+                "type": "header",
+                "text": {"type": "plain_text", "text": text, "emoji": true},
+            })
+        }
+        Block::Rule => json!({"type": "divider"}),
+        Block::Code(code) => preformatted(code),
+        Block::Table(rows) => preformatted(&table_to_text(rows)),
+        Block::List { ordered, items } => {
+            // Consecutive items at the same depth share one rich_text_list element
+            let mut lists: Vec<Value> = Vec::new();
+            let mut current: Option<(usize, Vec<Value>)> = None;
+            let style = if *ordered { "ordered" } else { "bullet" };
+
+            for item in items {
+                let mut elements = Vec::new();
+                if let Some(checked) = item.checked {
+                    elements.push(json!({
+                        "type": "text",
+                        "text": if checked { "☑ " } else { "☐ " },
+                    }));
+                }
+                elements.extend(rich_text_elements(&item.content, &[]));
+                let section = json!({"type": "rich_text_section", "elements": elements});
+
+                match &mut current {
+                    Some((indent, sections)) if *indent == item.indent => sections.push(section),
+                    _ => {
+                        if let Some((indent, sections)) = current.take() {
+                            lists.push(rich_text_list(style, indent, sections));
+                        }
+                        current = Some((item.indent, vec![section]));
+                    }
+                }
+            }
+            if let Some((indent, sections)) = current {
+                lists.push(rich_text_list(style, indent, sections));
+            }
+
+            json!({"type": "rich_text", "elements": lists})
+        }
+        Block::Paragraph(_) | Block::Quote(_) => json!({
+            "type": "section",
+            "text": {"type": "mrkdwn", "text": block_to_mrkdwn(block)},
+        }),
+    }
+}
+
+fn preformatted(text: &str) -> Value {
+    json!({
+        "type": "rich_text",
+        "elements": [{
+            "type": "rich_text_preformatted",
+            "elements": [{"type": "text", "text": text}],
+        }],
+    })
+}
+
+fn rich_text_list(style: &str, indent: usize, sections: Vec<Value>) -> Value {
+    let mut list = json!({
+        "type": "rich_text_list",
+        "style": style,
+        "elements": sections,
+    });
+    if indent > 0 {
+        list["indent"] = json!(indent);
+    }
+    list
+}
+
+fn rich_text_style(marks: &[Mark], code: bool) -> Option<Value> {
+    let mut style = serde_json::Map::new();
+    for mark in marks {
+        let key = match mark {
+            Mark::Bold => "bold",
+            Mark::Italic => "italic",
+            Mark::Strike => "strike",
+        };
+        style.insert(key.to_string(), json!(true));
+    }
+    if code {
+        style.insert("code".to_string(), json!(true));
+    }
+    (!style.is_empty()).then_some(Value::Object(style))
+}
+
+fn rich_text_elements(inlines: &[Inline], marks: &[Mark]) -> Vec<Value> {
+    let mut elements = Vec::new();
+    for inline in inlines {
+        let mut element = match inline {
+            Inline::Text(text) => json!({"type": "text", "text": text}),
+            Inline::Code(code) => json!({"type": "text", "text": code}),
+            Inline::Styled(mark, inner) => {
+                let mut nested = marks.to_vec();
+                nested.push(*mark);
+                elements.extend(rich_text_elements(inner, &nested));
+                continue;
+            }
+            Inline::Link { url, label } if label.is_empty() => json!({"type": "link", "url": url}),
+            Inline::Link { url, label } => json!({"type": "link", "url": url, "text": label}),
+            Inline::User { id, .. } => json!({"type": "user", "user_id": id}),
+            Inline::Channel { id, .. } => json!({"type": "channel", "channel_id": id}),
+            Inline::Broadcast(range) => json!({"type": "broadcast", "range": range}),
+        };
+        if let Some(style) = rich_text_style(marks, matches!(inline, Inline::Code(_))) {
+            element["style"] = style;
+        }
+        elements.push(element);
+    }
+    elements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::mrkdwn::{self, TextFormat};
+    use rstest::rstest;
+
+    fn resolve(sigil: char, name: &str) -> Option<String> {
+        match (sigil, name) {
+            ('@', "alice") => Some("U0123ABC".to_string()),
+            ('#', "dev-team") => Some("C0456".to_string()),
+            _ => None,
+        }
+    }
+
+    fn to_mrkdwn(markdown: &str) -> String {
+        to_slack(markdown, &mut resolve).0
+    }
+
+    fn to_blocks(markdown: &str) -> Vec<Value> {
+        to_slack(markdown, &mut resolve).1.expect("blocks")
+    }
+
+    #[rstest]
+    #[case("**bold**", "*bold*")]
+    #[case("__bold__", "*bold*")]
+    #[case("*italic*", "_italic_")]
+    #[case("_italic_", "_italic_")]
+    #[case("***both***", "*_both_*")]
+    #[case("~~strike~~", "~strike~")]
+    #[case("**bold _italic_ bold**", "*bold _italic_ bold*")]
+    #[case("[docs](https://x.com/a_b)", "<https://x.com/a_b|docs>")]
+    #[case("![logo](https://x.com/l.png)", "<https://x.com/l.png|logo>")]
+    #[case("<https://x.com>", "<https://x.com>")]
+    #[case("`a < b && *c*`", "`a &lt; b &amp;&amp; *c*`")]
+    #[case("1 < 2 & 3 > 2", "1 &lt; 2 &amp; 3 &gt; 2")]
+    #[case("snake_case_name", "snake_case_name")]
+    #[case("2 * 3 * 4", "2 * 3 * 4")]
+    #[case(r"\*not italic\*", "*not italic*")]
+    #[case("**unclosed", "**unclosed")]
+    fn test_inline_to_mrkdwn(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(to_mrkdwn(input), expected);
+    }
+
+    #[rstest]
+    #[case("hi @alice!", "hi <@U0123ABC>!")]
+    #[case("see #dev-team.", "see <#C0456>.")]
+    #[case("@here deploy", "<!here> deploy")]
+    #[case("@unknown and #nowhere", "@unknown and #nowhere")]
+    #[case("mail alice@example.com", "mail alice@example.com")]
+    #[case("issue #42", "issue #42")]
+    #[case("**@alice**", "*<@U0123ABC>*")]
+    fn test_mentions_to_mrkdwn(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(to_mrkdwn(input), expected);
+    }
+
+    #[test]
+    fn test_paragraphs_and_quotes_stay_text_only() {
+        let (text, blocks) = to_slack("line one\nline two\n\n> quoted **bold**", &mut resolve);
+        assert_eq!(text, "line one\nline two\n\n&gt; quoted *bold*");
+        assert!(blocks.is_none());
+    }
+
+    #[test]
+    fn test_lists_to_mrkdwn() {
+        let markdown = "- one\n- two\n  - nested\n\n1. first\n2. second\n\n- [ ] todo\n- [x] done";
+        assert_eq!(
+            to_mrkdwn(markdown),
+            "• one\n• two\n    • nested\n\n1. first\n2. second\n\n☐ todo\n☑ done"
+        );
+    }
+
+    #[test]
+    fn test_heading_rule_and_code_to_mrkdwn() {
+        let markdown = "# Release *notes*\n\n---\n\n```rust\nfn main() { a < b }\n```";
+        assert_eq!(
+            to_mrkdwn(markdown),
+            "*Release _notes_*\n\n──────────\n\n```\nfn main() { a &lt; b }\n```"
+        );
+    }
+
+    #[test]
+    fn test_table_to_mrkdwn() {
+        let markdown = "| Name | Role |\n|------|:----:|\n| alice | dev |\n| bob | pm |";
+        assert_eq!(
+            to_mrkdwn(markdown),
+            "```\nName  | Role\n------+-----\nalice | dev\nbob   | pm\n```"
+        );
+    }
+
+    #[test]
+    fn test_blocks_for_structured_markdown() {
+        let blocks = to_blocks("# Title\n\nIntro with @alice\n\n---\n\n```\ncode\n```");
+
+        assert_eq!(blocks[0]["type"], "header");
+        assert_eq!(blocks[0]["text"]["text"], "Title");
+        assert_eq!(blocks[1]["type"], "section");
+        assert_eq!(blocks[1]["text"]["text"], "Intro with <@U0123ABC>");
+        assert_eq!(blocks[2]["type"], "divider");
+        assert_eq!(blocks[3]["type"], "rich_text");
+        assert_eq!(blocks[3]["elements"][0]["type"], "rich_text_preformatted");
+        assert_eq!(blocks[3]["elements"][0]["elements"][0]["text"], "code");
+    }
+
+    #[test]
+    fn test_list_blocks_use_rich_text_lists() {
+        let blocks = to_blocks("- **bold** item\n  - nested [link](https://x.com)\n- @alice");
+
+        let lists = blocks[0]["elements"].as_array().unwrap();
+        assert_eq!(lists.len(), 3);
+        assert_eq!(lists[0]["style"], "bullet");
+        assert!(lists[0].get("indent").is_none());
+        assert_eq!(lists[1]["indent"], 1);
+
+        let first = &lists[0]["elements"][0]["elements"];
+        assert_eq!(first[0]["text"], "bold");
+        assert_eq!(first[0]["style"]["bold"], true);
+        assert_eq!(first[1]["text"], " item");
+        assert!(first[1].get("style").is_none());
+
+        let nested = &lists[1]["elements"][0]["elements"];
+        assert_eq!(nested[1]["type"], "link");
+        assert_eq!(nested[1]["url"], "https://x.com");
+
+        assert_eq!(lists[2]["elements"][0]["elements"][0]["type"], "user");
+        assert_eq!(
+            lists[2]["elements"][0]["elements"][0]["user_id"],
+            "U0123ABC"
+        );
+    }
+
+    #[test]
+    fn test_header_is_plain_and_truncated() {
+        let long = format!("# **{}**", "x".repeat(200));
+        let blocks = to_blocks(&long);
+        let text = blocks[0]["text"]["text"].as_str().unwrap();
+        assert_eq!(text.chars().count(), HEADER_MAX_CHARS);
+        assert!(!text.contains('*'));
+    }
+
+    // Round trips: Slack mrkdwn -> CommonMark (read path) -> mrkdwn (send path)
+
+    fn round_trip(slack: &str) -> String {
+        let markdown = mrkdwn::convert(slack, TextFormat::Markdown, &mut |entity| match entity
+            .split_once('|')
+        {
+            Some((url, label)) if !label.is_empty() => format!("[{}]({})", label, url),
+            _ => format!("<{}>", entity.split('|').next().unwrap_or(entity)),
+        });
+        to_mrkdwn(&markdown)
+    }
+
+    #[rstest]
+    #[case("*bold*")]
+    #[case("_italic_ and ~strike~")]
+    #[case("*bold _nested_ ~deep~*")]
+    #[case("_*inner bold*_")]
+    #[case("`code *not bold*`")]
+    #[case("1 &lt; 2 &amp;&amp; 3 &gt; 2")]
+    #[case("see <https://x.com/a_b|the docs>")]
+    #[case("```\nfn main() {\n    a &lt; b;\n}\n```")]
+    #[case("&gt; *quoted* text")]
+    #[case("line one\nline two")]
+    fn test_round_trip_mrkdwn(#[case] slack: &str) {
+        assert_eq!(round_trip(slack), slack);
+    }
+}
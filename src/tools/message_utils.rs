@@ -1,8 +1,61 @@
+use super::mrkdwn::{self, TextFormat, escape};
+use super::timestamps::TimeDisplay;
 use crate::cache::SqliteCache;
-use crate::slack::types::{SlackMessage, SlackUser};
-use chrono::{DateTime, TimeZone, Utc};
+use crate::config::OutputConfig;
+use crate::error::McpResult;
+use crate::slack::types::{Reaction, SlackFile, SlackMessage, SlackUser};
+use crate::utils::Permalink;
+use regex::{Captures, Regex};
+use serde::Serialize;
 use serde_json::{Value, json};
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
+
+/// Matches Slack mrkdwn entities such as `<@U123>`, `<#C123|general>` and `<https://x|label>`
+static ENTITY_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<([^<>\s][^<>]*)>").expect("valid entity pattern"));
+
+/// A user, channel or user group referenced in message text
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Mention {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub id: String,
+    pub name: String,
+}
+
+/// Rendering options shared by the message formatters
+#[derive(Debug, Clone, Default)]
+pub struct FormatOptions {
+    pub text_format: TextFormat,
+    pub time: TimeDisplay,
+    /// Workspace URL used to build `permalink`s; none are added without it
+    pub workspace_url: Option<String>,
+    /// Channel of the messages, for results that don't carry their own
+    pub channel_id: Option<String>,
+}
+
+impl FormatOptions {
+    /// Build from per-call parameters, falling back to the configured defaults
+    pub fn new(
+        text_format: TextFormat,
+        timezone: Option<&str>,
+        relative_times: Option<bool>,
+        defaults: &OutputConfig,
+    ) -> McpResult<Self> {
+        Ok(Self {
+            text_format,
+            time: TimeDisplay::new(timezone, relative_times, defaults)?,
+            ..Default::default()
+        })
+    }
+
+    /// Add permalinks to messages from `channel_id`
+    pub fn with_permalinks(mut self, workspace_url: Option<String>, channel_id: &str) -> Self {
+        self.workspace_url = workspace_url;
+        self.channel_id = Some(channel_id.to_string());
+        self
+    }
+}
 
 /// Remove fields with empty string values from JSON object
 fn remove_empty_strings(value: &mut Value) {
@@ -17,20 +70,6 @@ fn remove_empty_strings(value: &mut Value) {
     }
 }
 
-/// Convert Slack timestamp to ISO 8601 format
-/// Slack timestamps are Unix timestamps with microseconds (e.g., "1234567890.123456")
-fn slack_ts_to_iso8601(ts: &str) -> Option<String> {
-    // Parse the timestamp as a float and convert to seconds and nanoseconds
-    ts.parse::<f64>().ok().and_then(|timestamp| {
-        let seconds = timestamp as i64;
-        let nanos = ((timestamp - seconds as f64) * 1_000_000_000.0) as u32;
-
-        Utc.timestamp_opt(seconds, nanos)
-            .single()
-            .map(|dt: DateTime<Utc>| dt.to_rfc3339())
-    })
-}
-
 /// Get display name from a user, checking for empty strings
 pub fn get_user_display_name(user: &SlackUser) -> &str {
     if let Some(profile) = &user.profile {
@@ -49,44 +88,599 @@ pub fn get_user_display_name(user: &SlackUser) -> &str {
     &user.name
 }
 
+/// Render message text in the requested format, resolving entities via the cache
+///
+/// - `<@U123>` becomes `@display name`
+/// - `<#C123|general>` becomes `#general`
+/// - `<!subteam^S123|@team>` becomes `@team`, and `<!subteam^S123>` its cached handle
+///   (or `@group` when the group isn't cached)
+/// - `<!here>`, `<!channel>` and `<!everyone>` become `@here`, `@channel` and `@everyone`
+/// - `<https://x|label>` becomes `[label](https://x)` (or `label (https://x)` as plain text)
+///
+/// Returns the rendered text and the users, channels and groups it mentions. `Raw`
+/// only resolves the entities, leaving formatting and escapes as Slack sent them.
+pub fn resolve_mentions(
+    text: &str,
+    format: TextFormat,
+    cache: &SqliteCache,
+) -> (String, Vec<Mention>) {
+    let mut mentions: Vec<Mention> = Vec::new();
+    let mut render = |entity: &str| render_entity(entity, format, cache, &mut mentions);
+
+    let rendered = if format == TextFormat::Raw {
+        ENTITY_PATTERN
+            .replace_all(text, |caps: &Captures| render(&caps[1]))
+            .into_owned()
+    } else {
+        mrkdwn::convert(text, format, &mut render)
+    };
+
+    (rendered, mentions)
+}
+
+fn render_entity(
+    entity: &str,
+    format: TextFormat,
+    cache: &SqliteCache,
+    mentions: &mut Vec<Mention>,
+) -> String {
+    let (target, label) = match entity.split_once('|') {
+        Some((target, label)) => (target, Some(label).filter(|l| !l.is_empty())),
+        None => (entity, None),
+    };
+
+    let mention = if let Some(user_id) = target.strip_prefix('@') {
+        let name = cache
+            .get_user_by_id(user_id)
+            .ok()
+            .flatten()
+            .map(|user| get_user_display_name(&user).to_string())
+            .or_else(|| label.map(str::to_string))
+            .unwrap_or_else(|| user_id.to_string());
+        Some(("user", user_id, name))
+    } else if let Some(channel_id) = target.strip_prefix('#') {
+        let name = label
+            .map(str::to_string)
+            .or_else(|| {
+                cache
+                    .get_channel_by_id(channel_id)
+                    .ok()
+                    .flatten()
+                    .map(|channel| channel.name)
+            })
+            .unwrap_or_else(|| channel_id.to_string());
+        Some(("channel", channel_id, name))
+    } else if let Some(group_id) = target.strip_prefix("!subteam^") {
+        // Rich text blocks carry no handle, and an ID would read like a name
+        let name = label
+            .map(|l| l.trim_start_matches('@').to_string())
+            .or_else(|| cache.get_usergroup_handle(group_id).ok().flatten())
+            .unwrap_or_else(|| "group".to_string());
+        Some(("usergroup", group_id, name))
+    } else {
+        None
+    };
+
+    if let Some((kind, id, name)) = mention {
+        let prefix = if kind == "channel" { "#" } else { "@" };
+        let rendered = format!("{}{}", prefix, name);
+        if !mentions.iter().any(|m| m.kind == kind && m.id == id) {
+            mentions.push(Mention {
+                kind,
+                id: id.to_string(),
+                name,
+            });
+        }
+        return rendered;
+    }
+
+    if let Some(special) = target.strip_prefix('!') {
+        // Special mentions (here, channel, everyone) and formatted dates use their label
+        return match special.split('^').next() {
+            Some("here" | "channel" | "everyone") => format!("@{}", special),
+            _ => label.unwrap_or(special).to_string(),
+        };
+    }
+
+    let url = target.trim_start_matches("mailto:");
+    match label {
+        Some(label) if label != target => match format {
+            TextFormat::Plain => format!("{} ({})", label, url),
+            _ => format!("[{}]({})", label, target),
+        },
+        _ => url.to_string(),
+    }
+}
+
+/// Render Block Kit blocks and legacy attachments into mrkdwn text
+///
+/// Bot and integration messages often carry an empty `text` with all content in
+/// blocks or attachments. `rich_text` blocks are skipped when the message has text,
+/// since Slack uses them to store what the author typed.
+fn render_blocks_and_attachments(msg: &SlackMessage) -> String {
+    let mut parts = Vec::new();
+
+    if let Some(blocks) = &msg.blocks {
+        let skip_rich_text = !msg.text.trim().is_empty();
+        let rendered = render_blocks(blocks, skip_rich_text);
+        if !rendered.is_empty() {
+            parts.push(rendered);
+        }
+    }
+
+    for attachment in msg.attachments.iter().flatten() {
+        let rendered = render_attachment(attachment);
+        if !rendered.is_empty() && rendered != msg.text {
+            parts.push(rendered);
+        }
+    }
+
+    parts.join("\n\n")
+}
+
+fn render_blocks(blocks: &[Value], skip_rich_text: bool) -> String {
+    blocks
+        .iter()
+        .filter(|b| !(skip_rich_text && b["type"] == "rich_text"))
+        .filter_map(render_block)
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_block(block: &Value) -> Option<String> {
+    let text = match block["type"].as_str()? {
+        "header" => format!("*{}*", render_text_object(&block["text"])?),
+        "section" => {
+            let mut lines: Vec<String> = render_text_object(&block["text"]).into_iter().collect();
+            lines.extend(
+                block["fields"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(render_text_object),
+            );
+            lines.join("\n")
+        }
+        "context" => block["elements"]
+            .as_array()?
+            .iter()
+            .filter_map(|e| render_text_object(e).or_else(|| e["alt_text"].as_str().map(escape)))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        "divider" => "---".to_string(),
+        "image" => {
+            let label = render_text_object(&block["title"])
+                .or_else(|| block["alt_text"].as_str().map(escape))?;
+            format!("[image: {}]", label)
+        }
+        "actions" => block["elements"]
+            .as_array()?
+            .iter()
+            .filter_map(|e| render_text_object(&e["text"]))
+            .map(|label| format!("[{}]", label))
+            .collect::<Vec<_>>()
+            .join(" "),
+        "markdown" => block["text"].as_str()?.to_string(),
+        "rich_text" => block["elements"]
+            .as_array()?
+            .iter()
+            .map(render_rich_text_element)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => return None,
+    };
+    Some(text)
+}
+
+/// Render a `plain_text` or `mrkdwn` text object
+fn render_text_object(object: &Value) -> Option<String> {
+    let text = object["text"].as_str().filter(|t| !t.is_empty())?;
+    Some(match object["type"].as_str() {
+        Some("plain_text") => escape(text),
+        _ => text.to_string(),
+    })
+}
+
+fn render_rich_text_element(element: &Value) -> String {
+    let inline = |element: &Value| -> String {
+        element["elements"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(render_rich_text_leaf)
+            .collect()
+    };
+
+    match element["type"].as_str() {
+        Some("rich_text_list") => {
+            let ordered = element["style"] == "ordered";
+            let indent = "    ".repeat(element["indent"].as_u64().unwrap_or(0) as usize);
+            let offset = element["offset"].as_u64().unwrap_or(0) as usize;
+            element["elements"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .enumerate()
+                .map(|(i, item)| {
+                    let bullet = if ordered {
+                        format!("{}.", offset + i + 1)
+                    } else {
+                        "•".to_string()
+                    };
+                    format!("{}{} {}", indent, bullet, inline(item))
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        Some("rich_text_preformatted") => format!("```{}```", inline(element)),
+        Some("rich_text_quote") => inline(element)
+            .split('\n')
+            .map(|line| format!("&gt; {}", line))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => inline(element),
+    }
+}
+
+fn render_rich_text_leaf(leaf: &Value) -> String {
+    let text = match leaf["type"].as_str() {
+        Some("text") => escape(leaf["text"].as_str().unwrap_or_default()),
+        Some("link") => {
+            let url = leaf["url"].as_str().unwrap_or_default();
+            match leaf["text"].as_str() {
+                Some(label) if !label.is_empty() => format!("<{}|{}>", url, escape(label)),
+                _ => format!("<{}>", url),
+            }
+        }
+        Some("user") => format!("<@{}>", leaf["user_id"].as_str().unwrap_or_default()),
+        Some("channel") => format!("<#{}>", leaf["channel_id"].as_str().unwrap_or_default()),
+        Some("usergroup") => format!(
+            "<!subteam^{}>",
+            leaf["usergroup_id"].as_str().unwrap_or_default()
+        ),
+        Some("broadcast") => format!("<!{}>", leaf["range"].as_str().unwrap_or("here")),
+        Some("emoji") => format!(":{}:", leaf["name"].as_str().unwrap_or_default()),
+        Some("date") => leaf["fallback"]
+            .as_str()
+            .map(str::to_string)
+            .or_else(|| leaf["timestamp"].as_i64().map(|t| t.to_string()))
+            .unwrap_or_default(),
+        _ => return String::new(),
+    };
+
+    let style = &leaf["style"];
+    let core = text.trim();
+    if style.is_null() || core.is_empty() {
+        return text;
+    }
+
+    // mrkdwn markers must hug the text, so keep edge whitespace outside them
+    let start = text.len() - text.trim_start().len();
+    let (leading, trailing) = (&text[..start], &text[start + core.len()..]);
+    let mut styled = core.to_string();
+    if style["code"] == true {
+        styled = format!("`{}`", styled);
+    } else {
+        for (key, marker) in [("strike", '~'), ("italic", '_'), ("bold", '*')] {
+            if style[key] == true {
+                styled = format!("{}{}{}", marker, styled, marker);
+            }
+        }
+    }
+    format!("{}{}{}", leading, styled, trailing)
+}
+
+fn render_attachment(attachment: &Value) -> String {
+    let mut lines = Vec::new();
+    let field = |key: &str| attachment[key].as_str().filter(|s| !s.is_empty());
+
+    if let Some(pretext) = field("pretext") {
+        lines.push(pretext.to_string());
+    }
+    if let Some(author) = field("author_name") {
+        lines.push(author.to_string());
+    }
+    if let Some(title) = field("title") {
+        lines.push(match field("title_link") {
+            Some(link) => format!("*<{}|{}>*", link, title),
+            None => format!("*{}*", title),
+        });
+    }
+    if let Some(text) = field("text") {
+        lines.push(text.to_string());
+    }
+    for f in attachment["fields"].as_array().into_iter().flatten() {
+        let title = f["title"].as_str().unwrap_or_default();
+        let value = f["value"].as_str().unwrap_or_default();
+        lines.push(match (title.is_empty(), value.is_empty()) {
+            (false, false) => format!("*{}*: {}", title, value),
+            (false, true) => format!("*{}*", title),
+            _ => value.to_string(),
+        });
+    }
+    if let Some(blocks) = attachment["blocks"].as_array() {
+        let rendered = render_blocks(blocks, false);
+        if !rendered.is_empty() {
+            lines.push(rendered);
+        }
+    }
+    if let Some(footer) = field("footer") {
+        lines.push(footer.to_string());
+    }
+
+    // `fallback` is the plain summary Slack shows where attachments can't render
+    if lines.is_empty()
+        && let Some(fallback) = field("fallback")
+    {
+        lines.push(fallback.to_string());
+    }
+
+    lines.retain(|l| !l.trim().is_empty());
+    lines.join("\n")
+}
+
+/// Human-readable labels for message subtypes
+const SUBTYPE_LABELS: &[(&str, &str)] = &[
+    ("bot_message", "bot message"),
+    ("me_message", "/me message"),
+    ("thread_broadcast", "thread reply also sent to the channel"),
+    ("file_share", "shared a file"),
+    ("channel_join", "joined the channel"),
+    ("channel_leave", "left the channel"),
+    ("group_join", "joined the channel"),
+    ("group_leave", "left the channel"),
+    ("channel_topic", "changed the channel topic"),
+    ("channel_purpose", "changed the channel purpose"),
+    ("channel_name", "renamed the channel"),
+    ("channel_archive", "archived the channel"),
+    ("channel_unarchive", "unarchived the channel"),
+    ("pinned_item", "pinned an item"),
+    ("unpinned_item", "unpinned an item"),
+    ("reminder_add", "added a reminder"),
+    ("bot_add", "added an integration"),
+    ("bot_remove", "removed an integration"),
+    ("tombstone", "deleted message"),
+];
+
+/// Subtypes that record channel housekeeping rather than conversation
+const NOISE_SUBTYPES: &[&str] = &[
+    "channel_join",
+    "channel_leave",
+    "group_join",
+    "group_leave",
+    "channel_topic",
+    "channel_purpose",
+    "channel_name",
+    "channel_archive",
+    "channel_unarchive",
+    "group_topic",
+    "group_purpose",
+    "group_name",
+    "group_archive",
+    "group_unarchive",
+    "pinned_item",
+    "unpinned_item",
+    "reminder_add",
+    "bot_add",
+    "bot_remove",
+];
+
+fn subtype_label(subtype: &str) -> Option<&'static str> {
+    SUBTYPE_LABELS
+        .iter()
+        .find(|(name, _)| *name == subtype)
+        .map(|(_, label)| *label)
+}
+
+/// Whether a message is channel housekeeping (joins, topic changes, pins)
+pub fn is_noise(msg: &SlackMessage) -> bool {
+    msg.subtype
+        .as_deref()
+        .is_some_and(|subtype| NOISE_SUBTYPES.contains(&subtype))
+}
+
+/// Drop housekeeping messages when `exclude_noise` is set
+pub fn filter_noise(messages: &mut Vec<SlackMessage>, exclude_noise: bool) {
+    if exclude_noise {
+        messages.retain(|msg| !is_noise(msg));
+    }
+}
+
+/// Messages around an anchor, oldest first
+#[derive(Debug)]
+pub struct ContextWindow {
+    pub messages: Vec<SlackMessage>,
+    pub has_more_before: bool,
+    pub has_more_after: bool,
+}
+
+/// Take up to `before` messages preceding and `after` messages following the
+/// anchor. Input may be in any order; returns `None` when the anchor is missing.
+pub fn context_window(
+    mut messages: Vec<SlackMessage>,
+    anchor_ts: &str,
+    before: usize,
+    after: usize,
+) -> Option<ContextWindow> {
+    messages.sort_by(|a, b| a.ts.cmp(&b.ts));
+    messages.dedup_by(|a, b| a.ts == b.ts);
+
+    let anchor = messages.iter().position(|m| m.ts == anchor_ts)?;
+    let start = anchor.saturating_sub(before);
+    let end = (anchor + after + 1).min(messages.len());
+    let has_more_after = end < messages.len();
+
+    messages.truncate(end);
+    Some(ContextWindow {
+        messages: messages.split_off(start),
+        has_more_before: start > 0,
+        has_more_after,
+    })
+}
+
+fn format_reactions(reactions: &[Reaction], cache: &Arc<SqliteCache>) -> Value {
+    let formatted: Vec<Value> = reactions
+        .iter()
+        .map(|reaction| {
+            let users: Vec<String> = reaction
+                .users
+                .iter()
+                .map(|id| {
+                    cache
+                        .get_user_by_id(id)
+                        .ok()
+                        .flatten()
+                        .map(|user| get_user_display_name(&user).to_string())
+                        .unwrap_or_else(|| id.clone())
+                })
+                .collect();
+            json!({
+                "name": reaction.name,
+                "count": reaction.count,
+                "users": users,
+            })
+        })
+        .collect();
+    json!(formatted)
+}
+
+fn format_file(file: &SlackFile) -> Value {
+    let mut formatted = json!({
+        "name": file.name.as_ref().or(file.title.as_ref()).unwrap_or(&file.id),
+    });
+    if let Some(mimetype) = &file.mimetype {
+        formatted["mimetype"] = json!(mimetype);
+    }
+    if let Some(size) = file.size {
+        formatted["size"] = json!(size);
+    }
+    if let Some(permalink) = &file.permalink {
+        formatted["permalink"] = json!(permalink);
+    }
+    formatted
+}
+
+/// Slack's own permalink when the API returned one, otherwise built locally
+fn message_permalink(msg: &SlackMessage, options: &FormatOptions) -> Option<String> {
+    if let Some(permalink) = &msg.permalink {
+        return Some(permalink.clone());
+    }
+    let workspace_url = options.workspace_url.as_deref()?;
+    let channel = msg
+        .channel
+        .as_ref()
+        .map(|c| c.id.as_str())
+        .or(options.channel_id.as_deref())?;
+    Some(Permalink::build(
+        workspace_url,
+        channel,
+        &msg.ts,
+        msg.thread_ts.as_deref(),
+    ))
+}
+
 /// Format a message with user name resolution
 pub async fn format_message(
     msg: SlackMessage,
     cache: &Arc<SqliteCache>,
     include_thread_info: bool,
+    options: &FormatOptions,
 ) -> Value {
+    let text_format = options.text_format;
+    let (text, mut mentions) = resolve_mentions(&msg.text, text_format, cache);
     let mut result = json!({
         "ts": msg.ts.clone(),
-        "text": msg.text,
+        "text": text,
     });
 
-    // Add ISO 8601 formatted datetime
-    if let Some(iso_time) = slack_ts_to_iso8601(&msg.ts) {
+    let content = render_blocks_and_attachments(&msg);
+    if !content.is_empty() {
+        let (rendered, content_mentions) = resolve_mentions(&content, text_format, cache);
+        if rendered.trim() != result["text"].as_str().unwrap_or_default().trim() {
+            result["rendered_text"] = json!(rendered);
+        }
+        for mention in content_mentions {
+            if !mentions.contains(&mention) {
+                mentions.push(mention);
+            }
+        }
+    }
+
+    if !mentions.is_empty() {
+        result["mentions"] = json!(mentions);
+    }
+
+    let author = msg
+        .user
+        .as_deref()
+        .and_then(|id| cache.get_user_by_id(id).ok().flatten());
+
+    // Add ISO 8601 formatted datetime in the display timezone
+    if let Some(iso_time) = options.time.datetime(&msg.ts, author.as_ref()) {
         result["datetime"] = json!(iso_time);
     }
+    if let Some(relative) = options.time.relative(&msg.ts) {
+        result["relative_time"] = json!(relative);
+    }
 
     // Add user_id with name resolution if present
-    if let Some(user_id) = msg.user {
+    if let Some(user_id) = &msg.user {
         result["user_id"] = json!(user_id);
 
         // Try to get user name from cache
-        if let Ok(Some(user)) = cache.get_user_by_id(&user_id) {
-            result["user_name"] = json!(get_user_display_name(&user));
+        if let Some(user) = &author {
+            result["user_name"] = json!(get_user_display_name(user));
+            if user.deleted {
+                result["user_deleted"] = json!(true);
+            }
+        }
+    }
+
+    // Bot and integration messages carry a sender name instead of a user ID
+    if msg.user.is_none()
+        && let Some(username) = &msg.username
+    {
+        result["user_name"] = json!(username);
+    }
+
+    if let Some(subtype) = &msg.subtype {
+        result["subtype"] = json!(subtype);
+        if let Some(label) = subtype_label(subtype) {
+            result["subtype_label"] = json!(label);
         }
     }
 
+    if let Some(edited) = &msg.edited
+        && let Some(iso_time) = options.time.datetime(&edited.ts, author.as_ref())
+    {
+        result["edited_at"] = json!(iso_time);
+    }
+
+    if let Some(reactions) = msg.reactions.as_deref().filter(|r| !r.is_empty()) {
+        result["reactions"] = format_reactions(reactions, cache);
+    }
+
+    if let Some(files) = msg.files.as_deref().filter(|f| !f.is_empty()) {
+        result["files"] = json!(files.iter().map(format_file).collect::<Vec<_>>());
+    }
+
     // Add channel information if available (from search.messages)
     if let Some(channel) = &msg.channel {
         result["channel_id"] = json!(channel.id);
         result["channel_name"] = json!(channel.name);
     }
 
+    if let Some(permalink) = message_permalink(&msg, options) {
+        result["permalink"] = json!(permalink);
+    }
+
     // Add thread information if requested
     if include_thread_info && let Some(thread_ts) = &msg.thread_ts {
         result["thread_ts"] = json!(thread_ts);
 
         // Add ISO 8601 formatted thread datetime
-        if let Some(iso_time) = slack_ts_to_iso8601(thread_ts) {
+        if let Some(iso_time) = options.time.datetime(thread_ts, author.as_ref()) {
             result["thread_datetime"] = json!(iso_time);
         }
 
@@ -103,7 +697,7 @@ pub async fn format_message(
                 result["latest_reply"] = json!(latest_reply);
 
                 // Add ISO 8601 formatted latest reply datetime
-                if let Some(iso_time) = slack_ts_to_iso8601(latest_reply) {
+                if let Some(iso_time) = options.time.datetime(latest_reply, author.as_ref()) {
                     result["latest_reply_datetime"] = json!(iso_time);
                 }
             }
@@ -121,6 +715,7 @@ pub async fn format_message(
 pub async fn format_thread_messages(
     messages: Vec<SlackMessage>,
     cache: &Arc<SqliteCache>,
+    options: &FormatOptions,
 ) -> Value {
     if messages.is_empty() {
         return json!({
@@ -137,20 +732,32 @@ pub async fn format_thread_messages(
         && thread_ts == &first_msg.ts
     {
         // First message is the parent - extract parent info
+        let (parent_text, _) = resolve_mentions(&first_msg.text, options.text_format, cache);
         let mut parent_info = json!({
             "parent_ts": first_msg.ts.clone(),
-            "parent_text": first_msg.text.clone(),
+            "parent_text": parent_text,
         });
 
+        let parent_user = first_msg
+            .user
+            .as_deref()
+            .and_then(|id| cache.get_user_by_id(id).ok().flatten());
+
         // Add ISO 8601 formatted parent datetime
-        if let Some(iso_time) = slack_ts_to_iso8601(&first_msg.ts) {
+        if let Some(iso_time) = options.time.datetime(&first_msg.ts, parent_user.as_ref()) {
             parent_info["parent_datetime"] = json!(iso_time);
         }
+        if let Some(relative) = options.time.relative(&first_msg.ts) {
+            parent_info["parent_relative_time"] = json!(relative);
+        }
 
         if let Some(user_id) = &first_msg.user {
             parent_info["parent_user_id"] = json!(user_id);
-            if let Ok(Some(user)) = cache.get_user_by_id(user_id) {
-                parent_info["parent_user_name"] = json!(get_user_display_name(&user));
+            if let Some(user) = &parent_user {
+                parent_info["parent_user_name"] = json!(get_user_display_name(user));
+                if user.deleted {
+                    parent_info["parent_user_deleted"] = json!(true);
+                }
             }
         }
 
@@ -159,7 +766,7 @@ pub async fn format_thread_messages(
 
     // Format all messages without parent_user duplication
     for msg in messages {
-        formatted_messages.push(format_message(msg, cache, false).await);
+        formatted_messages.push(format_message(msg, cache, false, options).await);
     }
 
     result["messages"] = json!(formatted_messages);
@@ -184,7 +791,8 @@ pub async fn format_thread_messages(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::slack::types::{MessageChannel, SlackUserProfile};
+    use crate::slack::types::{EditedInfo, MessageChannel, SlackChannel, SlackUserProfile};
+    use rstest::rstest;
 
     async fn setup_cache() -> Arc<SqliteCache> {
         Arc::new(
@@ -201,6 +809,8 @@ mod tests {
             is_bot: false,
             is_admin: false,
             deleted: false,
+            tz: None,
+            tz_offset: None,
             profile: Some(SlackUserProfile {
                 real_name: Some(name.to_string()),
                 display_name: display_name.map(|s| s.to_string()),
@@ -228,38 +838,12 @@ mod tests {
             edited: None,
             blocks: None,
             attachments: None,
+            files: None,
+            username: None,
+            permalink: None,
         }
     }
 
-    // Tests for slack_ts_to_iso8601
-
-    #[test]
-    fn test_slack_ts_to_iso8601_valid() {
-        let result = slack_ts_to_iso8601("1609459200.000000");
-        assert!(result.is_some());
-        assert_eq!(result.unwrap(), "2021-01-01T00:00:00+00:00");
-    }
-
-    #[test]
-    fn test_slack_ts_to_iso8601_with_microseconds() {
-        let result = slack_ts_to_iso8601("1609459200.123456");
-        assert!(result.is_some());
-        let iso = result.unwrap();
-        assert!(iso.starts_with("2021-01-01T00:00:00.123"));
-    }
-
-    #[test]
-    fn test_slack_ts_to_iso8601_invalid() {
-        let result = slack_ts_to_iso8601("invalid");
-        assert!(result.is_none());
-    }
-
-    #[test]
-    fn test_slack_ts_to_iso8601_empty() {
-        let result = slack_ts_to_iso8601("");
-        assert!(result.is_none());
-    }
-
     // Tests for get_user_display_name
 
     #[test]
@@ -334,7 +918,7 @@ mod tests {
         let cache = setup_cache().await;
         let msg = create_test_message("1609459200.000000", "Hello World", None);
 
-        let result = format_message(msg, &cache, false).await;
+        let result = format_message(msg, &cache, false, &FormatOptions::default()).await;
 
         assert_eq!(result["ts"], "1609459200.000000");
         assert_eq!(result["text"], "Hello World");
@@ -351,19 +935,59 @@ mod tests {
 
         let msg = create_test_message("1609459200.000000", "Hello", Some("U123"));
 
-        let result = format_message(msg, &cache, false).await;
+        let result = format_message(msg, &cache, false, &FormatOptions::default()).await;
 
         assert_eq!(result["user_id"], "U123");
         assert_eq!(result["user_name"], "Alice");
     }
 
+    #[tokio::test]
+    async fn test_format_message_in_author_timezone() {
+        let cache = setup_cache().await;
+        let mut user = create_test_user("U123", "alice", None);
+        user.tz = Some("Asia/Seoul".to_string());
+        cache.save_users(vec![user]).await.unwrap();
+
+        let mut msg = create_test_message("1609459200.000000", "Hello", Some("U123"));
+        msg.thread_ts = Some("1609459200.000000".to_string());
+        let output = OutputConfig {
+            format: Default::default(),
+            timezone: "UTC".to_string(),
+            relative_times: false,
+        };
+        let options =
+            FormatOptions::new(TextFormat::Markdown, Some("user"), Some(true), &output).unwrap();
+
+        let result = format_message(msg, &cache, true, &options).await;
+
+        assert_eq!(result["datetime"], "2021-01-01T09:00:00+09:00");
+        assert_eq!(result["thread_datetime"], "2021-01-01T09:00:00+09:00");
+        assert!(result["relative_time"].as_str().unwrap().ends_with(" ago"));
+    }
+
+    #[tokio::test]
+    async fn test_format_message_with_deleted_user() {
+        let cache = setup_cache().await;
+
+        let mut user = create_test_user("U123", "alice", Some("Alice"));
+        user.deleted = true;
+        cache.save_users(vec![user]).await.unwrap();
+
+        let msg = create_test_message("1609459200.000000", "Hello", Some("U123"));
+
+        let result = format_message(msg, &cache, false, &FormatOptions::default()).await;
+
+        assert_eq!(result["user_name"], "Alice");
+        assert_eq!(result["user_deleted"], true);
+    }
+
     #[tokio::test]
     async fn test_format_message_user_not_in_cache() {
         let cache = setup_cache().await;
 
         let msg = create_test_message("1609459200.000000", "Hello", Some("U999"));
 
-        let result = format_message(msg, &cache, false).await;
+        let result = format_message(msg, &cache, false, &FormatOptions::default()).await;
 
         assert_eq!(result["user_id"], "U999");
         assert!(result["user_name"].is_null());
@@ -379,7 +1003,7 @@ mod tests {
             name: "general".to_string(),
         });
 
-        let result = format_message(msg, &cache, false).await;
+        let result = format_message(msg, &cache, false, &FormatOptions::default()).await;
 
         assert_eq!(result["channel_id"], "C123");
         assert_eq!(result["channel_name"], "general");
@@ -394,7 +1018,7 @@ mod tests {
         msg.reply_count = Some(5);
         msg.latest_reply = Some("1609459300.000000".to_string());
 
-        let result = format_message(msg, &cache, true).await;
+        let result = format_message(msg, &cache, true, &FormatOptions::default()).await;
 
         assert_eq!(result["is_thread_parent"], true);
         assert_eq!(result["thread_ts"], "1609459200.000000");
@@ -409,7 +1033,7 @@ mod tests {
         let mut msg = create_test_message("1609459250.000000", "Thread reply", Some("U456"));
         msg.thread_ts = Some("1609459200.000000".to_string());
 
-        let result = format_message(msg, &cache, true).await;
+        let result = format_message(msg, &cache, true, &FormatOptions::default()).await;
 
         assert_eq!(result["is_thread_reply"], true);
         assert_eq!(result["thread_ts"], "1609459200.000000");
@@ -423,7 +1047,7 @@ mod tests {
         let mut msg = create_test_message("1609459200.000000", "Message", None);
         msg.thread_ts = Some("1609459200.000000".to_string());
 
-        let result = format_message(msg, &cache, false).await;
+        let result = format_message(msg, &cache, false, &FormatOptions::default()).await;
 
         // Thread info should not be included
         assert!(result["thread_ts"].is_null());
@@ -436,7 +1060,7 @@ mod tests {
     async fn test_format_thread_messages_empty() {
         let cache = setup_cache().await;
 
-        let result = format_thread_messages(vec![], &cache).await;
+        let result = format_thread_messages(vec![], &cache, &FormatOptions::default()).await;
 
         assert!(result["messages"].is_array());
         assert_eq!(result["messages"].as_array().unwrap().len(), 0);
@@ -456,7 +1080,8 @@ mod tests {
         let mut reply = create_test_message("1609459250.000000", "Reply", Some("U123"));
         reply.thread_ts = Some("1609459200.000000".to_string());
 
-        let result = format_thread_messages(vec![parent, reply], &cache).await;
+        let result =
+            format_thread_messages(vec![parent, reply], &cache, &FormatOptions::default()).await;
 
         // Should have thread_info
         assert!(!result["thread_info"].is_null());
@@ -479,7 +1104,8 @@ mod tests {
         let mut msg2 = create_test_message("1609459300.000000", "Reply 2", Some("U456"));
         msg2.thread_ts = Some("1609459200.000000".to_string());
 
-        let result = format_thread_messages(vec![msg1, msg2], &cache).await;
+        let result =
+            format_thread_messages(vec![msg1, msg2], &cache, &FormatOptions::default()).await;
 
         // Should not have thread_info (parent not included)
         assert!(result["thread_info"].is_null());
@@ -494,11 +1120,512 @@ mod tests {
 
         let msg = create_test_message("1609459200.000000", "Message", None);
 
-        let result = format_thread_messages(vec![msg], &cache).await;
+        let result = format_thread_messages(vec![msg], &cache, &FormatOptions::default()).await;
 
         // Check that empty user fields are not included
         let first_msg = &result["messages"][0];
         assert!(first_msg["user_id"].is_null());
         assert!(first_msg["user_name"].is_null());
     }
+
+    // Tests for resolve_mentions
+
+    async fn setup_mentions_cache() -> Arc<SqliteCache> {
+        let cache = setup_cache().await;
+        cache
+            .save_users(vec![create_test_user(
+                "U0123ABC",
+                "alice",
+                Some("Alice Kim"),
+            )])
+            .await
+            .unwrap();
+        cache
+            .save_channels(vec![SlackChannel {
+                id: "C0456".to_string(),
+                name: "dev-team".to_string(),
+                is_channel: true,
+                is_private: false,
+                is_archived: false,
+                is_general: false,
+                is_im: false,
+                is_mpim: false,
+                is_member: true,
+                created: None,
+                creator: None,
+                topic: None,
+                purpose: None,
+                num_members: None,
+                previous_names: vec![],
+            }])
+            .await
+            .unwrap();
+        cache
+            .save_usergroups(&[("S0PLATFORM".to_string(), "platform-team".to_string())])
+            .unwrap();
+        cache
+    }
+
+    #[rstest]
+    #[case("Hi <@U0123ABC>!", "Hi @Alice Kim!")]
+    #[case("Hi <@U0123ABC|alice>", "Hi @Alice Kim")]
+    #[case("Hi <@U999|bob>", "Hi @bob")]
+    #[case("Hi <@U999>", "Hi @U999")]
+    #[case("See <#C0456|>", "See #dev-team")]
+    #[case("See <#C0456>", "See #dev-team")]
+    #[case("See <#C0456|renamed>", "See #renamed")]
+    #[case("See <#C999|>", "See #C999")]
+    #[case("Ping <!subteam^S123|@platform>", "Ping @platform")]
+    #[case("Ping <!subteam^S0PLATFORM>", "Ping @platform-team")]
+    #[case("Ping <!subteam^S123>", "Ping @group")]
+    #[case("<!here> <!channel> <!everyone|everyone>", "@here @channel @everyone")]
+    #[case("<!date^1392734382^{date_short}|Feb 18, 2014>", "Feb 18, 2014")]
+    #[case(
+        "Docs: <https://x.com/a|the docs>",
+        "Docs: [the docs](https://x.com/a)"
+    )]
+    #[case("Docs: <https://x.com/a>", "Docs: https://x.com/a")]
+    #[case("Mail <mailto:a@b.com|a@b.com>", "Mail [a@b.com](mailto:a@b.com)")]
+    #[case("1 < 2 and 3 > 2", "1 < 2 and 3 > 2")]
+    #[case("no entities", "no entities")]
+    #[tokio::test]
+    async fn test_resolve_mentions_text(#[case] input: &str, #[case] expected: &str) {
+        let cache = setup_mentions_cache().await;
+        let (text, _) = resolve_mentions(input, TextFormat::Markdown, &cache);
+        assert_eq!(text, expected);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_mentions_collects_unique_mentions() {
+        let cache = setup_mentions_cache().await;
+        let (_, mentions) = resolve_mentions(
+            "<@U0123ABC> and <@U0123ABC> in <#C0456|> cc <!subteam^S1|@ops> <!here>",
+            TextFormat::Markdown,
+            &cache,
+        );
+
+        assert_eq!(
+            mentions,
+            vec![
+                Mention {
+                    kind: "user",
+                    id: "U0123ABC".to_string(),
+                    name: "Alice Kim".to_string(),
+                },
+                Mention {
+                    kind: "channel",
+                    id: "C0456".to_string(),
+                    name: "dev-team".to_string(),
+                },
+                Mention {
+                    kind: "usergroup",
+                    id: "S1".to_string(),
+                    name: "ops".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_format_message_resolves_mentions() {
+        let cache = setup_mentions_cache().await;
+        let msg = create_test_message("1609459200.000000", "cc <@U0123ABC>", None);
+
+        let result = format_message(msg, &cache, false, &FormatOptions::default()).await;
+
+        assert_eq!(result["text"], "cc @Alice Kim");
+        assert_eq!(result["mentions"][0]["type"], "user");
+        assert_eq!(result["mentions"][0]["id"], "U0123ABC");
+    }
+
+    #[tokio::test]
+    async fn test_format_message_without_mentions_omits_array() {
+        let cache = setup_cache().await;
+        let msg = create_test_message("1609459200.000000", "plain", None);
+
+        let result = format_message(msg, &cache, false, &FormatOptions::default()).await;
+
+        assert!(result.get("mentions").is_none());
+    }
+
+    #[rstest]
+    #[case(
+        "*Ship <@U0123ABC>'s _fix_ in <#C0456|>*",
+        "**Ship @Alice Kim's _fix_ in #dev-team**",
+        "Ship @Alice Kim's fix in #dev-team"
+    )]
+    #[case(
+        "~*old <https://x.com/a_b|docs>*~ _see <!subteam^S1|@ops>_",
+        "~~**old [docs](https://x.com/a_b)**~~ _see @ops_",
+        "old docs (https://x.com/a_b) see @ops"
+    )]
+    #[case(
+        "&gt; *<@U0123ABC>* wrote:\n```<@U0123ABC> *stays*```",
+        "> **@Alice Kim** wrote:\n```\n<@U0123ABC> *stays*\n```",
+        "@Alice Kim wrote:\n<@U0123ABC> *stays*"
+    )]
+    #[tokio::test]
+    async fn test_resolve_mentions_with_nested_formatting(
+        #[case] input: &str,
+        #[case] markdown: &str,
+        #[case] plain: &str,
+    ) {
+        let cache = setup_mentions_cache().await;
+        assert_eq!(
+            resolve_mentions(input, TextFormat::Markdown, &cache).0,
+            markdown
+        );
+        assert_eq!(resolve_mentions(input, TextFormat::Plain, &cache).0, plain);
+    }
+
+    #[rstest]
+    #[case(TextFormat::Markdown, "**Hi** @Alice Kim, see [docs](https://x.com) &")]
+    #[case(TextFormat::Plain, "Hi @Alice Kim, see docs (https://x.com) &")]
+    #[case(TextFormat::Raw, "*Hi* @Alice Kim, see [docs](https://x.com) &amp;")]
+    #[tokio::test]
+    async fn test_format_message_text_formats(#[case] format: TextFormat, #[case] expected: &str) {
+        let cache = setup_mentions_cache().await;
+        let msg = create_test_message(
+            "1609459200.000000",
+            "*Hi* <@U0123ABC>, see <https://x.com|docs> &amp;",
+            None,
+        );
+
+        let options = FormatOptions {
+            text_format: format,
+            ..Default::default()
+        };
+        let result = format_message(msg, &cache, false, &options).await;
+
+        assert_eq!(result["text"], expected);
+        assert_eq!(result["mentions"][0]["id"], "U0123ABC");
+    }
+
+    // Tests for block and attachment rendering
+
+    fn message_with_content(text: &str, blocks: Value, attachments: Value) -> SlackMessage {
+        let mut msg = create_test_message("1609459200.000000", text, None);
+        msg.blocks = serde_json::from_value(blocks).unwrap();
+        msg.attachments = serde_json::from_value(attachments).unwrap();
+        msg
+    }
+
+    #[test]
+    fn test_render_layout_blocks() {
+        let msg = message_with_content(
+            "",
+            json!([
+                {"type": "header", "text": {"type": "plain_text", "text": "Deploy <prod>"}},
+                {"type": "section", "text": {"type": "mrkdwn", "text": "*Status:* failed"},
+                 "fields": [
+                    {"type": "mrkdwn", "text": "*Env*\nprod"},
+                    {"type": "plain_text", "text": "v1.2"}
+                 ]},
+                {"type": "divider"},
+                {"type": "context", "elements": [
+                    {"type": "image", "image_url": "https://x.com/i.png", "alt_text": "ci"},
+                    {"type": "mrkdwn", "text": "by <@U0123ABC>"}
+                ]},
+                {"type": "actions", "elements": [
+                    {"type": "button", "text": {"type": "plain_text", "text": "Retry"}}
+                ]},
+                {"type": "image", "image_url": "https://x.com/g.png", "alt_text": "graph"},
+                {"type": "unknown_block"}
+            ]),
+            Value::Null,
+        );
+
+        assert_eq!(
+            render_blocks_and_attachments(&msg),
+            "*Deploy &lt;prod&gt;*\n*Status:* failed\n*Env*\nprod\nv1.2\n---\nci | by <@U0123ABC>\n[Retry]\n[image: graph]"
+        );
+    }
+
+    #[test]
+    fn test_render_rich_text_blocks() {
+        let msg = message_with_content(
+            "",
+            json!([{"type": "rich_text", "elements": [
+                {"type": "rich_text_section", "elements": [
+                    {"type": "text", "text": "Hi "},
+                    {"type": "user", "user_id": "U0123ABC"},
+                    {"type": "text", "text": " see", "style": {"bold": true}},
+                    {"type": "text", "text": " "},
+                    {"type": "link", "url": "https://x.com", "text": "docs"},
+                    {"type": "emoji", "name": "tada"}
+                ]},
+                {"type": "rich_text_list", "style": "ordered", "elements": [
+                    {"type": "rich_text_section", "elements": [{"type": "text", "text": "one"}]},
+                    {"type": "rich_text_section", "elements": [{"type": "text", "text": "x", "style": {"code": true}}]}
+                ]},
+                {"type": "rich_text_preformatted", "elements": [{"type": "text", "text": "a < b"}]},
+                {"type": "rich_text_quote", "elements": [{"type": "text", "text": "quoted"}]}
+            ]}]),
+            Value::Null,
+        );
+
+        assert_eq!(
+            render_blocks_and_attachments(&msg),
+            "Hi <@U0123ABC> *see* <https://x.com|docs>:tada:\n1. one\n2. `x`\n```a &lt; b```\n&gt; quoted"
+        );
+    }
+
+    #[test]
+    fn test_render_skips_rich_text_when_message_has_text() {
+        let msg = message_with_content(
+            "typed text",
+            json!([{"type": "rich_text", "elements": [
+                {"type": "rich_text_section", "elements": [{"type": "text", "text": "typed text"}]}
+            ]}]),
+            Value::Null,
+        );
+
+        assert_eq!(render_blocks_and_attachments(&msg), "");
+    }
+
+    #[test]
+    fn test_render_attachments() {
+        let msg = message_with_content(
+            "",
+            Value::Null,
+            json!([
+                {
+                    "pretext": "New alert",
+                    "title": "CPU high",
+                    "title_link": "https://pd.com/1",
+                    "text": "host-1 at 98%",
+                    "fields": [{"title": "Severity", "value": "critical"}],
+                    "footer": "PagerDuty"
+                },
+                {"fallback": "[repo] 2 new commits"}
+            ]),
+        );
+
+        assert_eq!(
+            render_blocks_and_attachments(&msg),
+            "New alert\n*<https://pd.com/1|CPU high>*\nhost-1 at 98%\n*Severity*: critical\nPagerDuty\n\n[repo] 2 new commits"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_format_message_adds_rendered_text() {
+        let cache = setup_mentions_cache().await;
+        let msg = message_with_content(
+            "",
+            json!([{"type": "section", "text": {"type": "mrkdwn", "text": "*Build* failed for <@U0123ABC>"}}]),
+            Value::Null,
+        );
+        let options = FormatOptions {
+            text_format: TextFormat::Markdown,
+            ..Default::default()
+        };
+
+        let result = format_message(msg, &cache, false, &options).await;
+
+        assert!(result.get("text").is_none());
+        assert_eq!(result["rendered_text"], "**Build** failed for @Alice Kim");
+        assert_eq!(result["mentions"][0]["id"], "U0123ABC");
+    }
+
+    #[tokio::test]
+    async fn test_format_message_omits_rendered_text_matching_text() {
+        let cache = setup_cache().await;
+        let msg = message_with_content(
+            "same",
+            json!([{"type": "section", "text": {"type": "mrkdwn", "text": "same"}}]),
+            Value::Null,
+        );
+
+        let result = format_message(msg, &cache, false, &FormatOptions::default()).await;
+
+        assert!(result.get("rendered_text").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_format_message_reactions_edits_and_files() {
+        let cache = setup_cache().await;
+        cache
+            .save_users(vec![
+                create_test_user("U123", "alice", Some("Alice")),
+                create_test_user("U456", "bob", None),
+            ])
+            .await
+            .unwrap();
+
+        let mut msg = create_test_message("1609459200.000000", "Report", Some("U123"));
+        msg.reactions = Some(vec![Reaction {
+            name: "+1".to_string(),
+            users: vec!["U123".to_string(), "U456".to_string(), "U999".to_string()],
+            count: 3,
+        }]);
+        msg.edited = Some(EditedInfo {
+            user: "U123".to_string(),
+            ts: "1609462800.000000".to_string(),
+        });
+        msg.files = Some(vec![SlackFile {
+            id: "F1".to_string(),
+            name: Some("report.pdf".to_string()),
+            title: None,
+            mimetype: Some("application/pdf".to_string()),
+            size: Some(2048),
+            permalink: Some("https://example.slack.com/files/U123/F1/report.pdf".to_string()),
+        }]);
+
+        let result = format_message(msg, &cache, false, &FormatOptions::default()).await;
+
+        assert_eq!(
+            result["reactions"],
+            json!([{"name": "+1", "count": 3, "users": ["Alice", "bob", "U999"]}])
+        );
+        assert_eq!(result["edited_at"], "2021-01-01T01:00:00+00:00");
+        assert_eq!(
+            result["files"],
+            json!([{
+                "name": "report.pdf",
+                "mimetype": "application/pdf",
+                "size": 2048,
+                "permalink": "https://example.slack.com/files/U123/F1/report.pdf"
+            }])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_format_message_omits_absent_extras() {
+        let cache = setup_cache().await;
+        let mut msg = create_test_message("1609459200.000000", "Plain", None);
+        msg.reactions = Some(vec![]);
+
+        let result = format_message(msg, &cache, false, &FormatOptions::default()).await;
+
+        assert!(result.get("reactions").is_none());
+        assert!(result.get("edited_at").is_none());
+        assert!(result.get("files").is_none());
+        assert!(result.get("subtype_label").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_format_message_bot_message() {
+        let cache = setup_cache().await;
+        let mut msg = create_test_message("1609459200.000000", "Deployed", None);
+        msg.subtype = Some("bot_message".to_string());
+        msg.username = Some("deploy-bot".to_string());
+
+        let result = format_message(msg, &cache, false, &FormatOptions::default()).await;
+
+        assert_eq!(result["subtype"], "bot_message");
+        assert_eq!(result["subtype_label"], "bot message");
+        assert_eq!(result["user_name"], "deploy-bot");
+    }
+
+    #[rstest]
+    #[case(Some("channel_join"), Some("joined the channel"))]
+    #[case(
+        Some("thread_broadcast"),
+        Some("thread reply also sent to the channel")
+    )]
+    #[case(Some("bot_message"), Some("bot message"))]
+    #[case(Some("huddle_thread"), None)]
+    fn test_subtype_label(#[case] subtype: Option<&str>, #[case] expected: Option<&str>) {
+        assert_eq!(subtype.and_then(subtype_label), expected);
+    }
+
+    #[test]
+    fn test_filter_noise() {
+        let subtypes = [
+            None,
+            Some("channel_join"),
+            Some("bot_message"),
+            Some("channel_topic"),
+            Some("thread_broadcast"),
+            Some("pinned_item"),
+        ];
+        let messages: Vec<SlackMessage> = subtypes
+            .iter()
+            .enumerate()
+            .map(|(i, subtype)| {
+                let mut msg = create_test_message(&format!("{}.000000", i), "m", None);
+                msg.subtype = subtype.map(str::to_string);
+                msg
+            })
+            .collect();
+
+        let mut kept = messages.clone();
+        filter_noise(&mut kept, false);
+        assert_eq!(kept.len(), messages.len());
+
+        let mut filtered = messages;
+        filter_noise(&mut filtered, true);
+        let remaining: Vec<_> = filtered.iter().map(|m| m.subtype.as_deref()).collect();
+        assert_eq!(
+            remaining,
+            vec![None, Some("bot_message"), Some("thread_broadcast")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_format_message_permalinks() {
+        let cache = setup_cache().await;
+        let options = FormatOptions::default()
+            .with_permalinks(Some("https://team.slack.com".to_string()), "C0123ABCD");
+
+        let mut reply = create_test_message("1700000000.123456", "reply", None);
+        reply.thread_ts = Some("1699999999.000100".to_string());
+        let result = format_message(reply, &cache, false, &options).await;
+        assert_eq!(
+            result["permalink"],
+            "https://team.slack.com/archives/C0123ABCD/p1700000000123456?thread_ts=1699999999.000100&cid=C0123ABCD"
+        );
+
+        // Links returned by Slack (search results) are kept as is
+        let mut found = create_test_message("1700000000.123456", "hit", None);
+        found.permalink = Some("https://team.slack.com/archives/C1/p1".to_string());
+        let result = format_message(found, &cache, false, &options).await;
+        assert_eq!(result["permalink"], "https://team.slack.com/archives/C1/p1");
+
+        // No workspace URL, no permalink
+        let plain = create_test_message("1700000000.123456", "plain", None);
+        let result = format_message(plain, &cache, false, &FormatOptions::default()).await;
+        assert!(result.get("permalink").is_none());
+    }
+
+    fn timeline(timestamps: &[&str]) -> Vec<SlackMessage> {
+        timestamps
+            .iter()
+            .map(|ts| create_test_message(ts, "m", None))
+            .collect()
+    }
+
+    fn window_ts(window: &ContextWindow) -> Vec<&str> {
+        window.messages.iter().map(|m| m.ts.as_str()).collect()
+    }
+
+    #[test]
+    fn test_context_window_middle() {
+        // Newest first, as conversations.history returns them, with a duplicate
+        let messages = timeline(&["5.0", "4.0", "3.0", "3.0", "2.0", "1.0"]);
+
+        let window = context_window(messages, "3.0", 1, 1).unwrap();
+
+        assert_eq!(window_ts(&window), vec!["2.0", "3.0", "4.0"]);
+        assert!(window.has_more_before);
+        assert!(window.has_more_after);
+    }
+
+    #[test]
+    fn test_context_window_at_edges() {
+        let window = context_window(timeline(&["1.0", "2.0", "3.0"]), "1.0", 5, 5).unwrap();
+        assert_eq!(window_ts(&window), vec!["1.0", "2.0", "3.0"]);
+        assert!(!window.has_more_before);
+        assert!(!window.has_more_after);
+
+        let window = context_window(timeline(&["1.0", "2.0", "3.0"]), "3.0", 1, 0).unwrap();
+        assert_eq!(window_ts(&window), vec!["2.0", "3.0"]);
+        assert!(window.has_more_before);
+        assert!(!window.has_more_after);
+    }
+
+    #[test]
+    fn test_context_window_missing_anchor() {
+        assert!(context_window(timeline(&["1.0", "2.0"]), "9.0", 1, 1).is_none());
+    }
 }
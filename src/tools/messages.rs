@@ -1,71 +1,652 @@
 use async_trait::async_trait;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
 
-use super::message_utils::{format_message, format_thread_messages};
+use super::block_kit;
+use super::budget::{Budget, Continuation};
+use super::markdown::{self, MessageFormat};
+use super::message_utils::{
+    FormatOptions, context_window, filter_noise, format_message, format_thread_messages,
+};
+use super::mrkdwn::TextFormat;
 use super::{IntoToolResponse, Tool, ToolResponse};
-use crate::cache::SqliteCache;
-use crate::error::{IntoMcpError, McpResult};
+use crate::audit::payload_hash;
+use crate::cache::{IdempotencyClaim, SentMessageKey, SqliteCache};
+use crate::config::{MessagingConfig, OutputConfig, ScanAction};
+use crate::error::{IntoMcpError, McpError, McpResult};
+use crate::mcp::peer::{ClientPeer, Elicitation};
+use crate::policy::{Access, ChannelPolicy};
+use crate::scanner::{ContentScanner, merge_findings};
 use crate::slack::SlackClient;
-use crate::utils::{parse_params, resolve_channel_id, validate_required_one_of};
+use crate::slack::core::TokenKind;
+use crate::slack::messages::{PostIdentity, SlackMessageClient};
+use crate::slack::types::SlackMessage;
+use crate::utils::{
+    Permalink, param_or_permalink, parse_params, resolve_channel_name, resolve_permitted_channel,
+    resolve_user_id, validate_required_one_of,
+};
 
 pub struct SendMessageTool {
     slack_client: Arc<SlackClient>,
     cache: Arc<SqliteCache>,
+    policy: Arc<ChannelPolicy>,
+    scanner: Arc<ContentScanner>,
+    peer: Arc<ClientPeer>,
+    poster: MessagePoster,
+    messaging: MessagingConfig,
+    dry_run: bool,
+}
+
+pub struct ConfirmDraftTool {
+    cache: Arc<SqliteCache>,
+    policy: Arc<ChannelPolicy>,
+    poster: MessagePoster,
+    dry_run: bool,
 }
 
 pub struct ReadThreadTool {
     slack_client: Arc<SlackClient>,
     cache: Arc<SqliteCache>,
+    output: OutputConfig,
+    policy: Arc<ChannelPolicy>,
 }
 
 pub struct ListChannelMembersTool {
     slack_client: Arc<SlackClient>,
     cache: Arc<SqliteCache>,
+    policy: Arc<ChannelPolicy>,
 }
 
 pub struct GetChannelMessagesTool {
     slack_client: Arc<SlackClient>,
     cache: Arc<SqliteCache>,
+    output: OutputConfig,
+    policy: Arc<ChannelPolicy>,
+}
+
+pub struct GetMessageContextTool {
+    slack_client: Arc<SlackClient>,
+    cache: Arc<SqliteCache>,
+    output: OutputConfig,
+    policy: Arc<ChannelPolicy>,
+}
+
+pub struct GetMessageTool {
+    slack_client: Arc<SlackClient>,
+    cache: Arc<SqliteCache>,
+    output: OutputConfig,
+    policy: Arc<ChannelPolicy>,
 }
 
 impl SendMessageTool {
-    pub fn new(slack_client: Arc<SlackClient>, cache: Arc<SqliteCache>) -> Self {
+    /// With `dry_run`, the tool returns the request instead of posting it
+    pub fn new(
+        slack_client: Arc<SlackClient>,
+        cache: Arc<SqliteCache>,
+        policy: Arc<ChannelPolicy>,
+        scanner: Arc<ContentScanner>,
+        peer: Arc<ClientPeer>,
+        messaging: MessagingConfig,
+        dry_run: bool,
+    ) -> Self {
         Self {
+            poster: MessagePoster {
+                slack_client: slack_client.clone(),
+                cache: cache.clone(),
+                messaging: messaging.clone(),
+            },
             slack_client,
             cache,
+            policy,
+            scanner,
+            peer,
+            messaging,
+            dry_run,
+        }
+    }
+
+    /// Check the requested identity against the configured tokens
+    fn identity(&self, params: &SendMessageParams) -> McpResult<PostIdentity> {
+        if let Some(kind) = params.post_as
+            && !self.slack_client.has_token(kind)
+        {
+            return Err(McpError::InvalidParameter(format!(
+                "Can't post as {}: no {} token is configured",
+                kind.as_str(),
+                kind.as_str()
+            )));
+        }
+
+        let username = params
+            .username
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string);
+        let icon_emoji = params
+            .icon_emoji
+            .as_deref()
+            .map(|emoji| emoji.trim().trim_matches(':'))
+            .filter(|name| !name.is_empty())
+            .map(|name| format!(":{}:", name));
+
+        // Slack ignores both overrides on messages posted with a user token
+        let posts_as = params
+            .post_as
+            .or_else(|| self.slack_client.default_post_token());
+        if (username.is_some() || icon_emoji.is_some()) && posts_as != Some(TokenKind::Bot) {
+            return Err(McpError::InvalidParameter(
+                "username and icon_emoji only apply to messages posted as the bot".to_string(),
+            ));
+        }
+
+        Ok(PostIdentity {
+            token: params.post_as,
+            username,
+            icon_emoji,
+        })
+    }
+
+    /// Ask the user to approve the message through the client
+    async fn confirm_with_user(&self, message: &OutgoingMessage) -> McpResult<()> {
+        let prompt = format!(
+            "Post this message to {}?\n\n{}",
+            channel_label(&message.channel, &self.cache),
+            preview(&message.parts)
+        );
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "confirm": {
+                    "type": "boolean",
+                    "title": "Post this message",
+                    "default": true,
+                },
+            },
+            "required": ["confirm"],
+        });
+        let timeout = Duration::from_secs(self.messaging.confirmation_timeout_seconds);
+
+        let answer = self
+            .peer
+            .elicit(&prompt, schema, timeout)
+            .await
+            .map_err(|e| McpError::Internal(format!("Message not sent: {}", e)))?;
+        match answer {
+            Elicitation::Accept { content }
+                if content.get("confirm").and_then(Value::as_bool) != Some(false) =>
+            {
+                Ok(())
+            }
+            Elicitation::Accept { .. } | Elicitation::Decline => Err(McpError::PermissionDenied(
+                "Message not sent: the user declined it".to_string(),
+            )),
+            Elicitation::Cancel => Err(McpError::PermissionDenied(
+                "Message not sent: the user dismissed the confirmation".to_string(),
+            )),
+        }
+    }
+
+    /// Hold the message until `confirm_draft` is called with the returned ID
+    fn save_draft(&self, message: &OutgoingMessage) -> McpResult<Value> {
+        let draft_id = format!("draft_{}", &uuid::Uuid::new_v4().simple().to_string()[..12]);
+        let ttl_minutes = self.messaging.draft_ttl_minutes;
+        self.cache
+            .save_draft(
+                &draft_id,
+                &message.channel,
+                &serde_json::to_value(message)?,
+                (ttl_minutes * 60) as i64,
+            )
+            .mcp_context("Failed to save draft")?;
+
+        Ok(json!({
+            "status": "pending_confirmation",
+            "draft_id": draft_id,
+            "channel": message.channel,
+            "preview": preview(&message.parts),
+            "expires_in_minutes": ttl_minutes,
+            "next_step": "Messages to this channel need the user's approval. Show them the preview, then call confirm_draft with this draft_id if they approve, or with discard: true if they don't",
+        }))
+    }
+
+    /// Convert Markdown to mrkdwn, plus Block Kit when it has headings, lists or code.
+    /// Explicit blocks always take precedence over generated ones.
+    fn render(
+        &self,
+        text: Option<String>,
+        blocks: Option<Vec<Value>>,
+        format: MessageFormat,
+    ) -> MessagePart {
+        match (format, text) {
+            (MessageFormat::Markdown, Some(text)) => {
+                let (text, generated) = markdown::to_slack(&text, &mut |sigil, name| match sigil {
+                    '@' => resolve_user_id(name, &self.cache).ok(),
+                    _ => resolve_channel_name(name, &format!("#{}", name), &self.cache).ok(),
+                });
+                MessagePart {
+                    text: Some(text),
+                    blocks: blocks.or(generated),
+                }
+            }
+            (_, text) => MessagePart { text, blocks },
+        }
+    }
+
+    /// Break long text into messages that each pass validation
+    fn split_message(&self, text: &str, format: MessageFormat) -> McpResult<Vec<MessagePart>> {
+        let mut parts = Vec::new();
+        let mut pending = block_kit::split_text(text, block_kit::SPLIT_MAX_CHARS);
+        pending.reverse();
+
+        while let Some(chunk) = pending.pop() {
+            let part = self.render(Some(chunk.clone()), None, format);
+            match block_kit::validate(part.text.as_deref(), part.blocks.as_deref()) {
+                Ok(()) => parts.push(part),
+                Err(e) => {
+                    // Conversion can push a part over a limit (escaping, many small
+                    // blocks); retry it in halves
+                    let halves = block_kit::split_text(&chunk, chunk.chars().count() / 2);
+                    if halves.len() < 2 {
+                        return Err(e);
+                    }
+                    pending.extend(halves.into_iter().rev());
+                }
+            }
+        }
+
+        if parts.is_empty() {
+            return Err(McpError::InvalidParameter(
+                "text is empty; nothing to send".to_string(),
+            ));
+        }
+        Ok(parts)
+    }
+}
+
+/// Text and blocks of one outgoing message
+#[derive(Debug, Serialize, Deserialize)]
+struct MessagePart {
+    text: Option<String>,
+    blocks: Option<Vec<Value>>,
+}
+
+/// A rendered, scanned and validated message, ready to post. Pending drafts
+/// store it as JSON.
+#[derive(Debug, Serialize, Deserialize)]
+struct OutgoingMessage {
+    channel: String,
+    thread_ts: Option<String>,
+    reply_broadcast: bool,
+    parts: Vec<MessagePart>,
+    idempotency_key: Option<String>,
+    force: bool,
+    #[serde(default)]
+    identity: PostIdentity,
+}
+
+/// Posts messages for `send_message` and `confirm_draft`
+struct MessagePoster {
+    slack_client: Arc<SlackClient>,
+    cache: Arc<SqliteCache>,
+    messaging: MessagingConfig,
+}
+
+impl MessagePoster {
+    /// The chat.postMessage requests `post` would make, for dry-run mode
+    fn dry_run(&self, message: &OutgoingMessage) -> Value {
+        // Continuations go into the thread of the first part, whose ts isn't known yet
+        let payloads: Vec<Value> = message
+            .parts
+            .iter()
+            .enumerate()
+            .map(|(i, part)| {
+                let thread_ts = match (i, message.thread_ts.as_deref()) {
+                    (0, thread_ts) => thread_ts,
+                    (_, Some(thread_ts)) => Some(thread_ts),
+                    (_, None) => Some(FIRST_PART_TS),
+                };
+                SlackMessageClient::post_message_payload(
+                    &message.channel,
+                    part.text.as_deref(),
+                    part.blocks.as_ref(),
+                    thread_ts,
+                    message.reply_broadcast && i == 0,
+                    &message.identity,
+                )
+            })
+            .collect();
+
+        let mut response = json!({
+            "dry_run": true,
+            "method": "chat.postMessage",
+        });
+        let posts_as = message
+            .identity
+            .token
+            .or_else(|| self.slack_client.default_post_token());
+        if let Some(kind) = posts_as {
+            response["as"] = json!(kind);
+        }
+        if payloads.len() == 1 {
+            response["payload"] = payloads[0].clone();
+        } else {
+            response["payloads"] = json!(payloads);
+        }
+        response
+    }
+
+    /// Post a message, honouring its idempotency key and the duplicate window
+    async fn post(&self, message: &OutgoingMessage) -> McpResult<Value> {
+        let parts = &message.parts;
+        let channel_id = &message.channel;
+        let content: Vec<Value> = parts
+            .iter()
+            .map(|part| json!({"text": part.text, "blocks": part.blocks}))
+            .collect();
+        let content_hash = payload_hash(&json!(content));
+        let sent_key = SentMessageKey {
+            channel: channel_id,
+            thread_ts: message.thread_ts.as_deref(),
+            content_hash: &content_hash,
+        };
+        let ttl_seconds = (self.messaging.idempotency_ttl_hours * 3600) as i64;
+        let idempotency_key = message.idempotency_key.as_deref();
+
+        // A known key replays the original result; without one, refuse an identical
+        // message sent moments ago (most likely a client retry) unless forced
+        let mut part_ts = Vec::new();
+        match idempotency_key {
+            Some(key) => match self
+                .cache
+                .claim_idempotency_key(key, sent_key, ttl_seconds)
+                .mcp_context("Failed to check idempotency key")?
+            {
+                IdempotencyClaim::Claimed => {}
+                IdempotencyClaim::Sent { channel, ts } => {
+                    return Ok(json!({
+                        "channel": channel,
+                        "ts": ts,
+                        "replayed": true,
+                    }));
+                }
+                IdempotencyClaim::InFlight => {
+                    return Err(McpError::InvalidParameter(format!(
+                        "A message with idempotency_key '{}' is still being sent; retry shortly",
+                        key
+                    )));
+                }
+                // A retry after a part failed picks up where the last attempt stopped
+                IdempotencyClaim::Interrupted {
+                    content_hash: claimed_hash,
+                    posted,
+                } => {
+                    if claimed_hash != content_hash {
+                        let _ = self.cache.release_idempotency_key(key);
+                        return Err(McpError::InvalidParameter(format!(
+                            "idempotency_key '{}' belongs to a different message that was only partly sent",
+                            key
+                        )));
+                    }
+                    part_ts = posted;
+                }
+            },
+            None if !message.force && self.messaging.duplicate_window_seconds > 0 => {
+                let window = self.messaging.duplicate_window_seconds as i64;
+                if let Some((ts, age)) = self
+                    .cache
+                    .find_recent_duplicate(sent_key, window)
+                    .mcp_context("Failed to check recent messages")?
+                {
+                    return Err(McpError::InvalidParameter(format!(
+                        "The same message was sent to {} {}s ago (ts {}); pass force: true to send it again",
+                        channel_id, age, ts
+                    )));
+                }
+            }
+            None => {}
+        }
+        let resumed = !part_ts.is_empty();
+
+        // The first part goes where the message was addressed; the rest are
+        // replies under it
+        for (idx, part) in parts.iter().enumerate().skip(part_ts.len()) {
+            let (thread_ts, reply_broadcast) = match part_ts.first() {
+                Some(first) => (Some(message.thread_ts.as_deref().unwrap_or(first)), false),
+                None => (message.thread_ts.as_deref(), message.reply_broadcast),
+            };
+            let result = self
+                .slack_client
+                .messages
+                .post_message(
+                    channel_id,
+                    part.text.as_deref(),
+                    part.blocks.as_ref(),
+                    thread_ts,
+                    reply_broadcast,
+                    &message.identity,
+                )
+                .await;
+
+            let ts = match result {
+                Ok(ts) => ts,
+                Err(e) => {
+                    if let Some(key) = idempotency_key {
+                        let _ = self.cache.release_idempotency_key(key);
+                    }
+                    if part_ts.is_empty() {
+                        return Err(e).mcp_context("Failed to send message");
+                    }
+                    let retry = if idempotency_key.is_some() {
+                        "; retry with the same idempotency_key to post the rest"
+                    } else {
+                        ""
+                    };
+                    return Err(e).mcp_context(&format!(
+                        "Posted {} of {} parts (ts {}) before failing{}",
+                        part_ts.len(),
+                        parts.len(),
+                        part_ts.join(", "),
+                        retry
+                    ));
+                }
+            };
+
+            // Keyed sends complete their key once every part is posted; a keyless
+            // message counts for duplicate detection as soon as it is visible
+            let recorded = match idempotency_key {
+                Some(key) => self.cache.record_sent_part(key, &ts),
+                None if idx == 0 => {
+                    self.cache
+                        .record_sent_message(None, sent_key, &ts, ttl_seconds)
+                }
+                None => Ok(()),
+            };
+            if let Err(e) = recorded {
+                warn!("Failed to record sent message {}: {}", ts, e);
+            }
+            part_ts.push(ts);
+        }
+
+        let timestamp = part_ts[0].clone();
+        if let Some(key) = idempotency_key
+            && let Err(e) =
+                self.cache
+                    .record_sent_message(Some(key), sent_key, &timestamp, ttl_seconds)
+        {
+            warn!("Failed to record sent message {}: {}", timestamp, e);
+        }
+
+        if parts.len() == 1 {
+            return Ok(json!({
+                "channel": channel_id,
+                "ts": timestamp,
+            }));
+        }
+
+        let thread_ts = message
+            .thread_ts
+            .clone()
+            .unwrap_or_else(|| timestamp.clone());
+        let mut response = json!({
+            "channel": channel_id,
+            "ts": timestamp,
+            "thread_ts": thread_ts,
+            "parts": part_ts,
+        });
+        if resumed {
+            response["resumed"] = json!(true);
+        }
+        Ok(response)
+    }
+}
+
+/// Placeholder thread_ts for continuation parts in a dry run
+const FIRST_PART_TS: &str = "<ts of part 1>";
+
+impl ConfirmDraftTool {
+    /// With `dry_run`, the tool returns the request instead of posting it and
+    /// keeps the draft
+    pub fn new(
+        slack_client: Arc<SlackClient>,
+        cache: Arc<SqliteCache>,
+        policy: Arc<ChannelPolicy>,
+        messaging: MessagingConfig,
+        dry_run: bool,
+    ) -> Self {
+        Self {
+            poster: MessagePoster {
+                slack_client,
+                cache: cache.clone(),
+                messaging,
+            },
+            cache,
+            policy,
+            dry_run,
         }
     }
 }
 
+/// `#name` of a cached channel, or its ID
+fn channel_label(channel_id: &str, cache: &SqliteCache) -> String {
+    match cache.get_channel_by_id(channel_id).ok().flatten() {
+        Some(channel) => format!("#{}", channel.name),
+        None => channel_id.to_string(),
+    }
+}
+
+/// The text of each part as it will be posted, numbered when there are several
+fn preview(parts: &[MessagePart]) -> String {
+    parts
+        .iter()
+        .enumerate()
+        .map(|(i, part)| {
+            let body = match (&part.text, &part.blocks) {
+                (Some(text), _) => text.clone(),
+                (None, Some(blocks)) => format!("(Block Kit message with {} blocks)", blocks.len()),
+                (None, None) => String::new(),
+            };
+            if parts.len() == 1 {
+                body
+            } else {
+                format!("[{}/{}]\n{}", i + 1, parts.len(), body)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
 impl ReadThreadTool {
-    pub fn new(slack_client: Arc<SlackClient>, cache: Arc<SqliteCache>) -> Self {
+    pub fn new(
+        slack_client: Arc<SlackClient>,
+        cache: Arc<SqliteCache>,
+        output: OutputConfig,
+        policy: Arc<ChannelPolicy>,
+    ) -> Self {
         Self {
             slack_client,
             cache,
+            output,
+            policy,
         }
     }
 }
 
 impl ListChannelMembersTool {
-    pub fn new(slack_client: Arc<SlackClient>, cache: Arc<SqliteCache>) -> Self {
+    pub fn new(
+        slack_client: Arc<SlackClient>,
+        cache: Arc<SqliteCache>,
+        policy: Arc<ChannelPolicy>,
+    ) -> Self {
         Self {
             slack_client,
             cache,
+            policy,
         }
     }
 }
 
 impl GetChannelMessagesTool {
-    pub fn new(slack_client: Arc<SlackClient>, cache: Arc<SqliteCache>) -> Self {
+    pub fn new(
+        slack_client: Arc<SlackClient>,
+        cache: Arc<SqliteCache>,
+        output: OutputConfig,
+        policy: Arc<ChannelPolicy>,
+    ) -> Self {
+        Self {
+            slack_client,
+            cache,
+            output,
+            policy,
+        }
+    }
+}
+
+impl GetMessageTool {
+    pub fn new(
+        slack_client: Arc<SlackClient>,
+        cache: Arc<SqliteCache>,
+        output: OutputConfig,
+        policy: Arc<ChannelPolicy>,
+    ) -> Self {
         Self {
             slack_client,
             cache,
+            output,
+            policy,
         }
     }
 }
 
+impl GetMessageContextTool {
+    pub fn new(
+        slack_client: Arc<SlackClient>,
+        cache: Arc<SqliteCache>,
+        output: OutputConfig,
+        policy: Arc<ChannelPolicy>,
+    ) -> Self {
+        Self {
+            slack_client,
+            cache,
+            output,
+            policy,
+        }
+    }
+}
+
+/// Workspace URL for permalinks, looked up with any message from the channel
+async fn workspace_url(
+    slack_client: &SlackClient,
+    channel_id: &str,
+    messages: &[SlackMessage],
+) -> Option<String> {
+    let ts = &messages.first()?.ts;
+    slack_client.messages.workspace_url(channel_id, ts).await
+}
+
 #[derive(Debug, Deserialize)]
 struct SendMessageParams {
     channel: String,
@@ -73,14 +654,61 @@ struct SendMessageParams {
     blocks: Option<Value>,
     thread_ts: Option<String>,
     reply_broadcast: Option<bool>,
+    #[serde(default)]
+    format: MessageFormat,
+    /// Retries with the same key return the first message instead of posting again
+    #[serde(default)]
+    idempotency_key: Option<String>,
+    /// Send even if the same message was just sent to this channel/thread
+    #[serde(default)]
+    force: bool,
+    /// Post long text as several messages, continuing in the first one's thread
+    #[serde(default)]
+    split: bool,
+    /// Post with the bot or the user token instead of the default
+    #[serde(default, rename = "as")]
+    post_as: Option<TokenKind>,
+    /// Bot display name override
+    #[serde(default)]
+    username: Option<String>,
+    /// Bot avatar override, with or without the surrounding colons
+    #[serde(default)]
+    icon_emoji: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConfirmDraftParams {
+    draft_id: String,
+    /// Drop the draft instead of posting it
+    #[serde(default)]
+    discard: bool,
 }
 
 #[derive(Debug, Deserialize)]
 struct ReadThreadParams {
-    channel: String,
-    thread_ts: String,
+    #[serde(default)]
+    channel: Option<String>,
+    #[serde(default)]
+    thread_ts: Option<String>,
+    /// Link to the thread or any message in it, in place of channel/thread_ts
+    #[serde(default)]
+    permalink: Option<String>,
     #[serde(default = "retrieval_default_limit")]
     limit: usize,
+    #[serde(default)]
+    cursor: Option<String>,
+    #[serde(default)]
+    text_format: TextFormat,
+    #[serde(default)]
+    timezone: Option<String>,
+    #[serde(default)]
+    relative_times: Option<bool>,
+    #[serde(default)]
+    max_tokens: Option<usize>,
+    #[serde(default)]
+    max_chars: Option<usize>,
+    #[serde(default)]
+    exclude_noise: bool,
 }
 
 fn retrieval_default_limit() -> usize {
@@ -100,6 +728,10 @@ impl Tool for SendMessageTool {
         "Send message to channel or DM"
     }
 
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, params: Value) -> McpResult<Value> {
         // Parse parameters
         let params: SendMessageParams = parse_params(params)?;
@@ -107,30 +739,196 @@ impl Tool for SendMessageTool {
         // Validate that either text or blocks is provided
         validate_required_one_of(&params.text, &params.blocks, "'text' or 'blocks'")?;
 
+        let identity = self.identity(&params)?;
+
+        let dm_user = params.channel.trim().strip_prefix('@');
+        if dm_user.is_some() && !self.policy.allows_dms() {
+            return Err(McpError::PermissionDenied(format!(
+                "Policy does not allow direct messages ({})",
+                params.channel.trim()
+            )));
+        }
+
         // Resolve channel ID if name is provided
-        let channel_id =
-            resolve_channel_id(&params.channel, &self.cache, Some(&self.slack_client)).await?;
+        let channel_id = match (dm_user, identity.token) {
+            // A dry run doesn't open DMs; chat.postMessage also accepts a user ID
+            // as the channel
+            (Some(user), _) if self.dry_run => resolve_user_id(user, &self.cache)?,
+            // A DM has to be opened by the token that posts to it; the bot's DM
+            // with this user isn't the user's
+            (Some(user), Some(kind)) => {
+                let user_id = resolve_user_id(user, &self.cache)?;
+                let channel_id = self
+                    .slack_client
+                    .users
+                    .open_conversation_as(&user_id, kind)
+                    .await
+                    .mcp_context("Failed to open DM")?;
+                self.policy
+                    .check(
+                        &channel_id,
+                        &self.cache,
+                        Some(&self.slack_client),
+                        Access::Write,
+                    )
+                    .await?;
+                channel_id
+            }
+            _ => {
+                resolve_permitted_channel(
+                    &params.channel,
+                    &self.cache,
+                    &self.slack_client,
+                    true,
+                    &self.policy,
+                    Access::Write,
+                )
+                .await?
+            }
+        };
 
-        // Send the message
-        let blocks_vec: Option<Vec<serde_json::Value>> = params.blocks.map(|b| vec![b]);
-        let timestamp = self
-            .slack_client
-            .messages
-            .post_message(
-                &channel_id,
-                params.text.as_deref(),
-                blocks_vec.as_ref(),
-                params.thread_ts.as_deref(),
-                params.reply_broadcast.unwrap_or(false),
-            )
+        // Explicit blocks may be a single block or an array of blocks
+        let explicit_blocks = params.blocks.map(|blocks| match blocks {
+            Value::Array(blocks) => blocks,
+            block => vec![block],
+        });
+
+        let mut parts = match params.text {
+            Some(text) if params.split => {
+                if explicit_blocks.is_some() {
+                    return Err(McpError::InvalidParameter(
+                        "split can't be combined with explicit blocks".to_string(),
+                    ));
+                }
+                self.split_message(&text, params.format)?
+            }
+            text => vec![self.render(text, explicit_blocks, params.format)],
+        };
+
+        // Scan for secrets and PII: block rules stop the send, redact rules rewrite the
+        // content in place, and every finding is logged and reported in the result
+        let findings = merge_findings(
+            parts
+                .iter_mut()
+                .flat_map(|part| self.scanner.scan(part.text.as_mut(), part.blocks.as_mut())),
+        );
+        for finding in &findings {
+            warn!(
+                "Content scanner: {} match(es) of {} in message to {} ({})",
+                finding.count,
+                finding.rule,
+                channel_id,
+                finding.action.as_str()
+            );
+        }
+        let blocked: Vec<&str> = findings
+            .iter()
+            .filter(|f| f.action == ScanAction::Block)
+            .map(|f| f.rule.as_str())
+            .collect();
+        if !blocked.is_empty() {
+            return Err(McpError::PermissionDenied(format!(
+                "Message not sent: it appears to contain {}. Remove it and try again",
+                blocked.join(", ")
+            )));
+        }
+        let with_findings = |mut data: Value| {
+            if !findings.is_empty() {
+                data["scan_findings"] = json!(findings);
+            }
+            data
+        };
+
+        for part in &parts {
+            block_kit::validate(part.text.as_deref(), part.blocks.as_deref())?;
+        }
+
+        let message = OutgoingMessage {
+            channel: channel_id,
+            thread_ts: params.thread_ts,
+            reply_broadcast: params.reply_broadcast.unwrap_or(false),
+            parts,
+            idempotency_key: params.idempotency_key,
+            force: params.force,
+            identity,
+        };
+
+        if self.dry_run {
+            return ToolResponse::data(with_findings(self.poster.dry_run(&message)))
+                .into_response();
+        }
+
+        // High-stakes channels need a person's approval: asked through the client
+        // when it supports elicitation, otherwise held as a draft for confirm_draft
+        if self
+            .policy
+            .requires_confirmation(&message.channel, &self.cache, Some(&self.slack_client))
             .await
-            .mcp_context("Failed to send message")?;
+        {
+            if !self.peer.supports_elicitation() {
+                return ToolResponse::data(with_findings(self.save_draft(&message)?))
+                    .into_response();
+            }
+            self.confirm_with_user(&message).await?;
+        }
 
-        Ok(ToolResponse::data(json!({
-            "channel": channel_id,
-            "ts": timestamp,
-        }))
-        .into_response()?)
+        let result = self.poster.post(&message).await?;
+        ToolResponse::data(with_findings(result)).into_response()
+    }
+}
+
+#[async_trait]
+impl Tool for ConfirmDraftTool {
+    fn description(&self) -> &str {
+        "Post or discard a message that send_message held for confirmation. Only post after the user approved the draft"
+    }
+
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, params: Value) -> McpResult<Value> {
+        let params: ConfirmDraftParams = parse_params(params)?;
+
+        // A dry run leaves the draft in place so it can still be confirmed for real
+        let draft = if self.dry_run {
+            self.cache.get_draft(&params.draft_id)
+        } else {
+            self.cache.take_draft(&params.draft_id)
+        };
+        let draft = draft.mcp_context("Failed to load draft")?.ok_or_else(|| {
+            McpError::NotFound(format!(
+                "Draft '{}' doesn't exist, has expired or was already confirmed",
+                params.draft_id
+            ))
+        })?;
+
+        if params.discard {
+            return ToolResponse::data(json!({
+                "draft_id": params.draft_id,
+                "discarded": !self.dry_run,
+                "dry_run": self.dry_run,
+            }))
+            .into_response();
+        }
+
+        // The policy may have changed since the draft was made
+        let message: OutgoingMessage = serde_json::from_value(draft)?;
+        self.policy
+            .check(
+                &message.channel,
+                &self.cache,
+                Some(&self.poster.slack_client),
+                Access::Write,
+            )
+            .await?;
+
+        if self.dry_run {
+            return ToolResponse::data(self.poster.dry_run(&message)).into_response();
+        }
+
+        let result = self.poster.post(&message).await?;
+        ToolResponse::data(result).into_response()
     }
 }
 
@@ -144,36 +942,110 @@ impl Tool for ReadThreadTool {
         // Parse parameters
         let params: ReadThreadParams = parse_params(params)?;
 
+        let link = params
+            .permalink
+            .as_deref()
+            .map(Permalink::parse)
+            .transpose()?;
+        let channel = param_or_permalink(
+            params.channel,
+            link.as_ref().map(|l| l.channel.clone()),
+            "channel",
+        )?;
+        let thread_ts = param_or_permalink(
+            params.thread_ts,
+            link.as_ref().map(|l| l.thread_root().to_string()),
+            "thread_ts",
+        )?;
+
         // Resolve channel ID if name is provided
-        let channel_id = resolve_channel_id(
-            &params.channel,
+        let channel_id = resolve_permitted_channel(
+            &channel,
             &self.cache,
-            None, // No slack_client needed for this tool
+            &self.slack_client,
+            false,
+            &self.policy,
+            Access::Read,
         )
         .await?;
 
+        // A budget continuation resumes after the last reply already returned
+        let oldest = match params.cursor.as_deref().and_then(Continuation::parse) {
+            Some(Continuation::After(ts)) => Some(ts),
+            _ => None,
+        };
+
         // Get thread replies
-        let (messages, has_more) = self
+        let (mut messages, has_more) = self
             .slack_client
             .messages
-            .get_thread_replies(&channel_id, &params.thread_ts, params.limit)
+            .get_thread_replies(&channel_id, &thread_ts, params.limit, oldest.as_deref())
             .await
             .mcp_context("Failed to read thread")?;
 
+        // Slack always returns the parent; drop it when continuing
+        if let Some(oldest) = &oldest {
+            messages.retain(|m| m.ts.as_str() > oldest.as_str());
+        }
+        filter_noise(&mut messages, params.exclude_noise);
+
         // Use the common formatting utility
-        let result = format_thread_messages(messages, &self.cache).await;
+        let workspace_url = workspace_url(&self.slack_client, &channel_id, &messages).await;
+        let options = FormatOptions::new(
+            params.text_format,
+            params.timezone.as_deref(),
+            params.relative_times,
+            &self.output,
+        )?
+        .with_permalinks(workspace_url, &channel_id);
+        let mut result = format_thread_messages(messages, &self.cache, &options).await;
+
+        let Some(budget) = Budget::new(params.max_tokens, params.max_chars) else {
+            return ToolResponse::paginated(result, has_more, None).into_response();
+        };
+
+        let messages = match result["messages"].take() {
+            Value::Array(messages) => messages,
+            _ => Vec::new(),
+        };
+        let reserved = result.get("thread_info").map_or(0, |i| i.to_string().len());
+        let budgeted = budget.apply(messages, reserved);
+        result["messages"] = json!(budgeted.messages);
 
-        Ok(ToolResponse::paginated(result, has_more, None).into_response()?)
+        let next_cursor = budgeted
+            .resume_after
+            .map(|ts| Continuation::After(ts).to_cursor());
+        Ok(
+            ToolResponse::paginated(result, has_more || next_cursor.is_some(), next_cursor)
+                .with_elided(budgeted.elided)
+                .into_response()?,
+        )
     }
 }
 
 #[derive(Debug, Deserialize)]
 struct GetChannelMessagesParams {
-    channel: String,
+    #[serde(default)]
+    channel: Option<String>,
+    /// Link to any message in the channel, in place of channel
+    #[serde(default)]
+    permalink: Option<String>,
     #[serde(default = "retrieval_default_limit")]
     limit: usize,
     #[serde(default)]
     cursor: Option<String>,
+    #[serde(default)]
+    text_format: TextFormat,
+    #[serde(default)]
+    timezone: Option<String>,
+    #[serde(default)]
+    relative_times: Option<bool>,
+    #[serde(default)]
+    max_tokens: Option<usize>,
+    #[serde(default)]
+    max_chars: Option<usize>,
+    #[serde(default)]
+    exclude_noise: bool,
 }
 
 #[async_trait]
@@ -185,32 +1057,79 @@ impl Tool for GetChannelMessagesTool {
     async fn execute(&self, params: Value) -> McpResult<Value> {
         let params: GetChannelMessagesParams = parse_params(params)?;
 
+        let link = params
+            .permalink
+            .as_deref()
+            .map(Permalink::parse)
+            .transpose()?;
+        let channel = param_or_permalink(params.channel, link.map(|l| l.channel), "channel")?;
+
         // Resolve channel ID if name is provided
-        let channel_id = resolve_channel_id(
-            &params.channel,
+        let channel_id = resolve_permitted_channel(
+            &channel,
             &self.cache,
-            None, // No slack_client needed for this tool
+            &self.slack_client,
+            false,
+            &self.policy,
+            Access::Read,
         )
         .await?;
 
-        let (messages, next_cursor) = self
+        // Budget continuations page by timestamp; anything else is a Slack cursor
+        let (cursor, latest) = match params
+            .cursor
+            .as_deref()
+            .map(|c| (c, Continuation::parse(c)))
+        {
+            Some((_, Some(Continuation::Before(ts)))) => (None, Some(ts)),
+            Some((cursor, _)) => (Some(cursor), None),
+            None => (None, None),
+        };
+
+        let (mut messages, next_cursor) = self
             .slack_client
             .messages
-            .get_channel_messages(&channel_id, params.limit, params.cursor.as_deref())
+            .get_channel_messages(&channel_id, params.limit, cursor, latest.as_deref())
             .await
             .mcp_context("Failed to get channel messages")?;
+        filter_noise(&mut messages, params.exclude_noise);
 
         // Format response using common utility
+        let workspace_url = workspace_url(&self.slack_client, &channel_id, &messages).await;
+        let options = FormatOptions::new(
+            params.text_format,
+            params.timezone.as_deref(),
+            params.relative_times,
+            &self.output,
+        )?
+        .with_permalinks(workspace_url, &channel_id);
         let mut message_results = Vec::new();
         for msg in messages {
-            message_results.push(format_message(msg, &self.cache, true).await);
+            message_results.push(format_message(msg, &self.cache, true, &options).await);
         }
 
+        let Some(budget) = Budget::new(params.max_tokens, params.max_chars) else {
+            return ToolResponse::paginated(
+                json!({"messages": message_results}),
+                next_cursor.is_some(),
+                next_cursor,
+            )
+            .into_response();
+        };
+
+        // History is newest first, so the rest is older than the last message kept
+        let budgeted = budget.apply(message_results, 0);
+        let next_cursor = budgeted
+            .resume_after
+            .map(|ts| Continuation::Before(ts).to_cursor())
+            .or(next_cursor);
+
         Ok(ToolResponse::paginated(
-            json!({"messages": message_results}),
+            json!({"messages": budgeted.messages}),
             next_cursor.is_some(),
             next_cursor,
         )
+        .with_elided(budgeted.elided)
         .into_response()?)
     }
 }
@@ -226,7 +1145,15 @@ impl Tool for ListChannelMembersTool {
         let params: ListChannelMembersParams = parse_params(params)?;
 
         // Resolve channel ID if name is provided
-        let channel_id = resolve_channel_id(&params.channel, &self.cache, None).await?;
+        let channel_id = resolve_permitted_channel(
+            &params.channel,
+            &self.cache,
+            &self.slack_client,
+            false,
+            &self.policy,
+            Access::Read,
+        )
+        .await?;
 
         // Get channel members
         let (member_ids, _) = self
@@ -278,3 +1205,487 @@ impl Tool for ListChannelMembersTool {
         .into_response()?)
     }
 }
+
+#[derive(Debug, Deserialize)]
+struct GetMessageParams {
+    #[serde(default)]
+    permalink: Option<String>,
+    #[serde(default)]
+    channel: Option<String>,
+    #[serde(default)]
+    ts: Option<String>,
+    #[serde(default)]
+    thread_ts: Option<String>,
+    #[serde(default = "default_include_thread")]
+    include_thread: bool,
+    #[serde(default = "retrieval_default_limit")]
+    thread_limit: usize,
+    #[serde(default)]
+    text_format: TextFormat,
+    #[serde(default)]
+    timezone: Option<String>,
+    #[serde(default)]
+    relative_times: Option<bool>,
+}
+
+fn default_include_thread() -> bool {
+    true
+}
+
+#[async_trait]
+impl Tool for GetMessageTool {
+    fn description(&self) -> &str {
+        "Get a single message by permalink or channel/ts, with its thread"
+    }
+
+    async fn execute(&self, params: Value) -> McpResult<Value> {
+        let params: GetMessageParams = parse_params(params)?;
+
+        let link = params
+            .permalink
+            .as_deref()
+            .map(Permalink::parse)
+            .transpose()?;
+        let channel = param_or_permalink(
+            params.channel,
+            link.as_ref().map(|l| l.channel.clone()),
+            "channel",
+        )?;
+        let ts = param_or_permalink(params.ts, link.as_ref().map(|l| l.ts.clone()), "ts")?;
+        let thread_ts = params
+            .thread_ts
+            .or_else(|| link.as_ref().and_then(|l| l.thread_ts.clone()));
+
+        let channel_id = resolve_permitted_channel(
+            &channel,
+            &self.cache,
+            &self.slack_client,
+            false,
+            &self.policy,
+            Access::Read,
+        )
+        .await?;
+
+        let message = self
+            .slack_client
+            .messages
+            .get_message(&channel_id, &ts, thread_ts.as_deref())
+            .await
+            .mcp_context("Failed to get message")?
+            .ok_or_else(|| {
+                McpError::NotFound(format!("Message {} not found in {}", ts, channel))
+            })?;
+
+        let workspace_url = match link {
+            Some(link) => Some(link.workspace_url),
+            None => {
+                self.slack_client
+                    .messages
+                    .workspace_url(&channel_id, &message.ts)
+                    .await
+            }
+        };
+        let options = FormatOptions::new(
+            params.text_format,
+            params.timezone.as_deref(),
+            params.relative_times,
+            &self.output,
+        )?
+        .with_permalinks(workspace_url, &channel_id);
+
+        let thread_root = message.thread_ts.clone();
+        let mut result = json!({
+            "message": format_message(message, &self.cache, true, &options).await,
+        });
+
+        if params.include_thread
+            && let Some(thread_root) = thread_root
+        {
+            let (replies, has_more) = self
+                .slack_client
+                .messages
+                .get_thread_replies(&channel_id, &thread_root, params.thread_limit, None)
+                .await
+                .mcp_context("Failed to read thread")?;
+
+            let mut thread = format_thread_messages(replies, &self.cache, &options).await;
+            if has_more {
+                thread["has_more"] = json!(true);
+            }
+            result["thread"] = thread;
+        }
+
+        ToolResponse::data(result).into_response()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GetMessageContextParams {
+    #[serde(default)]
+    permalink: Option<String>,
+    #[serde(default)]
+    channel: Option<String>,
+    #[serde(default)]
+    ts: Option<String>,
+    #[serde(default)]
+    thread_ts: Option<String>,
+    #[serde(default = "default_context_size")]
+    before: usize,
+    #[serde(default = "default_context_size")]
+    after: usize,
+    #[serde(default)]
+    text_format: TextFormat,
+    #[serde(default)]
+    timezone: Option<String>,
+    #[serde(default)]
+    relative_times: Option<bool>,
+}
+
+fn default_context_size() -> usize {
+    5
+}
+
+/// Most messages returned on each side of the anchor
+const MAX_CONTEXT_SIZE: usize = 100;
+
+/// Replies fetched when the anchor is inside a thread
+const THREAD_CONTEXT_LIMIT: usize = 1000;
+
+#[async_trait]
+impl Tool for GetMessageContextTool {
+    fn description(&self) -> &str {
+        "Get messages before and after a message (by permalink or channel/ts)"
+    }
+
+    async fn execute(&self, params: Value) -> McpResult<Value> {
+        let params: GetMessageContextParams = parse_params(params)?;
+
+        let link = params
+            .permalink
+            .as_deref()
+            .map(Permalink::parse)
+            .transpose()?;
+        let channel = param_or_permalink(
+            params.channel,
+            link.as_ref().map(|l| l.channel.clone()),
+            "channel",
+        )?;
+        let ts = param_or_permalink(params.ts, link.as_ref().map(|l| l.ts.clone()), "ts")?;
+        let thread_ts = params
+            .thread_ts
+            .or_else(|| link.as_ref().and_then(|l| l.thread_ts.clone()))
+            .filter(|thread_ts| *thread_ts != ts);
+        let before = params.before.min(MAX_CONTEXT_SIZE);
+        let after = params.after.min(MAX_CONTEXT_SIZE);
+
+        let channel_id = resolve_permitted_channel(
+            &channel,
+            &self.cache,
+            &self.slack_client,
+            false,
+            &self.policy,
+            Access::Read,
+        )
+        .await?;
+
+        let (messages, more_before, more_after) = match &thread_ts {
+            // Replies only exist inside their thread
+            Some(thread_ts) => {
+                let (replies, has_more) = self
+                    .slack_client
+                    .messages
+                    .get_thread_replies(&channel_id, thread_ts, THREAD_CONTEXT_LIMIT, None)
+                    .await
+                    .mcp_context("Failed to read thread")?;
+                (replies, false, has_more)
+            }
+            None => {
+                let (mut earlier, more_before) = self
+                    .slack_client
+                    .messages
+                    .get_history_range(&channel_id, None, Some(&ts), true, before + 1)
+                    .await
+                    .mcp_context("Failed to get earlier messages")?;
+                // With only `oldest` set, Slack returns the messages closest to it
+                let (later, more_after) = if after > 0 {
+                    self.slack_client
+                        .messages
+                        .get_history_range(&channel_id, Some(&ts), None, false, after)
+                        .await
+                        .mcp_context("Failed to get later messages")?
+                } else {
+                    (Vec::new(), false)
+                };
+                earlier.extend(later);
+                (earlier, more_before, more_after)
+            }
+        };
+
+        let window = context_window(messages, &ts, before, after).ok_or_else(|| {
+            McpError::NotFound(format!(
+                "Message {} not found in {}; for thread replies pass thread_ts or a permalink",
+                ts, channel
+            ))
+        })?;
+
+        let workspace_url = match link {
+            Some(link) => Some(link.workspace_url),
+            None => {
+                self.slack_client
+                    .messages
+                    .workspace_url(&channel_id, &ts)
+                    .await
+            }
+        };
+        let options = FormatOptions::new(
+            params.text_format,
+            params.timezone.as_deref(),
+            params.relative_times,
+            &self.output,
+        )?
+        .with_permalinks(workspace_url, &channel_id);
+
+        let mut message_results = Vec::new();
+        for msg in window.messages {
+            let is_anchor = msg.ts == ts;
+            let mut formatted = format_message(msg, &self.cache, true, &options).await;
+            if is_anchor {
+                formatted["is_anchor"] = json!(true);
+            }
+            message_results.push(formatted);
+        }
+
+        let mut result = json!({
+            "channel_id": channel_id,
+            "anchor_ts": ts,
+            "messages": message_results,
+            "has_more_before": window.has_more_before || more_before,
+            "has_more_after": window.has_more_after || more_after,
+        });
+        if let Some(thread_ts) = thread_ts {
+            result["thread_ts"] = json!(thread_ts);
+        }
+
+        ToolResponse::data(result).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AccessRulesConfig, PolicyConfig, ScannerConfig};
+    use crate::quota::count_api_calls;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn messaging() -> MessagingConfig {
+        MessagingConfig {
+            idempotency_ttl_hours: 24,
+            duplicate_window_seconds: 60,
+            draft_ttl_minutes: 60,
+            confirmation_timeout_seconds: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_confirm_draft_dry_run_does_not_post() {
+        let cache = Arc::new(SqliteCache::new(":memory:").await.unwrap());
+        let draft = OutgoingMessage {
+            channel: "C0123ABCD".to_string(),
+            thread_ts: None,
+            reply_broadcast: false,
+            parts: vec![MessagePart {
+                text: Some("Release 2.0 is out".to_string()),
+                blocks: None,
+            }],
+            idempotency_key: None,
+            force: false,
+            identity: PostIdentity::default(),
+        };
+        cache
+            .save_draft(
+                "draft_1",
+                &draft.channel,
+                &serde_json::to_value(&draft).unwrap(),
+                3600,
+            )
+            .unwrap();
+        let tool = ConfirmDraftTool::new(
+            // Nothing listens here: any call would fail the test
+            SlackClient::for_api_url("http://127.0.0.1:9"),
+            cache.clone(),
+            Arc::new(ChannelPolicy::default()),
+            messaging(),
+            true,
+        );
+
+        let (result, api_calls) =
+            count_api_calls(None, tool.execute(json!({"draft_id": "draft_1"}))).await;
+
+        assert_eq!(api_calls, 0);
+        let response = result.unwrap();
+        assert_eq!(response["dry_run"], true);
+        assert_eq!(response["payload"]["channel"], "C0123ABCD");
+        assert_eq!(response["payload"]["text"], "Release 2.0 is out");
+        // The draft can still be confirmed once the server is out of dry-run mode
+        assert!(cache.get_draft("draft_1").unwrap().is_some());
+    }
+
+    async fn send_message_tool(
+        slack_client: Arc<SlackClient>,
+        policy: PolicyConfig,
+        dry_run: bool,
+    ) -> SendMessageTool {
+        SendMessageTool::new(
+            slack_client,
+            Arc::new(SqliteCache::new(":memory:").await.unwrap()),
+            Arc::new(ChannelPolicy::from_config(&policy).unwrap()),
+            Arc::new(ContentScanner::from_config(&ScannerConfig::default()).unwrap()),
+            Arc::new(ClientPeer::new().0),
+            messaging(),
+            dry_run,
+        )
+    }
+
+    /// Slack API whose conversations.open, called with the user token, returns `dm`
+    async fn slack_with_user_dm(dm: &str) -> MockServer {
+        let slack = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/conversations.open"))
+            .and(header("authorization", "Bearer xoxp-test"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "ok": true,
+                "channel": {"id": dm},
+            })))
+            .expect(1)
+            .mount(&slack)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat.postMessage"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({"ok": true, "ts": "1.000001"})),
+            )
+            .expect(0)
+            .mount(&slack)
+            .await;
+        slack
+    }
+
+    #[tokio::test]
+    async fn test_write_deny_blocks_user_token_dm() {
+        let slack = slack_with_user_dm("D0CEO0001").await;
+        let policy = PolicyConfig {
+            write: AccessRulesConfig {
+                deny: vec!["D0CEO*".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let tool = send_message_tool(SlackClient::for_api_url(&slack.uri()), policy, false).await;
+
+        let err = tool
+            .execute(json!({"channel": "@U0CEO0001", "text": "hi", "as": "user"}))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, McpError::PermissionDenied(_)));
+        assert!(err.to_string().contains("D0CEO0001"));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_user_token_dm_opens_no_conversation() {
+        let slack = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/conversations.open"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "ok": true,
+                "channel": {"id": "D0CEO0001"},
+            })))
+            .expect(0)
+            .mount(&slack)
+            .await;
+        let tool = send_message_tool(
+            SlackClient::for_api_url(&slack.uri()),
+            PolicyConfig::default(),
+            true,
+        )
+        .await;
+
+        let response = tool
+            .execute(json!({"channel": "@U0CEO0001", "text": "hi", "as": "user"}))
+            .await
+            .unwrap();
+
+        assert_eq!(response["as"], "user");
+        assert_eq!(response["payload"]["channel"], "U0CEO0001");
+        assert!(slack.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_retry_resumes_split_message_after_failed_part() {
+        use wiremock::matchers::body_partial_json;
+
+        let slack = MockServer::start().await;
+        let posted = |body: Value, ts: &str| {
+            Mock::given(method("POST"))
+                .and(path("/chat.postMessage"))
+                .and(body_partial_json(body))
+                .respond_with(
+                    ResponseTemplate::new(200).set_body_json(json!({"ok": true, "ts": ts})),
+                )
+        };
+        posted(json!({"text": "one"}), "1.1")
+            .expect(1)
+            .mount(&slack)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat.postMessage"))
+            .and(body_partial_json(json!({"text": "two"})))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"ok": false, "error": "channel_not_found"})),
+            )
+            .up_to_n_times(1)
+            .mount(&slack)
+            .await;
+        for (text, ts) in [("two", "1.2"), ("three", "1.3")] {
+            posted(json!({"text": text, "thread_ts": "1.1"}), ts)
+                .expect(1)
+                .mount(&slack)
+                .await;
+        }
+
+        let poster = MessagePoster {
+            slack_client: SlackClient::for_api_url(&slack.uri()),
+            cache: Arc::new(SqliteCache::new(":memory:").await.unwrap()),
+            messaging: messaging(),
+        };
+        let message = OutgoingMessage {
+            channel: "C0123ABCD".to_string(),
+            thread_ts: None,
+            reply_broadcast: false,
+            parts: ["one", "two", "three"]
+                .into_iter()
+                .map(|text| MessagePart {
+                    text: Some(text.to_string()),
+                    blocks: None,
+                })
+                .collect(),
+            idempotency_key: Some("key-1".to_string()),
+            force: false,
+            identity: PostIdentity::default(),
+        };
+
+        let failed = poster.post(&message).await.unwrap_err().to_string();
+        assert!(failed.contains("Posted 1 of 3 parts"), "{}", failed);
+
+        // The retry posts only the missing parts, into the first part's thread
+        let resumed = poster.post(&message).await.unwrap();
+        assert_eq!(resumed["parts"], json!(["1.1", "1.2", "1.3"]));
+        assert_eq!(resumed["resumed"], true);
+
+        let replayed = poster.post(&message).await.unwrap();
+        assert_eq!(replayed["ts"], "1.1");
+        assert_eq!(replayed["replayed"], true);
+    }
+}
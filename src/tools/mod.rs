@@ -1,8 +1,14 @@
+pub mod audit;
+pub mod block_kit;
+pub mod budget;
 pub mod cache;
+pub mod markdown;
 pub mod message_utils;
 pub mod messages;
+pub mod mrkdwn;
 pub mod response;
 pub mod search;
+pub mod timestamps;
 
 use crate::error::McpResult;
 use async_trait::async_trait;
@@ -14,4 +20,9 @@ pub use response::{IntoToolResponse, ToolResponse};
 pub trait Tool {
     fn description(&self) -> &str;
     async fn execute(&self, params: Value) -> McpResult<Value>;
+
+    /// Whether the tool changes anything in Slack; these are left out in read-only mode
+    fn is_mutating(&self) -> bool {
+        false
+    }
 }
@@ -0,0 +1,359 @@
+use serde::Deserialize;
+
+/// How message text is returned to the client
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextFormat {
+    /// Slack mrkdwn formatting as sent
+    #[default]
+    Raw,
+    /// CommonMark with mentions and links resolved
+    Markdown,
+    /// Plain text with formatting removed
+    Plain,
+}
+
+const CODE_FENCE: &str = "```";
+
+/// A unit of inline text: a single character, or an atom that formatting never splits
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Char(char),
+    Atom(String),
+}
+
+/// Convert Slack mrkdwn to CommonMark or plain text.
+///
+/// `entity` renders the contents of each `<...>` entity (mentions, channels, links);
+/// everything else is handled here: `*bold*`, `_italic_`, `~strike~`, inline code,
+/// code blocks, `>` / `>>>` quotes and the `&lt;` `&gt;` `&amp;` escapes.
+pub fn convert(text: &str, format: TextFormat, entity: &mut dyn FnMut(&str) -> String) -> String {
+    if format == TextFormat::Raw {
+        return text.to_string();
+    }
+
+    let mut parts: Vec<&str> = text.split(CODE_FENCE).collect();
+
+    // An unclosed fence is literal text, so fold it back into the trailing prose
+    let unclosed = if parts.len().is_multiple_of(2) {
+        parts.pop()
+    } else {
+        None
+    };
+
+    let mut out = String::new();
+    for (i, part) in parts.iter().enumerate() {
+        if i % 2 == 0 {
+            out.push_str(&convert_prose(part, format, entity));
+        } else {
+            push_code_block(&mut out, part, format);
+        }
+    }
+    if let Some(rest) = unclosed {
+        out.push_str(&convert_prose(
+            &format!("{}{}", CODE_FENCE, rest),
+            format,
+            entity,
+        ));
+    }
+
+    if format == TextFormat::Markdown {
+        out.trim_matches('\n').to_string()
+    } else {
+        out
+    }
+}
+
+fn push_code_block(out: &mut String, code: &str, format: TextFormat) {
+    let code = unescape(code.trim_matches('\n'));
+    match format {
+        TextFormat::Markdown => {
+            if !out.is_empty() && !out.ends_with('\n') {
+                out.push('\n');
+            }
+            out.push_str(CODE_FENCE);
+            out.push('\n');
+            out.push_str(&code);
+            out.push('\n');
+            out.push_str(CODE_FENCE);
+            out.push('\n');
+        }
+        _ => out.push_str(&code),
+    }
+}
+
+fn convert_prose(text: &str, format: TextFormat, entity: &mut dyn FnMut(&str) -> String) -> String {
+    let mut lines = Vec::new();
+    let mut quote_rest = false;
+
+    for line in text.split('\n') {
+        let (quoted, body) = if quote_rest {
+            (true, line)
+        } else if let Some(rest) = strip_marker(line, &["&gt;&gt;&gt;", ">>>"]) {
+            // ">>>" quotes everything that follows it
+            quote_rest = true;
+            (true, rest)
+        } else if let Some(rest) = strip_marker(line, &["&gt;", ">"]) {
+            (true, rest)
+        } else {
+            (false, line)
+        };
+
+        let body = convert_inline(body, format, entity);
+        lines.push(match (quoted, format) {
+            (true, TextFormat::Markdown) if body.is_empty() => ">".to_string(),
+            (true, TextFormat::Markdown) => format!("> {}", body),
+            _ => body,
+        });
+    }
+
+    lines.join("\n")
+}
+
+fn strip_marker<'a>(line: &'a str, markers: &[&str]) -> Option<&'a str> {
+    markers.iter().find_map(|marker| {
+        line.strip_prefix(marker)
+            .map(|rest| rest.strip_prefix(' ').unwrap_or(rest))
+    })
+}
+
+fn convert_inline(
+    line: &str,
+    format: TextFormat,
+    entity: &mut dyn FnMut(&str) -> String,
+) -> String {
+    let tokens = tokenize(line, format, entity);
+    render(&tokens, format)
+}
+
+/// Split a line into characters and atoms, resolving entities, inline code and escapes
+fn tokenize(line: &str, format: TextFormat, entity: &mut dyn FnMut(&str) -> String) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = line;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '<'
+            && let Some(end) = rest.find('>')
+            && end > 1
+            && !rest[1..].starts_with(char::is_whitespace)
+            && !rest[1..end].contains('<')
+        {
+            tokens.push(Token::Atom(entity(&rest[1..end])));
+            rest = &rest[end + 1..];
+            continue;
+        }
+
+        if c == '`'
+            && let Some(end) = rest[1..].find('`')
+            && end > 0
+        {
+            let code = unescape(&rest[1..end + 1]);
+            tokens.push(Token::Atom(match format {
+                TextFormat::Markdown => format!("`{}`", code),
+                _ => code,
+            }));
+            rest = &rest[end + 2..];
+            continue;
+        }
+
+        if c == '&'
+            && let Some((escaped, len)) = [("&lt;", '<'), ("&gt;", '>'), ("&amp;", '&')]
+                .iter()
+                .find(|(seq, _)| rest.starts_with(seq))
+                .map(|(seq, ch)| (*ch, seq.len()))
+        {
+            // Escaped characters are literal text, never formatting markers
+            tokens.push(Token::Atom(escaped.to_string()));
+            rest = &rest[len..];
+            continue;
+        }
+
+        tokens.push(Token::Char(c));
+        rest = &rest[c.len_utf8()..];
+    }
+
+    tokens
+}
+
+fn is_marker(c: char) -> bool {
+    matches!(c, '*' | '_' | '~')
+}
+
+fn is_word_char(token: Option<&Token>) -> bool {
+    matches!(token, Some(Token::Char(c)) if c.is_alphanumeric())
+}
+
+fn is_space(token: Option<&Token>) -> bool {
+    matches!(token, Some(Token::Char(c)) if c.is_whitespace())
+}
+
+/// Slack only treats a marker as opening when it starts a word
+fn can_open(tokens: &[Token], i: usize) -> bool {
+    let prev = i.checked_sub(1).and_then(|p| tokens.get(p));
+    let next = tokens.get(i + 1);
+    !is_word_char(prev) && next.is_some() && !is_space(next)
+}
+
+/// ...and as closing when it ends one
+fn can_close(tokens: &[Token], open: usize, j: usize) -> bool {
+    j > open + 1 && !is_space(tokens.get(j - 1)) && !is_word_char(tokens.get(j + 1))
+}
+
+fn render(tokens: &[Token], format: TextFormat) -> String {
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        match &tokens[i] {
+            Token::Atom(text) => out.push_str(text),
+            Token::Char(marker) if is_marker(*marker) && can_open(tokens, i) => {
+                let close = (i + 2..tokens.len())
+                    .find(|&j| tokens[j] == Token::Char(*marker) && can_close(tokens, i, j));
+
+                if let Some(close) = close {
+                    let inner = render(&tokens[i + 1..close], format);
+                    match (format, marker) {
+                        (TextFormat::Markdown, '*') => out.push_str(&format!("**{}**", inner)),
+                        (TextFormat::Markdown, '_') => out.push_str(&format!("_{}_", inner)),
+                        (TextFormat::Markdown, _) => out.push_str(&format!("~~{}~~", inner)),
+                        _ => out.push_str(&inner),
+                    }
+                    i = close + 1;
+                    continue;
+                }
+                out.push(*marker);
+            }
+            Token::Char(c) => out.push(*c),
+        }
+        i += 1;
+    }
+
+    out
+}
+
+/// Escape `&`, `<` and `>`, which Slack reserves for control sequences
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Undo Slack's HTML escaping of `<`, `>` and `&`
+pub fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn to_markdown(text: &str) -> String {
+        convert(text, TextFormat::Markdown, &mut |e| format!("<{}>", e))
+    }
+
+    fn to_plain(text: &str) -> String {
+        convert(text, TextFormat::Plain, &mut |e| format!("<{}>", e))
+    }
+
+    #[rstest]
+    #[case("*bold*", "**bold**")]
+    #[case("_italic_", "_italic_")]
+    #[case("~strike~", "~~strike~~")]
+    #[case("a *bold* word", "a **bold** word")]
+    #[case("*bold _italic_ inside*", "**bold _italic_ inside**")]
+    #[case("_*both*_", "_**both**_")]
+    #[case("~*struck bold*~", "~~**struck bold**~~")]
+    #[case("snake_case_name", "snake_case_name")]
+    #[case("2 * 3 * 4", "2 * 3 * 4")]
+    #[case("a*b*c", "a*b*c")]
+    #[case("* not bold*", "* not bold*")]
+    #[case("*unclosed", "*unclosed")]
+    #[case("(*paren*)", "(**paren**)")]
+    #[case("*bold*.", "**bold**.")]
+    #[case("`*not bold*`", "`*not bold*`")]
+    #[case("1 &lt; 2 &amp;&amp; 3 &gt; 2", "1 < 2 && 3 > 2")]
+    #[case("&lt;b&gt;not html&lt;/b&gt;", "<b>not html</b>")]
+    #[case("*a &amp; b*", "**a & b**")]
+    fn test_inline_to_markdown(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(to_markdown(input), expected);
+    }
+
+    #[rstest]
+    #[case("&gt; quoted", "> quoted")]
+    #[case("&gt; *bold* quote\nafter", "> **bold** quote\nafter")]
+    #[case("&gt;&gt;&gt; all\nof this", "> all\n> of this")]
+    #[case("&gt;\n&gt; gap", ">\n> gap")]
+    fn test_quotes_to_markdown(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(to_markdown(input), expected);
+    }
+
+    #[rstest]
+    #[case("```let x = 1;```", "```\nlet x = 1;\n```")]
+    #[case("before ```code``` after", "before \n```\ncode\n```\n after")]
+    #[case("```\nfn main() {}\n```", "```\nfn main() {}\n```")]
+    #[case("```*not* _formatted_ &lt;T&gt;```", "```\n*not* _formatted_ <T>\n```")]
+    #[case("```a```\n*b*", "```\na\n```\n\n**b**")]
+    #[case("unclosed ```fence *x*", "unclosed ```fence **x**")]
+    fn test_code_blocks_to_markdown(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(to_markdown(input), expected);
+    }
+
+    #[rstest]
+    #[case("*bold* _it_ ~st~", "bold it st")]
+    #[case("*bold _nested_*", "bold nested")]
+    #[case("&gt; quote", "quote")]
+    #[case("`code` and ```block```", "code and block")]
+    #[case("a &amp; b", "a & b")]
+    fn test_to_plain(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(to_plain(input), expected);
+    }
+
+    #[test]
+    fn test_entities_are_atomic() {
+        let mut seen = Vec::new();
+        let result = convert(
+            "*hi <@U1>* see <https://a.com/x_y_z|docs_page>",
+            TextFormat::Markdown,
+            &mut |e| {
+                seen.push(e.to_string());
+                format!("[{}]", e)
+            },
+        );
+
+        assert_eq!(result, "**hi [@U1]** see [https://a.com/x_y_z|docs_page]");
+        assert_eq!(seen, vec!["@U1", "https://a.com/x_y_z|docs_page"]);
+    }
+
+    #[test]
+    fn test_raw_is_unchanged() {
+        let text = "*bold* &lt;@U1&gt; <@U1>";
+        let result = convert(text, TextFormat::Raw, &mut |_| unreachable!());
+        assert_eq!(result, text);
+    }
+
+    #[rstest]
+    #[case("*bold _nested_ ~deep~*")]
+    #[case("&gt; *quoted* line\nplain `code`")]
+    #[case("```fn x() { *y* }```")]
+    #[case("mixed *a* _b_ ~c~ &amp; <@U1>")]
+    #[case("*ping <@U1> about _<#C1|dev-team>_*")]
+    #[case("see <https://x.com/a_b|the _docs_> and <https://y.com>")]
+    #[case("~*_all three_ nested*~ then `<@U2>` in code")]
+    #[case("&gt;&gt;&gt; *release* by <@U1>\n_notes_: <https://x.com|changelog>")]
+    fn test_markdown_then_plain_matches_plain(#[case] input: &str) {
+        // Stripping the CommonMark output again must not lose or add any text
+        let markdown = to_markdown(input);
+        let plain = to_plain(input);
+        let stripped: String = markdown
+            .lines()
+            .map(|line| line.strip_prefix("> ").unwrap_or(line))
+            .collect::<String>()
+            .replace("**", "")
+            .replace("~~", "")
+            .replace(['_', '`'], "");
+        assert_eq!(stripped, plain.replace(['_', '\n'], ""));
+    }
+}
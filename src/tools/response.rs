@@ -1,5 +1,33 @@
-use crate::error::McpResult;
-use serde_json::Value;
+use super::budget::Elided;
+use crate::config::RedactionConfig;
+use crate::error::{McpError, McpResult};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+const EMAIL_PATTERN: &str = r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}";
+/// Separated digit groups such as `010-1234-5678`, `+1 (555) 123-4567` or `555.123.4567`;
+/// unseparated digit runs like Slack timestamps are left alone
+const PHONE_PATTERN: &str =
+    r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{2,4}\)|\b\d{2,4})[\s.-]\d{3,4}[\s.-]\d{4}\b";
+
+/// Identifier fields that are never redacted
+const REDACTION_SKIP_KEYS: &[&str] = &["ts", "thread_ts", "cursor", "next_cursor", "permalink"];
+
+/// How tool results are serialized into MCP text content
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// Pretty-printed JSON
+    #[default]
+    Json,
+    /// JSON without whitespace
+    JsonCompact,
+    /// Tab-separated rows for the result list, other fields as `key: value` lines
+    Tsv,
+    /// Markdown table for the result list, other fields as `key: value` lines
+    MarkdownTable,
+}
 
 /// Simplified unified response structure for all tools
 #[derive(Debug)]
@@ -21,6 +49,9 @@ pub struct ResponseMetadata {
 
     /// Total count (only when different from returned items)
     pub total_count: Option<usize>,
+
+    /// What an output budget removed (only when something was)
+    pub elided: Option<Elided>,
 }
 
 impl ToolResponse {
@@ -40,10 +71,26 @@ impl ToolResponse {
                 has_more: Some(has_more),
                 next_cursor,
                 total_count: None,
+                elided: None,
             }),
         }
     }
 
+    /// Report what an output budget removed from the response
+    pub fn with_elided(mut self, elided: Elided) -> Self {
+        if elided.is_empty() {
+            return self;
+        }
+        let metadata = self.metadata.get_or_insert(ResponseMetadata {
+            has_more: None,
+            next_cursor: None,
+            total_count: None,
+            elided: None,
+        });
+        metadata.elided = Some(elided);
+        self
+    }
+
     /// Convert to JSON Value for MCP protocol
     pub fn into_json(self) -> Value {
         if let Some(metadata) = self.metadata {
@@ -59,6 +106,9 @@ impl ToolResponse {
             if let Some(count) = metadata.total_count {
                 result["total_count"] = count.into();
             }
+            if let Some(elided) = metadata.elided {
+                result["elided"] = serde_json::json!(elided);
+            }
 
             result
         } else {
@@ -67,6 +117,174 @@ impl ToolResponse {
     }
 }
 
+impl OutputFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::JsonCompact => "json_compact",
+            Self::Tsv => "tsv",
+            Self::MarkdownTable => "markdown_table",
+        }
+    }
+
+    /// Render a tool result.
+    ///
+    /// The tabular formats lay out the result's list of objects (the result itself,
+    /// or its first non-empty array of objects such as `messages` or `users`) as rows,
+    /// with one column per key. Results without such a list fall back to compact JSON.
+    pub fn render(self, value: &Value) -> McpResult<String> {
+        let text = match self {
+            Self::Json => serde_json::to_string_pretty(value)?,
+            Self::JsonCompact => serde_json::to_string(value)?,
+            Self::Tsv | Self::MarkdownTable => match split_table(value) {
+                Some((rows, fields)) => self.render_table(&rows, &fields),
+                None => serde_json::to_string(value)?,
+            },
+        };
+        Ok(text)
+    }
+
+    fn render_table(self, rows: &[&Map<String, Value>], fields: &[(&String, &Value)]) -> String {
+        let mut columns: Vec<&String> = Vec::new();
+        for row in rows {
+            for key in row.keys() {
+                if !columns.contains(&key) {
+                    columns.push(key);
+                }
+            }
+        }
+
+        let cell = |value: Option<&Value>| -> String {
+            let text = match value {
+                None | Some(Value::Null) => String::new(),
+                Some(Value::String(s)) => s.clone(),
+                Some(other) => other.to_string(),
+            };
+            match self {
+                Self::Tsv => text
+                    .replace('\\', "\\\\")
+                    .replace('\t', "\\t")
+                    .replace('\n', "\\n")
+                    .replace('\r', "\\r"),
+                _ => text.replace('|', "\\|").replace('\n', "<br>"),
+            }
+        };
+
+        let mut lines: Vec<String> = Vec::new();
+        let header: Vec<String> = columns
+            .iter()
+            .map(|c| cell(Some(&Value::from(c.as_str()))))
+            .collect();
+        let body = rows.iter().map(|row| {
+            columns
+                .iter()
+                .map(|c| cell(row.get(*c)))
+                .collect::<Vec<_>>()
+        });
+
+        match self {
+            Self::Tsv => {
+                lines.push(header.join("\t"));
+                lines.extend(body.map(|cells| cells.join("\t")));
+            }
+            _ => {
+                lines.push(format!("| {} |", header.join(" | ")));
+                lines.push(format!("|{}|", vec!["---"; columns.len()].join("|")));
+                lines.extend(body.map(|cells| format!("| {} |", cells.join(" | "))));
+            }
+        }
+
+        if !fields.is_empty() {
+            lines.push(String::new());
+            lines.extend(fields.iter().map(|(key, value)| match value {
+                Value::String(s) => format!("{}: {}", key, s),
+                other => format!("{}: {}", key, other),
+            }));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// A non-empty array whose elements are all objects
+fn as_rows(value: &Value) -> Option<Vec<&Map<String, Value>>> {
+    let rows: Vec<_> = value
+        .as_array()?
+        .iter()
+        .map(Value::as_object)
+        .collect::<Option<_>>()?;
+    (!rows.is_empty()).then_some(rows)
+}
+
+/// Table rows plus the remaining top-level fields
+type Table<'a> = (Vec<&'a Map<String, Value>>, Vec<(&'a String, &'a Value)>);
+
+/// Split a result into its table rows and the remaining top-level fields
+fn split_table(value: &Value) -> Option<Table<'_>> {
+    if let Some(rows) = as_rows(value) {
+        return Some((rows, Vec::new()));
+    }
+
+    let object = value.as_object()?;
+    let (table_key, rows) = object
+        .iter()
+        .find_map(|(k, v)| as_rows(v).map(|rows| (k, rows)))?;
+    let fields = object.iter().filter(|(k, _)| *k != table_key).collect();
+    Some((rows, fields))
+}
+
+/// Masks emails, phone numbers and configured patterns in every string of a tool result
+/// (user profiles, message text, search results) before it is rendered
+pub struct Redactor {
+    rules: Vec<(Regex, &'static str)>,
+}
+
+impl Redactor {
+    pub fn from_config(config: &RedactionConfig) -> McpResult<Self> {
+        let compile = |pattern: &str| {
+            Regex::new(pattern).map_err(|e| {
+                McpError::InvalidParameter(format!(
+                    "Invalid redaction pattern '{}': {}",
+                    pattern, e
+                ))
+            })
+        };
+
+        let mut rules = Vec::new();
+        if config.emails {
+            rules.push((compile(EMAIL_PATTERN)?, "[email]"));
+        }
+        if config.phone_numbers {
+            rules.push((compile(PHONE_PATTERN)?, "[phone]"));
+        }
+        for pattern in &config.patterns {
+            rules.push((compile(pattern)?, "[redacted]"));
+        }
+        Ok(Self { rules })
+    }
+
+    pub fn redact(&self, value: &mut Value) {
+        if self.rules.is_empty() {
+            return;
+        }
+        match value {
+            Value::String(s) => {
+                for (pattern, mask) in &self.rules {
+                    if pattern.is_match(s) {
+                        *s = pattern.replace_all(s, *mask).into_owned();
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|v| self.redact(v)),
+            Value::Object(fields) => fields
+                .iter_mut()
+                .filter(|(key, _)| !REDACTION_SKIP_KEYS.contains(&key.as_str()))
+                .for_each(|(_, v)| self.redact(v)),
+            _ => {}
+        }
+    }
+}
+
 /// Helper trait for converting tool results to responses
 pub trait IntoToolResponse {
     fn into_response(self) -> McpResult<Value>;
@@ -81,6 +299,7 @@ impl IntoToolResponse for ToolResponse {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rstest::rstest;
     use serde_json::json;
 
     #[test]
@@ -127,6 +346,7 @@ mod tests {
                 has_more: Some(true),
                 next_cursor: None,
                 total_count: Some(100),
+                elided: None,
             }),
         };
 
@@ -192,6 +412,7 @@ mod tests {
                 has_more: None,
                 next_cursor: None,
                 total_count: None,
+                elided: None,
             }),
         };
 
@@ -201,4 +422,170 @@ mod tests {
         assert!(result.get("next_cursor").is_none());
         assert!(result.get("total_count").is_none());
     }
+
+    #[test]
+    fn test_tool_response_with_elided() {
+        let elided = Elided {
+            truncated_messages: 2,
+            omitted_messages: 5,
+            ..Default::default()
+        };
+        let response =
+            ToolResponse::paginated(json!({"messages": []}), true, None).with_elided(elided);
+
+        let result = response.into_json();
+        assert_eq!(result["has_more"], json!(true));
+        assert_eq!(
+            result["elided"],
+            json!({"truncated_messages": 2, "omitted_messages": 5})
+        );
+    }
+
+    #[test]
+    fn test_tool_response_with_empty_elided_adds_nothing() {
+        let response = ToolResponse::data(json!({"ok": true})).with_elided(Elided::default());
+
+        assert!(response.metadata.is_none());
+    }
+
+    #[test]
+    fn test_output_format_json_variants() {
+        let value = json!({"a": 1});
+
+        assert_eq!(
+            OutputFormat::Json.render(&value).unwrap(),
+            "{\n  \"a\": 1\n}"
+        );
+        assert_eq!(
+            OutputFormat::JsonCompact.render(&value).unwrap(),
+            r#"{"a":1}"#
+        );
+    }
+
+    #[test]
+    fn test_output_format_tsv_from_array() {
+        let value = json!([
+            {"id": "U1", "name": "alice", "is_bot": true},
+            {"id": "U2", "name": "bob\tsmith", "score": 0.5}
+        ]);
+
+        assert_eq!(
+            OutputFormat::Tsv.render(&value).unwrap(),
+            "id\tis_bot\tname\tscore\nU1\ttrue\talice\t\nU2\t\tbob\\tsmith\t0.5"
+        );
+    }
+
+    #[test]
+    fn test_output_format_tsv_keeps_other_fields() {
+        let value = json!({
+            "messages": [{"ts": "1.0", "text": "line one\nline two"}],
+            "has_more": true,
+            "next_cursor": "before:1.0",
+        });
+
+        assert_eq!(
+            OutputFormat::Tsv.render(&value).unwrap(),
+            "text\tts\nline one\\nline two\t1.0\n\nhas_more: true\nnext_cursor: before:1.0"
+        );
+    }
+
+    #[test]
+    fn test_output_format_markdown_table() {
+        let value = json!({
+            "members": [
+                {"id": "U1", "name": "a|b"},
+                {"id": "U2", "name": "multi\nline", "tags": ["x"]}
+            ],
+            "count": 2,
+        });
+
+        assert_eq!(
+            OutputFormat::MarkdownTable.render(&value).unwrap(),
+            "| id | name | tags |\n|---|---|---|\n| U1 | a\\|b |  |\n| U2 | multi<br>line | [\"x\"] |\n\ncount: 2"
+        );
+    }
+
+    #[test]
+    fn test_output_format_tabular_falls_back_to_compact_json() {
+        let value = json!({"channel": "C1", "ts": "1.0", "messages": []});
+
+        assert_eq!(
+            OutputFormat::Tsv.render(&value).unwrap(),
+            r#"{"channel":"C1","messages":[],"ts":"1.0"}"#
+        );
+    }
+
+    #[test]
+    fn test_output_format_deserialize() {
+        let format: OutputFormat = serde_json::from_value(json!("markdown_table")).unwrap();
+        assert_eq!(format, OutputFormat::MarkdownTable);
+        assert!(serde_json::from_value::<OutputFormat>(json!("xml")).is_err());
+    }
+
+    fn redactor(emails: bool, phone_numbers: bool, patterns: &[&str]) -> Redactor {
+        Redactor::from_config(&RedactionConfig {
+            emails,
+            phone_numbers,
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            index_emails: true,
+        })
+        .unwrap()
+    }
+
+    #[rstest]
+    #[case("mail kim.lee+ops@example.co.kr", "mail [email]")]
+    #[case("<mailto:kim@example.com|kim@example.com>", "<mailto:[email]|[email]>")]
+    #[case("call 010-1234-5678 now", "call [phone] now")]
+    #[case("+1 (555) 123-4567", "[phone]")]
+    #[case("+82 10-1234-5678", "[phone]")]
+    #[case("555.123.4567", "[phone]")]
+    #[case("deployed 1700000000.000100", "deployed 1700000000.000100")]
+    #[case("on 2024-01-15 at 10:30", "on 2024-01-15 at 10:30")]
+    #[case("from 192.168.1.100", "from 192.168.1.100")]
+    fn test_redactor_masks_emails_and_phones(#[case] input: &str, #[case] expected: &str) {
+        let mut value = json!(input);
+        redactor(true, true, &[]).redact(&mut value);
+        assert_eq!(value, json!(expected));
+    }
+
+    #[test]
+    fn test_redactor_walks_results_and_skips_identifiers() {
+        let mut value = json!({
+            "users": [{"id": "U1", "real_name": "Kim", "email": "kim@example.com"}],
+            "messages": [{"ts": "1700000000.000100", "text": "ticket CUST-12345 for kim@example.com"}],
+            "next_cursor": "a@b.cd",
+        });
+
+        redactor(true, false, &[r"CUST-\d+"]).redact(&mut value);
+
+        assert_eq!(value["users"][0]["email"], "[email]");
+        assert_eq!(value["users"][0]["real_name"], "Kim");
+        assert_eq!(
+            value["messages"][0]["text"],
+            "ticket [redacted] for [email]"
+        );
+        assert_eq!(value["messages"][0]["ts"], "1700000000.000100");
+        assert_eq!(value["next_cursor"], "a@b.cd");
+    }
+
+    #[test]
+    fn test_redactor_disabled_by_default() {
+        let mut value = json!({"text": "kim@example.com 010-1234-5678"});
+        Redactor::from_config(&RedactionConfig::default())
+            .unwrap()
+            .redact(&mut value);
+        assert_eq!(value["text"], "kim@example.com 010-1234-5678");
+    }
+
+    #[test]
+    fn test_redactor_rejects_invalid_pattern() {
+        let config = RedactionConfig {
+            patterns: vec!["(unclosed".to_string()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            Redactor::from_config(&config),
+            Err(McpError::InvalidParameter(_))
+        ));
+    }
 }
@@ -3,12 +3,16 @@ use serde::Deserialize;
 use serde_json::{Value, json};
 use std::sync::Arc;
 
-use super::message_utils::format_message;
+use super::budget::{Budget, Continuation};
+use super::message_utils::{FormatOptions, format_message};
+use super::mrkdwn::TextFormat;
 use super::{IntoToolResponse, Tool, ToolResponse};
 use crate::cache::SqliteCache;
+use crate::config::OutputConfig;
 use crate::error::{IntoMcpError, McpResult};
+use crate::policy::{Access, ChannelPolicy, ChannelRef};
 use crate::slack::SlackClient;
-use crate::utils::parse_params;
+use crate::utils::{is_channel_id, parse_params};
 
 pub struct SearchUsersTool {
     cache: Arc<SqliteCache>,
@@ -16,11 +20,14 @@ pub struct SearchUsersTool {
 
 pub struct SearchChannelsTool {
     cache: Arc<SqliteCache>,
+    policy: Arc<ChannelPolicy>,
 }
 
 pub struct SearchMessagesTool {
     slack_client: Arc<SlackClient>,
     cache: Arc<SqliteCache>,
+    output: OutputConfig,
+    policy: Arc<ChannelPolicy>,
 }
 
 impl SearchUsersTool {
@@ -30,16 +37,23 @@ impl SearchUsersTool {
 }
 
 impl SearchChannelsTool {
-    pub fn new(cache: Arc<SqliteCache>) -> Self {
-        Self { cache }
+    pub fn new(cache: Arc<SqliteCache>, policy: Arc<ChannelPolicy>) -> Self {
+        Self { cache, policy }
     }
 }
 
 impl SearchMessagesTool {
-    pub fn new(slack_client: Arc<SlackClient>, cache: Arc<SqliteCache>) -> Self {
+    pub fn new(
+        slack_client: Arc<SlackClient>,
+        cache: Arc<SqliteCache>,
+        output: OutputConfig,
+        policy: Arc<ChannelPolicy>,
+    ) -> Self {
         Self {
             slack_client,
             cache,
+            output,
+            policy,
         }
     }
 }
@@ -51,6 +65,8 @@ struct SearchUsersParams {
     limit: usize,
     #[serde(default)]
     include_bots: bool,
+    #[serde(default)]
+    include_deleted: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -58,6 +74,8 @@ struct SearchChannelsParams {
     query: String,
     #[serde(default = "default_limit")]
     limit: usize,
+    #[serde(default)]
+    include_archived: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -69,12 +87,32 @@ struct SearchMessagesParams {
     from_user: Option<String>,
     #[serde(default = "default_limit")]
     limit: usize,
+    #[serde(default)]
+    cursor: Option<String>,
+    #[serde(default)]
+    text_format: TextFormat,
+    #[serde(default)]
+    timezone: Option<String>,
+    #[serde(default)]
+    relative_times: Option<bool>,
+    #[serde(default)]
+    max_tokens: Option<usize>,
+    #[serde(default)]
+    max_chars: Option<usize>,
 }
 
+/// Slack returns at most this many search results per page
+const SEARCH_MAX_COUNT: usize = 100;
+
 fn default_limit() -> usize {
     10
 }
 
+/// Round relevance scores to two decimals to keep responses compact
+fn round_score(score: f64) -> f64 {
+    (score * 100.0).round() / 100.0
+}
+
 #[async_trait]
 impl Tool for SearchUsersTool {
     fn description(&self) -> &str {
@@ -86,17 +124,27 @@ impl Tool for SearchUsersTool {
 
         let users = self
             .cache
-            .search_users(&params.query, params.limit, params.include_bots)
+            .search_users(
+                &params.query,
+                params.limit,
+                params.include_bots,
+                params.include_deleted,
+            )
             .mcp_context("Failed to search users")?;
 
         // Format response with essential user fields
         let user_results: Vec<Value> = users
             .into_iter()
-            .map(|user| {
+            .map(|m| {
+                let user = &m.item;
                 let mut result = json!({
                     "id": user.id,
                     "name": user.name,
+                    "score": round_score(m.score),
                 });
+                if let Some(field) = m.matched_field {
+                    result["matched_field"] = json!(field);
+                }
 
                 // Add only essential optional fields
                 if user.is_bot {
@@ -114,6 +162,9 @@ impl Tool for SearchUsersTool {
                     // Only include if non-empty and different from name
                     result["display_name"] = json!(display_name);
                 }
+                if let Some(tz) = &user.tz {
+                    result["tz"] = json!(tz);
+                }
                 if user.deleted {
                     result["deleted"] = json!(true);
                 }
@@ -135,19 +186,42 @@ impl Tool for SearchChannelsTool {
     async fn execute(&self, params: Value) -> McpResult<Value> {
         let params: SearchChannelsParams = parse_params(params)?;
 
-        let channels = self
-            .cache
-            .search_channels(&params.query, params.limit)
-            .mcp_context("Failed to search channels")?;
+        // Hide channels the policy can't read before limiting, fetching more
+        // matches until the page is full or the search runs out
+        let mut fetch = params.limit;
+        let channels = loop {
+            let matches = self
+                .cache
+                .search_channels(&params.query, fetch, params.include_archived)
+                .mcp_context("Failed to search channels")?;
+            let exhausted = matches.len() < fetch;
+            let permitted: Vec<_> = matches
+                .into_iter()
+                .filter(|m| {
+                    self.policy
+                        .permits(&ChannelRef::from(&m.item), Access::Read)
+                })
+                .take(params.limit)
+                .collect();
+            if permitted.len() >= params.limit || exhausted {
+                break permitted;
+            }
+            fetch *= 2;
+        };
 
         // Format response with useful channel fields
         let channel_results: Vec<Value> = channels
             .into_iter()
-            .map(|channel| {
+            .map(|m| {
+                let channel = &m.item;
                 let mut result = json!({
                     "id": channel.id,
                     "name": channel.name,
+                    "score": round_score(m.score),
                 });
+                if let Some(field) = m.matched_field {
+                    result["matched_field"] = json!(field);
+                }
 
                 // Only include boolean flags when true (omit false to save tokens)
                 if channel.is_private {
@@ -189,7 +263,7 @@ impl Tool for SearchMessagesTool {
         // Resolve channel ID to name if needed for search API
         let channel_for_search = if let Some(channel) = &params.channel {
             // If it's a channel ID, resolve to channel name
-            if channel.starts_with('C') || channel.starts_with('G') {
+            if is_channel_id(channel) {
                 // Try to find channel name from cache
                 let channels = self
                     .cache
@@ -208,6 +282,19 @@ impl Tool for SearchMessagesTool {
             None
         };
 
+        // A budget continuation re-runs the search and skips results already returned
+        let offset = match params.cursor.as_deref().and_then(Continuation::parse) {
+            Some(Continuation::Offset(offset)) => offset.min(SEARCH_MAX_COUNT),
+            _ => 0,
+        };
+
+        // Results the policy hides don't count toward the page, so ask for as many
+        // as Slack returns when it hides any
+        let search_count = if self.policy.restricts(Access::Read) {
+            SEARCH_MAX_COUNT
+        } else {
+            (offset + params.limit).min(SEARCH_MAX_COUNT)
+        };
         let messages = self
             .slack_client
             .messages
@@ -215,17 +302,98 @@ impl Tool for SearchMessagesTool {
                 &params.query,
                 channel_for_search.as_deref(),
                 params.from_user.as_deref(),
-                params.limit,
+                search_count,
             )
             .await
             .mcp_context("Failed to search messages")?;
 
         // Format response using common utility
+        let options = FormatOptions::new(
+            params.text_format,
+            params.timezone.as_deref(),
+            params.relative_times,
+            &self.output,
+        )?;
         let mut message_results = Vec::new();
-        for msg in messages {
-            message_results.push(format_message(msg, &self.cache, true).await);
+        // Policy filtering happens before the offset so continuations stay aligned
+        let permitted = messages.into_iter().filter(|msg| {
+            msg.channel.as_ref().is_none_or(|channel| {
+                self.policy.is_permitted(
+                    &channel.id,
+                    Some(&channel.name),
+                    &self.cache,
+                    Access::Read,
+                )
+            })
+        });
+        for msg in permitted.skip(offset).take(params.limit) {
+            message_results.push(format_message(msg, &self.cache, true, &options).await);
         }
 
-        Ok(ToolResponse::data(json!(message_results)).into_response()?)
+        let Some(budget) = Budget::new(params.max_tokens, params.max_chars) else {
+            return ToolResponse::data(json!(message_results)).into_response();
+        };
+
+        // Budgeted results are wrapped in an object so pagination fields can be added
+        let fetched = message_results.len();
+        let budgeted = budget.apply(message_results, 0);
+        let returned = fetched - budgeted.elided.omitted_messages;
+        let next_cursor = budgeted
+            .resume_after
+            .is_some()
+            .then(|| Continuation::Offset(offset + returned).to_cursor());
+
+        Ok(ToolResponse::paginated(
+            json!({"messages": budgeted.messages}),
+            next_cursor.is_some(),
+            next_cursor,
+        )
+        .with_elided(budgeted.elided)
+        .into_response()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AccessRulesConfig, PolicyConfig};
+    use crate::slack::SlackChannel;
+
+    fn channel(id: &str, name: &str) -> SlackChannel {
+        serde_json::from_value(json!({"id": id, "name": name, "is_channel": true})).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_search_channels_fills_page_past_hidden_channels() {
+        let cache = Arc::new(SqliteCache::new(":memory:").await.unwrap());
+        let mut channels: Vec<SlackChannel> = (0..5)
+            .map(|i| channel(&format!("C0SECRET{i}"), &format!("dev-x{i}")))
+            .collect();
+        channels.push(channel("C0TEAM001", "dev-team"));
+        channels.push(channel("C0TOOLS01", "dev-tools"));
+        cache.save_channels(channels).await.unwrap();
+
+        let policy = ChannelPolicy::from_config(&PolicyConfig {
+            read: AccessRulesConfig {
+                allow: vec![],
+                deny: vec!["dev-x*".to_string()],
+            },
+            ..PolicyConfig::default()
+        })
+        .unwrap();
+        let tool = SearchChannelsTool::new(cache, Arc::new(policy));
+
+        // The hidden channels score higher, so a single fetch of `limit` finds only them
+        let result = tool
+            .execute(json!({"query": "dev-", "limit": 2}))
+            .await
+            .unwrap();
+        let names: Vec<&str> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["dev-team", "dev-tools"]);
     }
 }
@@ -0,0 +1,268 @@
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::config::OutputConfig;
+use crate::error::{McpError, McpResult};
+use crate::slack::types::SlackUser;
+
+/// `timezone` value that renders each message in its author's own timezone
+const AUTHOR_ZONE: &str = "user";
+
+/// Timezone used to display message times
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisplayZone {
+    Fixed(Tz),
+    /// The message author's Slack timezone, falling back to the contained zone
+    Author(Tz),
+}
+
+/// How message timestamps are rendered
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeDisplay {
+    zone: DisplayZone,
+    relative: bool,
+    now: DateTime<Utc>,
+}
+
+impl Default for TimeDisplay {
+    fn default() -> Self {
+        Self {
+            zone: DisplayZone::Fixed(Tz::UTC),
+            relative: false,
+            now: Utc::now(),
+        }
+    }
+}
+
+impl TimeDisplay {
+    /// Build from per-call overrides, falling back to the configured defaults.
+    ///
+    /// `timezone` is an IANA name ("Asia/Seoul", "UTC") or `"user"` for each
+    /// author's own timezone.
+    pub fn new(
+        timezone: Option<&str>,
+        relative: Option<bool>,
+        defaults: &OutputConfig,
+    ) -> McpResult<Self> {
+        let fallback = parse_tz(&defaults.timezone).unwrap_or(Tz::UTC);
+        let zone = match timezone.unwrap_or(&defaults.timezone) {
+            AUTHOR_ZONE => DisplayZone::Author(fallback),
+            name => DisplayZone::Fixed(parse_tz(name).ok_or_else(|| {
+                McpError::InvalidParameter(format!(
+                    "Unknown timezone '{}'. Use an IANA name like 'Asia/Seoul' or 'user'",
+                    name
+                ))
+            })?),
+        };
+
+        Ok(Self {
+            zone,
+            relative: relative.unwrap_or(defaults.relative_times),
+            now: Utc::now(),
+        })
+    }
+
+    /// Render a Slack timestamp as RFC 3339 in the display timezone
+    pub fn datetime(&self, ts: &str, author: Option<&SlackUser>) -> Option<String> {
+        let dt = parse_slack_ts(ts)?;
+        let rendered = match self.zone {
+            DisplayZone::Fixed(tz) => dt.with_timezone(&tz).to_rfc3339(),
+            DisplayZone::Author(fallback) => match author.and_then(user_zone) {
+                Some(UserZone::Named(tz)) => dt.with_timezone(&tz).to_rfc3339(),
+                Some(UserZone::Offset(offset)) => dt.with_timezone(&offset).to_rfc3339(),
+                None => dt.with_timezone(&fallback).to_rfc3339(),
+            },
+        };
+        Some(rendered)
+    }
+
+    /// Render a Slack timestamp relative to now ("3h ago"), when enabled
+    pub fn relative(&self, ts: &str) -> Option<String> {
+        if !self.relative {
+            return None;
+        }
+        let dt = parse_slack_ts(ts)?;
+        Some(relative_time(
+            self.now.signed_duration_since(dt).num_seconds(),
+        ))
+    }
+}
+
+enum UserZone {
+    Named(Tz),
+    Offset(FixedOffset),
+}
+
+fn user_zone(user: &SlackUser) -> Option<UserZone> {
+    user.tz
+        .as_deref()
+        .and_then(parse_tz)
+        .map(UserZone::Named)
+        .or_else(|| {
+            user.tz_offset
+                .and_then(FixedOffset::east_opt)
+                .map(UserZone::Offset)
+        })
+}
+
+fn parse_tz(name: &str) -> Option<Tz> {
+    name.parse().ok()
+}
+
+/// Parse a Slack timestamp ("1234567890.123456": Unix seconds with microseconds)
+pub fn parse_slack_ts(ts: &str) -> Option<DateTime<Utc>> {
+    ts.parse::<f64>().ok().and_then(|timestamp| {
+        let seconds = timestamp as i64;
+        let nanos = ((timestamp - seconds as f64) * 1_000_000_000.0) as u32;
+        Utc.timestamp_opt(seconds, nanos).single()
+    })
+}
+
+fn relative_time(seconds: i64) -> String {
+    let magnitude = seconds.unsigned_abs();
+    let amount = match magnitude {
+        0..60 => return "just now".to_string(),
+        60..3_600 => format!("{}m", magnitude / 60),
+        3_600..86_400 => format!("{}h", magnitude / 3_600),
+        86_400..2_592_000 => format!("{}d", magnitude / 86_400),
+        2_592_000..31_536_000 => format!("{}mo", magnitude / 2_592_000),
+        _ => format!("{}y", magnitude / 31_536_000),
+    };
+    if seconds < 0 {
+        format!("in {}", amount)
+    } else {
+        format!("{} ago", amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn defaults(timezone: &str, relative_times: bool) -> OutputConfig {
+        OutputConfig {
+            format: Default::default(),
+            timezone: timezone.to_string(),
+            relative_times,
+        }
+    }
+
+    fn user_with_tz(tz: Option<&str>, tz_offset: Option<i32>) -> SlackUser {
+        SlackUser {
+            id: "U1".to_string(),
+            name: "alice".to_string(),
+            is_bot: false,
+            is_admin: false,
+            deleted: false,
+            tz: tz.map(str::to_string),
+            tz_offset,
+            profile: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_slack_ts_valid() {
+        let dt = parse_slack_ts("1609459200.000000").unwrap();
+        assert_eq!(dt.to_rfc3339(), "2021-01-01T00:00:00+00:00");
+    }
+
+    #[test]
+    fn test_parse_slack_ts_with_microseconds() {
+        let dt = parse_slack_ts("1609459200.123456").unwrap();
+        assert!(dt.to_rfc3339().starts_with("2021-01-01T00:00:00.123"));
+    }
+
+    #[rstest]
+    #[case("invalid")]
+    #[case("")]
+    fn test_parse_slack_ts_invalid(#[case] ts: &str) {
+        assert!(parse_slack_ts(ts).is_none());
+    }
+
+    #[test]
+    fn test_default_display_is_utc() {
+        let display = TimeDisplay::default();
+        assert_eq!(
+            display.datetime("1609459200.000000", None).unwrap(),
+            "2021-01-01T00:00:00+00:00"
+        );
+        assert!(display.relative("1609459200.000000").is_none());
+    }
+
+    #[test]
+    fn test_configured_timezone() {
+        let display = TimeDisplay::new(None, None, &defaults("Asia/Seoul", false)).unwrap();
+        assert_eq!(
+            display.datetime("1609459200.000000", None).unwrap(),
+            "2021-01-01T09:00:00+09:00"
+        );
+    }
+
+    #[test]
+    fn test_per_call_timezone_overrides_config() {
+        let display = TimeDisplay::new(
+            Some("America/New_York"),
+            None,
+            &defaults("Asia/Seoul", false),
+        )
+        .unwrap();
+        assert_eq!(
+            display.datetime("1609459200.000000", None).unwrap(),
+            "2020-12-31T19:00:00-05:00"
+        );
+    }
+
+    #[test]
+    fn test_unknown_timezone_is_rejected() {
+        let result = TimeDisplay::new(Some("Mars/Olympus"), None, &defaults("UTC", false));
+        assert!(matches!(result, Err(McpError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_author_timezone() {
+        let display = TimeDisplay::new(Some("user"), None, &defaults("UTC", false)).unwrap();
+        let seoul = user_with_tz(Some("Asia/Seoul"), Some(32_400));
+        let offset_only = user_with_tz(None, Some(-18_000));
+
+        assert_eq!(
+            display.datetime("1609459200.000000", Some(&seoul)).unwrap(),
+            "2021-01-01T09:00:00+09:00"
+        );
+        assert_eq!(
+            display
+                .datetime("1609459200.000000", Some(&offset_only))
+                .unwrap(),
+            "2020-12-31T19:00:00-05:00"
+        );
+        // Unknown authors fall back to the configured zone
+        assert_eq!(
+            display.datetime("1609459200.000000", None).unwrap(),
+            "2021-01-01T00:00:00+00:00"
+        );
+    }
+
+    #[rstest]
+    #[case(0, "just now")]
+    #[case(59, "just now")]
+    #[case(90, "1m ago")]
+    #[case(3 * 3_600 + 5, "3h ago")]
+    #[case(2 * 86_400, "2d ago")]
+    #[case(65 * 86_400, "2mo ago")]
+    #[case(800 * 86_400, "2y ago")]
+    #[case(-7_200, "in 2h")]
+    fn test_relative_time(#[case] seconds: i64, #[case] expected: &str) {
+        assert_eq!(relative_time(seconds), expected);
+    }
+
+    #[test]
+    fn test_relative_uses_now() {
+        let mut display = TimeDisplay::new(None, Some(true), &defaults("UTC", false)).unwrap();
+        display.now = parse_slack_ts("1609459200.000000").unwrap() + chrono::Duration::hours(3);
+
+        assert_eq!(
+            display.relative("1609459200.000000").as_deref(),
+            Some("3h ago")
+        );
+    }
+}
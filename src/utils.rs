@@ -1,11 +1,111 @@
 use crate::cache::SqliteCache;
-use crate::error::{IntoMcpError, McpError, McpResult};
+use crate::error::{Candidate, IntoMcpError, McpError, McpResult};
+use crate::policy::{Access, ChannelPolicy};
 use crate::slack::SlackClient;
+use crate::slack::types::{SlackChannel, SlackUser};
+use regex::Regex;
 use serde::de::DeserializeOwned;
 use serde_json::Value;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 
-const CHANNEL_SEARCH_LIMIT: usize = 1;
+/// Maximum number of "did you mean" suggestions returned on a failed lookup
+const SUGGESTION_LIMIT: usize = 5;
+
+static CHANNEL_ID_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[CGD][A-Z0-9]{6,}$").expect("valid channel ID pattern"));
+
+static USER_ID_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[UW][A-Z0-9]{6,}$").expect("valid user ID pattern"));
+
+/// Whether `identifier` is an ID matching `pattern` rather than a name. Slack IDs
+/// always contain a digit, which tells them apart from all-caps names like `GENERAL`.
+fn is_id(pattern: &Regex, identifier: &str) -> bool {
+    pattern.is_match(identifier) && identifier.bytes().any(|b| b.is_ascii_digit())
+}
+
+/// Whether `identifier` is a channel ID (`C…`, `G…`, `D…`) rather than a name
+pub fn is_channel_id(identifier: &str) -> bool {
+    is_id(&CHANNEL_ID_PATTERN, identifier)
+}
+
+static PERMALINK_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(https?://[^/\s]+)/archives/([CGD][A-Z0-9]+)/p(\d{10})(\d{6})/?(?:\?(\S*))?$")
+        .expect("valid permalink pattern")
+});
+
+/// A Slack message URL split into its parts
+#[derive(Debug, Clone, PartialEq)]
+pub struct Permalink {
+    /// Workspace base URL, e.g. "https://team.slack.com"
+    pub workspace_url: String,
+    pub channel: String,
+    pub ts: String,
+    /// Parent timestamp when the link points at a thread reply
+    pub thread_ts: Option<String>,
+}
+
+impl Permalink {
+    /// Parse a message URL such as
+    /// `https://team.slack.com/archives/C123/p1700000000123456?thread_ts=1700000000.000100`
+    pub fn parse(url: &str) -> McpResult<Self> {
+        let url = url.trim().trim_start_matches('<').trim_end_matches('>');
+        let caps = PERMALINK_PATTERN.captures(url).ok_or_else(|| {
+            McpError::InvalidParameter(format!(
+                "'{}' is not a Slack message link (https://<workspace>.slack.com/archives/<channel>/p<timestamp>)",
+                url
+            ))
+        })?;
+
+        let thread_ts = caps.get(5).and_then(|query| {
+            query
+                .as_str()
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("thread_ts="))
+                .filter(|ts| !ts.is_empty())
+                .map(str::to_string)
+        });
+
+        Ok(Self {
+            workspace_url: caps[1].to_string(),
+            channel: caps[2].to_string(),
+            ts: format!("{}.{}", &caps[3], &caps[4]),
+            thread_ts,
+        })
+    }
+
+    /// Build a message URL the way Slack formats them
+    pub fn build(workspace_url: &str, channel: &str, ts: &str, thread_ts: Option<&str>) -> String {
+        let mut url = format!(
+            "{}/archives/{}/p{}",
+            workspace_url.trim_end_matches('/'),
+            channel,
+            ts.replace('.', "")
+        );
+        if let Some(thread_ts) = thread_ts.filter(|thread_ts| *thread_ts != ts) {
+            url.push_str(&format!("?thread_ts={}&cid={}", thread_ts, channel));
+        }
+        url
+    }
+
+    /// Timestamp of the thread the linked message belongs to (itself when it's not a reply)
+    pub fn thread_root(&self) -> &str {
+        self.thread_ts.as_deref().unwrap_or(&self.ts)
+    }
+}
+
+/// Take a parameter given directly, or else the value carried by a permalink
+pub fn param_or_permalink(
+    value: Option<String>,
+    from_permalink: Option<String>,
+    field_name: &str,
+) -> McpResult<String> {
+    value.or(from_permalink).ok_or_else(|| {
+        McpError::InvalidParameter(format!(
+            "Either {} or permalink must be provided",
+            field_name
+        ))
+    })
+}
 
 /// Parse JSON value into a typed parameter struct
 pub fn parse_params<T: DeserializeOwned>(params: Value) -> McpResult<T> {
@@ -31,35 +131,28 @@ pub fn validate_required_one_of<T, U>(
 /// Resolve channel identifier to channel ID
 /// Supports:
 /// - Channel IDs (C..., G..., D...)
-/// - Channel names (without #)
+/// - Channel names (without #), including archived and renamed channels
 /// - #channel-name format
 /// - @username format (opens DM)
+///
+/// Ambiguous or unknown names fail with `McpError::Ambiguous` listing candidates
+/// rather than guessing.
 pub async fn resolve_channel_id(
     identifier: &str,
     cache: &Arc<SqliteCache>,
     slack_client: Option<&Arc<SlackClient>>,
 ) -> McpResult<String> {
-    // Already a channel ID (starts with C, G, or D)
+    let identifier = identifier.trim();
+
+    // Already a channel ID
     // Return immediately without cache lookup - let the API call handle access validation
-    if identifier.starts_with('C') || identifier.starts_with('G') || identifier.starts_with('D') {
+    if is_channel_id(identifier) {
         return Ok(identifier.to_string());
     }
 
-    // Handle #channel-name format
-    let channel_name = if let Some(stripped) = identifier.strip_prefix('#') {
-        stripped
-    } else if identifier.starts_with('@') && slack_client.is_some() {
-        // Handle @username format - resolve to DM
-        let username = &identifier[1..];
-        let users = cache.get_users().mcp_context("Failed to get users")?;
-
-        let user_id = users
-            .iter()
-            .find(|u| u.name == username || u.display_name() == Some(username))
-            .map(|u| u.id.clone())
-            .ok_or_else(|| {
-                McpError::InvalidParameter(format!("User '{}' not found", identifier))
-            })?;
+    // Handle @username format - resolve to DM
+    if let Some(username) = identifier.strip_prefix('@') {
+        let user_id = resolve_user_id(username, cache)?;
 
         // Open DM channel with user
         let client = slack_client.ok_or_else(|| {
@@ -70,22 +163,186 @@ pub async fn resolve_channel_id(
             .open_conversation(&user_id)
             .await
             .mcp_context("Failed to open DM");
-    } else {
-        identifier
-    };
+    }
+
+    // Handle #channel-name format
+    let channel_name = identifier.strip_prefix('#').unwrap_or(identifier);
+    resolve_channel_name(channel_name, identifier, cache)
+}
+
+/// Resolve a channel identifier like [`resolve_channel_id`], then check the
+/// channel policy grants `access` to it. `@user` opens a DM only when `open_dm`
+/// is set; the client is also used to look up names of uncached channels.
+pub async fn resolve_permitted_channel(
+    identifier: &str,
+    cache: &Arc<SqliteCache>,
+    slack_client: &Arc<SlackClient>,
+    open_dm: bool,
+    policy: &ChannelPolicy,
+    access: Access,
+) -> McpResult<String> {
+    // Don't open a DM the policy would refuse anyway
+    if identifier.trim().starts_with('@') && !policy.allows_dms() {
+        return Err(McpError::PermissionDenied(format!(
+            "Policy does not allow direct messages ({})",
+            identifier.trim()
+        )));
+    }
+
+    let channel_id = resolve_channel_id(identifier, cache, open_dm.then_some(slack_client)).await?;
+    policy
+        .check(&channel_id, cache, Some(slack_client), access)
+        .await?;
+    Ok(channel_id)
+}
+
+/// Resolve a user identifier (ID, username, display name or real name) to a user ID
+pub fn resolve_user_id(identifier: &str, cache: &Arc<SqliteCache>) -> McpResult<String> {
+    let identifier = identifier.trim();
+    let username = identifier.strip_prefix('@').unwrap_or(identifier);
 
-    // Search for channel by name in cache
+    if is_id(&USER_ID_PATTERN, username) {
+        return Ok(username.to_string());
+    }
+
+    let users = cache.get_users().mcp_context("Failed to get users")?;
+    let is = |value: Option<&str>| value.is_some_and(|v| v.eq_ignore_ascii_case(username));
+
+    // Usernames are unique, so an exact username match always wins
+    if let Some(user) = users.iter().find(|u| is(Some(u.name.as_str()))) {
+        return Ok(user.id.clone());
+    }
+
+    let matches: Vec<Candidate> = users
+        .iter()
+        .filter_map(|u| {
+            if is(u.display_name()) {
+                Some(user_candidate(u, "display_name"))
+            } else if is(u.real_name()) {
+                Some(user_candidate(u, "real_name"))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    match matches.len() {
+        1 => Ok(matches[0].id.clone()),
+        0 => {
+            let suggestions: Vec<Candidate> = cache
+                .search_users(username, SUGGESTION_LIMIT, false, false)
+                .mcp_context("Failed to search users")?
+                .iter()
+                .map(|m| user_candidate(m, "similar"))
+                .collect();
+            not_found(format!("User '{}' not found", identifier), suggestions)
+        }
+        _ => Err(ambiguous(
+            format!("User '{}' matches multiple users", identifier),
+            matches,
+        )),
+    }
+}
+
+/// Resolve a channel name to a channel ID; `identifier` is the input as the caller wrote it
+pub fn resolve_channel_name(
+    name: &str,
+    identifier: &str,
+    cache: &Arc<SqliteCache>,
+) -> McpResult<String> {
     let channels = cache
-        .search_channels(channel_name, CHANNEL_SEARCH_LIMIT)
+        .find_channels_by_name(name)
         .mcp_context("Failed to search channels")?;
 
-    if !channels.is_empty() && channels[0].name == channel_name {
-        Ok(channels[0].id.clone())
+    // An active channel with this exact name always wins
+    if let Some(channel) = channels
+        .iter()
+        .find(|c| !c.is_archived && c.name.eq_ignore_ascii_case(name))
+    {
+        return Ok(channel.id.clone());
+    }
+
+    // Otherwise only resolve archived or renamed channels when there is a single one
+    let matches: Vec<Candidate> = channels
+        .iter()
+        .map(|c| {
+            let reason = if !c.name.eq_ignore_ascii_case(name) {
+                "previous_name"
+            } else {
+                "archived"
+            };
+            channel_candidate(c, reason)
+        })
+        .collect();
+
+    match matches.len() {
+        1 => Ok(matches[0].id.clone()),
+        0 => {
+            let suggestions: Vec<Candidate> = cache
+                .search_channels(name, SUGGESTION_LIMIT, true)
+                .mcp_context("Failed to search channels")?
+                .iter()
+                .map(|m| channel_candidate(m, "similar"))
+                .collect();
+            not_found(format!("Channel '{}' not found", identifier), suggestions)
+        }
+        _ => Err(ambiguous(
+            format!("Channel '{}' matches multiple channels", identifier),
+            matches,
+        )),
+    }
+}
+
+fn user_candidate(user: &SlackUser, reason: &'static str) -> Candidate {
+    Candidate {
+        id: user.id.clone(),
+        name: format!("@{}", user.name),
+        detail: user
+            .real_name()
+            .or(user.display_name())
+            .filter(|n| !n.is_empty())
+            .map(str::to_string),
+        reason,
+    }
+}
+
+fn channel_candidate(channel: &SlackChannel, reason: &'static str) -> Candidate {
+    let detail = match reason {
+        "archived" => Some("archived".to_string()),
+        "previous_name" if channel.is_archived => Some("renamed, archived".to_string()),
+        "previous_name" => Some("renamed".to_string()),
+        _ if channel.is_archived => Some("archived".to_string()),
+        _ => None,
+    };
+
+    Candidate {
+        id: channel.id.clone(),
+        name: format!("#{}", channel.name),
+        detail,
+        reason,
+    }
+}
+
+fn ambiguous(message: String, candidates: Vec<Candidate>) -> McpError {
+    let names: Vec<String> = candidates
+        .iter()
+        .map(|c| match &c.detail {
+            Some(detail) => format!("{} ({}, {})", c.name, c.id, detail),
+            None => format!("{} ({})", c.name, c.id),
+        })
+        .collect();
+
+    McpError::Ambiguous {
+        message: format!("{}. Did you mean: {}?", message, names.join(", ")),
+        candidates,
+    }
+}
+
+fn not_found<T>(message: String, suggestions: Vec<Candidate>) -> McpResult<T> {
+    if suggestions.is_empty() {
+        Err(McpError::InvalidParameter(message))
     } else {
-        Err(McpError::InvalidParameter(format!(
-            "Channel '{}' not found",
-            identifier
-        )))
+        Err(ambiguous(message, suggestions))
     }
 }
 
@@ -94,9 +351,100 @@ mod tests {
     use super::*;
     use crate::cache::SqliteCache;
     use crate::slack::types::{SlackChannel, SlackUser, SlackUserProfile};
+    use rstest::rstest;
     use serde::{Deserialize, Serialize};
     use serde_json::json;
 
+    #[rstest]
+    #[case(
+        "https://team.slack.com/archives/C0123ABCD/p1700000000123456",
+        "C0123ABCD",
+        "1700000000.123456",
+        None
+    )]
+    #[case(
+        "<https://team.slack.com/archives/C0123ABCD/p1700000000123456?thread_ts=1699999999.000100&cid=C0123ABCD>",
+        "C0123ABCD",
+        "1700000000.123456",
+        Some("1699999999.000100")
+    )]
+    #[case(
+        " https://acme.enterprise.slack.com/archives/G0123ABCD/p1700000000123456/ ",
+        "G0123ABCD",
+        "1700000000.123456",
+        None
+    )]
+    fn test_permalink_parse(
+        #[case] url: &str,
+        #[case] channel: &str,
+        #[case] ts: &str,
+        #[case] thread_ts: Option<&str>,
+    ) {
+        let link = Permalink::parse(url).unwrap();
+        assert!(link.workspace_url.ends_with(".slack.com"));
+        assert_eq!(link.channel, channel);
+        assert_eq!(link.ts, ts);
+        assert_eq!(link.thread_ts.as_deref(), thread_ts);
+        assert_eq!(link.thread_root(), thread_ts.unwrap_or(ts));
+    }
+
+    #[rstest]
+    #[case("https://team.slack.com/archives/C0123ABCD")]
+    #[case("https://example.com/p1700000000123456")]
+    #[case("C0123ABCD")]
+    fn test_permalink_parse_rejects_other_urls(#[case] url: &str) {
+        assert!(matches!(
+            Permalink::parse(url),
+            Err(McpError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_permalink_build_round_trip() {
+        let reply = Permalink::build(
+            "https://team.slack.com/",
+            "C0123ABCD",
+            "1700000000.123456",
+            Some("1699999999.000100"),
+        );
+        assert_eq!(
+            reply,
+            "https://team.slack.com/archives/C0123ABCD/p1700000000123456?thread_ts=1699999999.000100&cid=C0123ABCD"
+        );
+        assert_eq!(
+            Permalink::parse(&reply).unwrap().thread_ts.as_deref(),
+            Some("1699999999.000100")
+        );
+
+        // A thread parent links without thread_ts
+        let parent = Permalink::build(
+            "https://team.slack.com",
+            "C0123ABCD",
+            "1700000000.123456",
+            Some("1700000000.123456"),
+        );
+        assert_eq!(
+            parent,
+            "https://team.slack.com/archives/C0123ABCD/p1700000000123456"
+        );
+    }
+
+    #[test]
+    fn test_param_or_permalink() {
+        assert_eq!(
+            param_or_permalink(Some("a".to_string()), Some("b".to_string()), "channel").unwrap(),
+            "a"
+        );
+        assert_eq!(
+            param_or_permalink(None, Some("b".to_string()), "channel").unwrap(),
+            "b"
+        );
+        assert!(matches!(
+            param_or_permalink(None, None, "channel"),
+            Err(McpError::InvalidParameter(_))
+        ));
+    }
+
     #[derive(Debug, Deserialize, Serialize, PartialEq)]
     struct TestParams {
         name: String,
@@ -111,7 +459,6 @@ mod tests {
         )
     }
 
-    #[allow(dead_code)]
     fn create_test_user(id: &str, name: &str, display_name: Option<&str>) -> SlackUser {
         SlackUser {
             id: id.to_string(),
@@ -119,6 +466,8 @@ mod tests {
             is_bot: false,
             is_admin: false,
             deleted: false,
+            tz: None,
+            tz_offset: None,
             profile: Some(SlackUserProfile {
                 real_name: Some(name.to_string()),
                 display_name: display_name.map(|s| s.to_string()),
@@ -145,6 +494,7 @@ mod tests {
             topic: None,
             purpose: None,
             num_members: Some(10),
+            previous_names: vec![],
         }
     }
 
@@ -217,6 +567,18 @@ mod tests {
         assert!(err.to_string().contains("field1 or field2"));
     }
 
+    #[rstest]
+    #[case("C1234567890", true)]
+    #[case("G0123ABCD", true)]
+    #[case("D0123ABCD", true)]
+    #[case("CUSTOMERS", false)]
+    #[case("GENERAL", false)]
+    #[case("general", false)]
+    #[case("U0123ABCD", false)]
+    fn test_is_channel_id(#[case] identifier: &str, #[case] expected: bool) {
+        assert_eq!(is_channel_id(identifier), expected);
+    }
+
     #[tokio::test]
     async fn test_resolve_channel_id_with_channel_id() {
         let cache = setup_cache().await;
@@ -248,6 +610,29 @@ mod tests {
         assert_eq!(result.unwrap(), "C123");
     }
 
+    #[rstest]
+    #[case("GENERAL", "C0000001")]
+    #[case("CHANGELOG", "C0000002")]
+    #[case("#DEPLOYS", "G0000003")]
+    #[tokio::test]
+    async fn test_resolve_all_caps_channel_name(#[case] input: &str, #[case] expected: &str) {
+        let cache = setup_cache().await;
+        cache
+            .save_channels(vec![
+                create_test_channel("C0000001", "general"),
+                create_test_channel("C0000002", "changelog"),
+                create_test_channel("G0000003", "deploys"),
+            ])
+            .await
+            .unwrap();
+
+        // Shaped like IDs, but without a digit they can only be names
+        assert_eq!(
+            resolve_channel_id(input, &cache, None).await.unwrap(),
+            expected
+        );
+    }
+
     #[tokio::test]
     async fn test_resolve_channel_id_with_hash_prefix() {
         let cache = setup_cache().await;
@@ -284,4 +669,206 @@ mod tests {
                 .contains("Channel '#missing' not found")
         );
     }
+
+    fn create_archived_channel(id: &str, name: &str) -> SlackChannel {
+        SlackChannel {
+            is_archived: true,
+            ..create_test_channel(id, name)
+        }
+    }
+
+    fn create_renamed_channel(id: &str, name: &str, previous: &str) -> SlackChannel {
+        SlackChannel {
+            previous_names: vec![previous.to_string()],
+            ..create_test_channel(id, name)
+        }
+    }
+
+    fn candidate_ids(err: &McpError) -> Vec<&str> {
+        match err {
+            McpError::Ambiguous { candidates, .. } => {
+                candidates.iter().map(|c| c.id.as_str()).collect()
+            }
+            other => panic!("Expected ambiguous error, got {:?}", other),
+        }
+    }
+
+    #[rstest]
+    #[case("design")]
+    #[case("growth")]
+    #[case("dev-team")]
+    #[case("#growth")]
+    #[tokio::test]
+    async fn test_resolve_channel_id_names_starting_with_id_prefix(#[case] identifier: &str) {
+        let cache = setup_cache().await;
+        cache
+            .save_channels(vec![
+                create_test_channel("C0001", "design"),
+                create_test_channel("C0002", "growth"),
+                create_test_channel("C0003", "dev-team"),
+            ])
+            .await
+            .unwrap();
+
+        let result = resolve_channel_id(identifier, &cache, None).await;
+        assert!(result.unwrap().starts_with("C000"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_channel_id_prefers_active_over_archived() {
+        let cache = setup_cache().await;
+        cache
+            .save_channels(vec![
+                create_archived_channel("C0001", "design"),
+                create_test_channel("C0002", "design"),
+            ])
+            .await
+            .unwrap();
+
+        let result = resolve_channel_id("design", &cache, None).await;
+        assert_eq!(result.unwrap(), "C0002");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_channel_id_archived_channel() {
+        let cache = setup_cache().await;
+        cache
+            .save_channels(vec![create_archived_channel("C0001", "old-project")])
+            .await
+            .unwrap();
+
+        let result = resolve_channel_id("#old-project", &cache, None).await;
+        assert_eq!(result.unwrap(), "C0001");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_channel_id_renamed_channel() {
+        let cache = setup_cache().await;
+        cache
+            .save_channels(vec![create_renamed_channel(
+                "C0001",
+                "product-design",
+                "design",
+            )])
+            .await
+            .unwrap();
+
+        let result = resolve_channel_id("#design", &cache, None).await;
+        assert_eq!(result.unwrap(), "C0001");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_channel_id_ambiguous_rename_and_archive() {
+        let cache = setup_cache().await;
+        cache
+            .save_channels(vec![
+                create_archived_channel("C0001", "design"),
+                create_renamed_channel("C0002", "product-design", "design"),
+            ])
+            .await
+            .unwrap();
+
+        let err = resolve_channel_id("design", &cache, None)
+            .await
+            .unwrap_err();
+        let ids = candidate_ids(&err);
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&"C0001"));
+        assert!(ids.contains(&"C0002"));
+        assert!(err.to_string().contains("matches multiple channels"));
+        assert!(err.to_string().contains("Did you mean"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_channel_id_suggests_similar_channels() {
+        let cache = setup_cache().await;
+        cache
+            .save_channels(vec![
+                create_test_channel("C0001", "engineering-platform"),
+                create_test_channel("C0002", "random"),
+            ])
+            .await
+            .unwrap();
+
+        let err = resolve_channel_id("#eng-plat", &cache, None)
+            .await
+            .unwrap_err();
+        assert_eq!(candidate_ids(&err), vec!["C0001"]);
+        let message = err.to_string();
+        assert!(message.contains("Channel '#eng-plat' not found"));
+        assert!(message.contains("#engineering-platform"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_user_id_by_name_and_display_name() {
+        let cache = setup_cache().await;
+        cache
+            .save_users(vec![
+                create_test_user("U0001", "alice", Some("Ali")),
+                create_test_user("U0002", "bob", None),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(resolve_user_id("alice", &cache).unwrap(), "U0001");
+        assert_eq!(resolve_user_id("@Ali", &cache).unwrap(), "U0001");
+        assert_eq!(resolve_user_id("U0002ABC", &cache).unwrap(), "U0002ABC");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_all_caps_user_name() {
+        let cache = setup_cache().await;
+        cache
+            .save_users(vec![create_test_user("U0001", "walter", Some("WALTERW"))])
+            .await
+            .unwrap();
+
+        assert_eq!(resolve_user_id("@WALTERW", &cache).unwrap(), "U0001");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_user_id_ambiguous_display_name() {
+        let cache = setup_cache().await;
+        cache
+            .save_users(vec![
+                create_test_user("U0001", "john.smith", Some("john")),
+                create_test_user("U0002", "john.doe", Some("John")),
+            ])
+            .await
+            .unwrap();
+
+        let err = resolve_user_id("@john", &cache).unwrap_err();
+        assert_eq!(candidate_ids(&err), vec!["U0002", "U0001"]);
+        assert!(err.to_string().contains("matches multiple users"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_user_id_not_found() {
+        let cache = setup_cache().await;
+        cache
+            .save_users(vec![create_test_user("U0001", "alice", None)])
+            .await
+            .unwrap();
+
+        let err = resolve_user_id("zed", &cache).unwrap_err();
+        assert!(matches!(err, McpError::InvalidParameter(_)));
+        assert!(err.to_string().contains("User 'zed' not found"));
+
+        let err = resolve_user_id("alcie", &cache).unwrap_err();
+        assert_eq!(candidate_ids(&err), vec!["U0001"]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_channel_id_dm_requires_client() {
+        let cache = setup_cache().await;
+        cache
+            .save_users(vec![create_test_user("U0001", "alice", None)])
+            .await
+            .unwrap();
+
+        let err = resolve_channel_id("@alice", &cache, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Slack client required"));
+    }
 }